@@ -46,6 +46,7 @@ mod spaces;
 mod stories;
 mod stream;
 mod super_invites;
+mod task_import;
 mod tasks;
 mod typing;
 mod utils;
@@ -61,11 +62,17 @@ pub use uniffi_api::*;
 pub use account::{Account, ExternalId, ThreePidEmailTokenResponse};
 pub use acter_core::{
     events::{
-        calendar::EventLocationInfo, news::NewsContent, stories::StoryContent, Category,
-        CategoryBuilder, Colorize, ColorizeBuilder, Display, DisplayBuilder, ObjRefBuilder,
-        UtcDateTime,
+        calendar::EventLocationInfo,
+        news::NewsContent,
+        stories::StoryContent,
+        tasks::{ChecklistItem, TaskState},
+        Category, CategoryBuilder, Colorize, ColorizeBuilder, Display, DisplayBuilder,
+        ObjRefBuilder, UtcDateTime,
+    },
+    models::{
+        ActerModel, BurndownDay, Tag, TaskFieldChange, TaskHistoryEntry, TaskTimeLog,
+        TextMessageContent,
     },
-    models::{ActerModel, Tag, TextMessageContent},
 };
 pub use attachments::{Attachment, AttachmentDraft, AttachmentsManager};
 pub use auth::{
@@ -80,7 +87,10 @@ pub use auth::{
 };
 pub use backup::BackupManager;
 pub use bookmarks::Bookmarks;
-pub use calendar_events::{CalendarEvent, CalendarEventDraft, CalendarEventUpdateBuilder};
+pub use calendar_events::{
+    CalendarEvent, CalendarEventDraft, CalendarEventOccurrence, CalendarEventUpdateBuilder,
+    CalendarImportReport,
+};
 pub use categories::{Categories, CategoriesBuilder};
 pub use client::{Client, ClientStateBuilder, HistoryLoadState, SyncState};
 pub use comments::{Comment, CommentDraft, CommentsManager};
@@ -128,8 +138,10 @@ pub use stream::{MsgDraft, RoomMessageDiff, TimelineStream};
 pub use super_invites::{
     SuperInviteInfo, SuperInviteToken, SuperInvites, SuperInvitesTokenUpdateBuilder,
 };
+pub use task_import::{TaskImportProgress, TaskImporter};
 pub use tasks::{
-    Task, TaskDraft, TaskList, TaskListDraft, TaskListUpdateBuilder, TaskUpdateBuilder,
+    Task, TaskColumn, TaskDraft, TaskList, TaskListDraft, TaskListUpdateBuilder, TaskQuery,
+    TaskUpdateBuilder,
 };
 pub use typing::TypingEvent;
 pub use utils::parse_markdown;
@@ -2,17 +2,18 @@ use acter_core::{
     events::{
         calendar::{
             self as calendar_events, CalendarEventBuilder, EventLocation, EventLocationInfo,
+            EventStatus, OccurrenceOverride,
         },
         rsvp::RsvpStatus,
-        CalendarEventRefPreview, RefDetails as CoreRefDetails, UtcDateTime,
+        CalendarEventRefPreview, RecurrenceRule, RefDetails as CoreRefDetails, UtcDateTime,
     },
-    models::{self, can_redact, ActerModel, AnyActerModel},
+    models::{self, can_redact, ActerModel, AnyActerModel, CalendarImport},
     statics::KEYS,
 };
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, Result};
 use chrono::DateTime;
+use chrono_tz::Tz;
 use futures::stream::StreamExt;
-use icalendar::Calendar as iCalendar;
 use matrix_sdk::room::Room;
 use matrix_sdk_base::{
     ruma::{
@@ -100,6 +101,26 @@ impl Client {
             })
             .await?
     }
+
+    /// The occurrences of all calendar events overlapping the window between
+    /// the given times in rfc3339 format, ordered by their start
+    pub async fn calendar_event_occurrences(
+        &self,
+        start: String,
+        end: String,
+    ) -> Result<Vec<CalendarEventOccurrence>> {
+        let start: UtcDateTime = DateTime::parse_from_rfc3339(&start)?.into();
+        let end: UtcDateTime = DateTime::parse_from_rfc3339(&end)?.into();
+        let calendar_events = self.calendar_events().await?;
+        Ok(occurrences_between(calendar_events, start, end))
+    }
+
+    /// Write the calendar events of all my spaces as one iCalendar feed to the
+    /// file, for desktop calendars to subscribe to
+    pub async fn ical_feed(&self, file_name: String) -> Result<bool> {
+        let calendar_events = self.calendar_events().await?;
+        write_ical_feed(self.clone(), calendar_events, file_name).await
+    }
 }
 
 impl Space {
@@ -129,6 +150,71 @@ impl Space {
             })
             .await?
     }
+
+    /// The occurrences of the calendar events of this space overlapping the
+    /// window between the given times in rfc3339 format, ordered by their start
+    pub async fn calendar_event_occurrences(
+        &self,
+        start: String,
+        end: String,
+    ) -> Result<Vec<CalendarEventOccurrence>> {
+        let start: UtcDateTime = DateTime::parse_from_rfc3339(&start)?.into();
+        let end: UtcDateTime = DateTime::parse_from_rfc3339(&end)?.into();
+        let calendar_events = self.calendar_events().await?;
+        Ok(occurrences_between(calendar_events, start, end))
+    }
+
+    /// Write the calendar events of this space as one iCalendar feed to the
+    /// file, for desktop calendars to subscribe to
+    pub async fn ical_feed(&self, file_name: String) -> Result<bool> {
+        let calendar_events = self.calendar_events().await?;
+        write_ical_feed(self.client.clone(), calendar_events, file_name).await
+    }
+}
+
+/// With my RSVP on every event, written again as a whole on every export
+async fn write_ical_feed(
+    client: Client,
+    calendar_events: Vec<CalendarEvent>,
+    file_name: String,
+) -> Result<bool> {
+    let my_id = client.user_id()?;
+    RUNTIME
+        .spawn(async move {
+            let calendar_events: Vec<models::CalendarEvent> =
+                calendar_events.into_iter().map(|e| e.inner).collect();
+            let ical_data = models::ical_feed(client.store(), &calendar_events, &my_id).await?;
+            std::fs::write(file_name, ical_data)?;
+            Ok(true)
+        })
+        .await?
+}
+
+fn occurrences_between(
+    calendar_events: Vec<CalendarEvent>,
+    start: UtcDateTime,
+    end: UtcDateTime,
+) -> Vec<CalendarEventOccurrence> {
+    let mut occurrences: Vec<CalendarEventOccurrence> = calendar_events
+        .into_iter()
+        .flat_map(|calendar_event| {
+            let occurrences = calendar_event.inner.occurrences_between(start, end);
+            occurrences
+                .into_iter()
+                .map(move |inner| CalendarEventOccurrence {
+                    calendar_event: calendar_event.clone(),
+                    inner,
+                })
+        })
+        .collect();
+    occurrences.sort_by_key(|o| o.inner.utc_start());
+    occurrences
+}
+
+fn parse_time_zone(time_zone: Option<String>) -> Result<Option<Tz>> {
+    time_zone
+        .map(|tz| tz.parse::<Tz>().map_err(|e| anyhow!(e)))
+        .transpose()
 }
 
 #[derive(Clone, Debug)]
@@ -320,8 +406,128 @@ impl CalendarEvent {
             .await?
     }
 
+    /// How many people can attend, `None` if there is no limit
+    pub fn capacity(&self) -> Option<u32> {
+        self.inner.capacity()
+    }
+
+    /// One of confirmed, tentative, cancelled or postponed
+    pub fn status(&self) -> String {
+        self.inner.status().to_string()
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.is_cancelled()
+    }
+
+    /// Whether I said yes and got a seat
+    pub async fn am_i_confirmed(&self) -> Result<bool> {
+        Ok(self.my_seat().await? == Some(models::RsvpSeat::Confirmed))
+    }
+
+    /// Whether I said yes after the event was full
+    pub async fn am_i_waitlisted(&self) -> Result<bool> {
+        Ok(self.my_seat().await? == Some(models::RsvpSeat::Waitlisted))
+    }
+
+    async fn my_seat(&self) -> Result<Option<models::RsvpSeat>> {
+        let me = self.clone();
+        let my_id = self.client.user_id()?;
+        RUNTIME
+            .spawn(async move {
+                let manager = me.rsvps().await?;
+                let attendance = manager.attendance().await?;
+                Ok(attendance.seat_of(&my_id))
+            })
+            .await?
+    }
+
+    /// The IANA time zone the event was planned in
+    pub fn time_zone(&self) -> Option<String> {
+        self.inner.time_zone().map(|tz| tz.to_string())
+    }
+
+    /// When the event starts in rfc3339 format, in the given time zone or
+    /// else in the one it was planned in
+    pub fn start_in_time_zone(&self, time_zone: Option<String>) -> Result<String> {
+        let tz = parse_time_zone(time_zone)?;
+        Ok(self.inner.start_in(tz).to_rfc3339())
+    }
+
+    /// When the event ends in rfc3339 format, in the given time zone or else
+    /// in the one it was planned in
+    pub fn end_in_time_zone(&self, time_zone: Option<String>) -> Result<String> {
+        let tz = parse_time_zone(time_zone)?;
+        Ok(self.inner.end_in(tz).to_rfc3339())
+    }
+
+    pub fn is_recurring(&self) -> bool {
+        self.inner.is_recurring()
+    }
+
+    pub fn recurrence(&self) -> Option<String> {
+        self.inner.recurrence.clone()
+    }
+
+    /// The occurrences of this event overlapping the window between the given
+    /// times in rfc3339 format
+    pub fn occurrences_between(
+        &self,
+        start: String,
+        end: String,
+    ) -> Result<Vec<CalendarEventOccurrence>> {
+        let start: UtcDateTime = DateTime::parse_from_rfc3339(&start)?.into();
+        let end: UtcDateTime = DateTime::parse_from_rfc3339(&end)?.into();
+        Ok(occurrences_between(vec![self.clone()], start, end))
+    }
+
+    /// Cancel the single occurrence originally starting at `recurrence_id`
+    pub async fn cancel_occurrence(&self, recurrence_id: String) -> Result<OwnedEventId> {
+        let recurrence_id: UtcDateTime = DateTime::parse_from_rfc3339(&recurrence_id)?.into();
+        self.override_occurrence(OccurrenceOverride {
+            recurrence_id,
+            utc_start: None,
+            utc_end: None,
+            title: None,
+            cancelled: true,
+        })
+        .await
+    }
+
+    /// Move the single occurrence originally starting at `recurrence_id`
+    pub async fn move_occurrence(
+        &self,
+        recurrence_id: String,
+        utc_start: String,
+        utc_end: String,
+    ) -> Result<OwnedEventId> {
+        let recurrence_id: UtcDateTime = DateTime::parse_from_rfc3339(&recurrence_id)?.into();
+        let utc_start: UtcDateTime = DateTime::parse_from_rfc3339(&utc_start)?.into();
+        let utc_end: UtcDateTime = DateTime::parse_from_rfc3339(&utc_end)?.into();
+        self.override_occurrence(OccurrenceOverride {
+            recurrence_id,
+            utc_start: Some(utc_start),
+            utc_end: Some(utc_end),
+            title: None,
+            cancelled: false,
+        })
+        .await
+    }
+
+    async fn override_occurrence(&self, change: OccurrenceOverride) -> Result<OwnedEventId> {
+        if !self.inner.is_recurring() {
+            bail!("Only occurrences of recurring events can be changed");
+        }
+        let mut overrides = self.inner.occurrence_overrides.clone();
+        overrides.retain(|o| o.recurrence_id != change.recurrence_id);
+        overrides.push(change);
+        let mut builder = self.update_builder()?;
+        builder.inner.occurrence_overrides(Some(overrides));
+        builder.send().await
+    }
+
     pub fn ical_for_sharing(&self, file_name: String) -> Result<bool> {
-        let ical_data: String = (&iCalendar::from([self.inner.as_ical_event()])).try_into()?;
+        let ical_data = models::ical_calendar(&[self.inner.clone()]);
         std::fs::write(file_name, ical_data)?;
         Ok(true)
     }
@@ -359,6 +565,27 @@ impl CalendarEvent {
     }
 }
 
+/// A single instance of a calendar event
+#[derive(Clone, Debug)]
+pub struct CalendarEventOccurrence {
+    calendar_event: CalendarEvent,
+    inner: models::CalendarEventOccurrence,
+}
+
+impl Deref for CalendarEventOccurrence {
+    type Target = models::CalendarEventOccurrence;
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl CalendarEventOccurrence {
+    /// The event this is an occurrence of
+    pub fn calendar_event(&self) -> CalendarEvent {
+        self.calendar_event.clone()
+    }
+}
+
 #[derive(Clone)]
 pub struct CalendarEventDraft {
     client: Client,
@@ -425,6 +652,50 @@ impl CalendarEventDraft {
         Ok(())
     }
 
+    pub fn time_zone(&mut self, time_zone: String) -> Result<()> {
+        let time_zone = time_zone.parse::<Tz>().map_err(|e| anyhow!(e))?;
+        self.inner.time_zone(Some(time_zone.name().to_owned()));
+        Ok(())
+    }
+
+    pub fn unset_time_zone(&mut self) -> &mut Self {
+        self.inner.time_zone(None);
+        self
+    }
+
+    pub fn capacity(&mut self, capacity: u32) -> &mut Self {
+        self.inner.capacity(Some(capacity));
+        self
+    }
+
+    pub fn unset_capacity(&mut self) -> &mut Self {
+        self.inner.capacity(None);
+        self
+    }
+
+    pub fn recurrence(&mut self, rrule: String) -> Result<()> {
+        let rule = rrule.parse::<RecurrenceRule>().map_err(|e| anyhow!(e))?;
+        self.inner.recurrence(Some(rule.to_string()));
+        Ok(())
+    }
+
+    pub fn unset_recurrence(&mut self) -> &mut Self {
+        self.inner.recurrence(None);
+        self
+    }
+
+    pub fn add_recurrence_date_from_rfc3339(&mut self, date: String) -> Result<()> {
+        let dt: UtcDateTime = DateTime::parse_from_rfc3339(&date)?.into();
+        self.inner.add_recurrence_date(dt);
+        Ok(())
+    }
+
+    pub fn add_excluded_date_from_rfc3339(&mut self, date: String) -> Result<()> {
+        let dt: UtcDateTime = DateTime::parse_from_rfc3339(&date)?.into();
+        self.inner.add_excluded_date(dt);
+        Ok(())
+    }
+
     pub fn physical_location(
         &mut self,
         name: Option<String>,
@@ -594,6 +865,65 @@ impl CalendarEventUpdateBuilder {
         self
     }
 
+    pub fn time_zone(&mut self, time_zone: String) -> Result<()> {
+        let time_zone = time_zone.parse::<Tz>().map_err(|e| anyhow!(e))?;
+        self.inner
+            .time_zone(Some(Some(time_zone.name().to_owned())));
+        Ok(())
+    }
+
+    pub fn unset_time_zone(&mut self) -> &mut Self {
+        self.inner.time_zone(Some(None));
+        self
+    }
+
+    pub fn unset_time_zone_update(&mut self) -> &mut Self {
+        self.inner.time_zone(None);
+        self
+    }
+
+    pub fn capacity(&mut self, capacity: u32) -> &mut Self {
+        self.inner.capacity(Some(Some(capacity)));
+        self
+    }
+
+    pub fn unset_capacity(&mut self) -> &mut Self {
+        self.inner.capacity(Some(None));
+        self
+    }
+
+    pub fn unset_capacity_update(&mut self) -> &mut Self {
+        self.inner.capacity(None);
+        self
+    }
+
+    pub fn status(&mut self, status: String) -> Result<()> {
+        let status = status.parse::<EventStatus>()?;
+        self.inner.status(Some(status));
+        Ok(())
+    }
+
+    pub fn unset_status_update(&mut self) -> &mut Self {
+        self.inner.status(None);
+        self
+    }
+
+    pub fn recurrence(&mut self, rrule: String) -> Result<()> {
+        let rule = rrule.parse::<RecurrenceRule>().map_err(|e| anyhow!(e))?;
+        self.inner.recurrence(Some(Some(rule.to_string())));
+        Ok(())
+    }
+
+    pub fn unset_recurrence(&mut self) -> &mut Self {
+        self.inner.recurrence(Some(None));
+        self
+    }
+
+    pub fn unset_recurrence_update(&mut self) -> &mut Self {
+        self.inner.recurrence(None);
+        self
+    }
+
     pub fn unset_locations(&mut self) -> &mut Self {
         self.inner.locations(Some(vec![]));
         self
@@ -624,7 +954,109 @@ impl CalendarEventUpdateBuilder {
     }
 }
 
+/// What importing an iCalendar file into a space did
+#[derive(Clone, Debug, Default)]
+pub struct CalendarImportReport {
+    created: Vec<OwnedEventId>,
+    updated: Vec<OwnedEventId>,
+    skipped: Vec<String>,
+}
+
+impl CalendarImportReport {
+    /// The ids of the calendar events created
+    pub fn created_ids(&self) -> Vec<String> {
+        self.created.iter().map(ToString::to_string).collect()
+    }
+
+    /// The ids of the calendar events imported before and updated now
+    pub fn updated_ids(&self) -> Vec<String> {
+        self.updated.iter().map(ToString::to_string).collect()
+    }
+
+    /// The events left out, with the reason
+    pub fn skipped(&self) -> Vec<String> {
+        self.skipped.clone()
+    }
+}
+
 impl Space {
+    /// Create calendar events for the VEVENTs of the iCalendar file
+    ///
+    /// Events imported before are matched by their UID and only updated if
+    /// anything changed.
+    pub async fn import_ical(&self, file_name: String) -> Result<CalendarImportReport> {
+        if !self.is_joined() {
+            bail!("Unable to create calendar_events for spaces we are not part on");
+        }
+        let me = self.clone();
+        let room = self.inner.room.clone();
+        let my_id = self.client.user_id()?;
+
+        RUNTIME
+            .spawn(async move {
+                let ical = std::fs::read_to_string(file_name)?;
+                let import = CalendarImport::from_ical(&ical)?;
+                let permitted = room
+                    .can_user_send_message(&my_id, MessageLikeEventType::RoomMessage)
+                    .await?;
+                if !permitted {
+                    bail!("No permissions to send message in this room");
+                }
+                // by the UID they were imported with, or the one we export
+                let existing: HashMap<String, models::CalendarEvent> = me
+                    .calendar_events()
+                    .await?
+                    .into_iter()
+                    .flat_map(|event| {
+                        let event = event.inner;
+                        let mut uids = vec![event.ical_uid()];
+                        uids.extend(event.external_uid.clone());
+                        uids.into_iter().map(move |uid| (uid, event.clone()))
+                    })
+                    .collect();
+                let mut report = CalendarImportReport {
+                    skipped: import.skipped,
+                    ..Default::default()
+                };
+                for content in import.events {
+                    let known = content
+                        .external_uid
+                        .as_ref()
+                        .and_then(|uid| existing.get(uid));
+                    let title = content.title.clone();
+                    let Some(event) = known else {
+                        match room.send(content).await {
+                            Ok(response) => report.created.push(response.event_id),
+                            Err(error) => {
+                                warn!(?error, %title, "Sending imported event failed");
+                                report.skipped.push(format!("{title}: sending failed"));
+                            }
+                        }
+                        continue;
+                    };
+                    let Some(update) = event.update_from_import(&content) else {
+                        report.skipped.push(format!("{title}: unchanged"));
+                        continue;
+                    };
+                    let sent = match update.build() {
+                        Ok(update) => room.send(update).await.map_err(anyhow::Error::from),
+                        Err(error) => Err(error.into()),
+                    };
+                    match sent {
+                        Ok(_) => report.updated.push(event.event_id().to_owned()),
+                        Err(error) => {
+                            warn!(?error, %title, "Sending update of imported event failed");
+                            report
+                                .skipped
+                                .push(format!("{title}: sending the update failed"));
+                        }
+                    }
+                }
+                Ok(report)
+            })
+            .await?
+    }
+
     pub fn calendar_event_draft(&self) -> Result<CalendarEventDraft> {
         if !self.is_joined() {
             bail!("Unable to create calendar_events for spaces we are not part on");
@@ -1,5 +1,6 @@
 use acter_core::{
-    events::SyncAnyActerEvent, executor::Executor, models::AnyActerModel, spaces::is_acter_space,
+    events::SyncAnyActerEvent, executor::Executor, models::AnyActerModel,
+    scheduler::DueTasksScheduler, spaces::is_acter_space,
 };
 use anyhow::Result;
 use core::time::Duration;
@@ -145,6 +146,7 @@ impl SyncError {
 pub struct SyncState {
     handle: Mutable<Option<JoinHandle<()>>>,
     first_sync_task: Mutable<Option<JoinHandle<Result<()>>>>,
+    due_tasks_task: Mutable<Option<JoinHandle<()>>>,
     first_synced_rx: Arc<Receiver<bool>>,
     sync_error: Arc<Receiver<SyncError>>,
     history_loading: Mutable<HistoryLoadState>,
@@ -157,6 +159,7 @@ impl SyncState {
             sync_error: Arc::new(sync_error),
             history_loading: Default::default(),
             first_sync_task: Default::default(),
+            due_tasks_task: Default::default(),
             handle: Default::default(),
         }
     }
@@ -207,6 +210,9 @@ impl SyncState {
         if let Some(handle) = self.handle.replace(None) {
             handle.abort();
         }
+        if let Some(handle) = self.due_tasks_task.replace(None) {
+            handle.abort();
+        }
     }
 }
 
@@ -464,6 +470,10 @@ impl Client {
         let history_loading = sync_state.history_loading.clone();
         let first_sync_task = sync_state.first_sync_task.clone();
 
+        // move tasks into overdue and due soon as time passes
+        let due_tasks = RUNTIME.spawn(DueTasksScheduler::new(executor.clone()).run());
+        sync_state.due_tasks_task.set(Some(due_tasks));
+
         let handle = RUNTIME.spawn(async move {
             info!("spawning sync callback");
 
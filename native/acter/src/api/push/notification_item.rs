@@ -1,5 +1,6 @@
 use acter_core::{
     events::{
+        calendar::EventStatus,
         news::{FallbackNewsContent, NewsContent},
         AnyActerEvent, SyncAnyActerEvent,
     },
@@ -236,6 +237,8 @@ impl TryFrom<&AnyActerModel> for NotificationItemParent {
             | AnyActerModel::TaskUpdate(_)
             | AnyActerModel::TaskSelfAssign(_)
             | AnyActerModel::TaskSelfUnassign(_)
+            | AnyActerModel::TaskAssignment(_)
+            | AnyActerModel::TaskTimeLog(_)
             | AnyActerModel::PinUpdate(_)
             | AnyActerModel::NewsEntryUpdate(_)
             | AnyActerModel::Story(_)
@@ -288,6 +291,12 @@ pub enum NotificationItemInner {
         event_id: OwnedEventId,
         key: String,
     },
+    EventCancelled {
+        parent_obj: Option<NotificationItemParent>,
+        parent_id: OwnedEventId,
+        room_id: OwnedRoomId,
+        event_id: OwnedEventId,
+    },
 }
 
 impl NotificationItemInner {
@@ -297,6 +306,7 @@ impl NotificationItemInner {
             NotificationItemInner::Invite { .. } => "invite",
             NotificationItemInner::Comment { .. } => "comment",
             NotificationItemInner::Reaction { .. } => "reaction",
+            NotificationItemInner::EventCancelled { .. } => "event-cancelled",
             NotificationItemInner::ChatMessage { is_dm, .. } => {
                 if *is_dm {
                     "dm"
@@ -336,6 +346,10 @@ impl NotificationItemInner {
                 parent.target_url(),
                 encode(event_id.as_str()),
             ),
+            NotificationItemInner::EventCancelled {
+                parent_obj: Some(parent),
+                ..
+            } => parent.target_url(),
             // -- fallback when the parent isn't there.
             NotificationItemInner::Comment {
                 event_id,
@@ -348,6 +362,12 @@ impl NotificationItemInner {
                 room_id,
                 event_id,
                 ..
+            }
+            | NotificationItemInner::EventCancelled {
+                parent_id,
+                room_id,
+                event_id,
+                ..
             } => {
                 format!(
                     "/forward?eventId={}&roomId={}&parentId={}",
@@ -370,14 +390,18 @@ impl NotificationItemInner {
     pub fn parent(&self) -> Option<NotificationItemParent> {
         match self {
             NotificationItemInner::Comment { parent_obj, .. }
-            | NotificationItemInner::Reaction { parent_obj, .. } => parent_obj.clone(),
+            | NotificationItemInner::Reaction { parent_obj, .. }
+            | NotificationItemInner::EventCancelled { parent_obj, .. } => parent_obj.clone(),
             _ => None,
         }
     }
     pub fn parent_id_str(&self) -> Option<String> {
         match self {
             NotificationItemInner::Comment { parent_id, .. }
-            | NotificationItemInner::Reaction { parent_id, .. } => Some(parent_id.to_string()),
+            | NotificationItemInner::Reaction { parent_id, .. }
+            | NotificationItemInner::EventCancelled { parent_id, .. } => {
+                Some(parent_id.to_string())
+            }
             _ => None,
         }
     }
@@ -665,6 +689,28 @@ impl NotificationItem {
                     })
                     .build()?)
             }
+
+            AnyActerEvent::CalendarEventUpdate(MessageLikeEvent::Original(e))
+                if e.content.status == Some(EventStatus::Cancelled) =>
+            {
+                let parent_obj = client
+                    .store()
+                    .get(e.content.calendar_event.event_id.as_str())
+                    .await
+                    .map_err(|error| {
+                        tracing::error!(?error, "Error loading cancelled calendar event");
+                    })
+                    .ok()
+                    .and_then(|o| NotificationItemParent::try_from(&o).ok());
+                Ok(builder
+                    .inner(NotificationItemInner::EventCancelled {
+                        parent_obj,
+                        parent_id: e.content.calendar_event.event_id,
+                        room_id: e.room_id,
+                        event_id: e.event_id,
+                    })
+                    .build()?)
+            }
             _ => {
                 tracing::warn!(?event, "Notification not support");
                 Ok(builder.build()?)
@@ -10,7 +10,7 @@ use acter_core::{
         news::NewsEntryEventContent,
         pins::PinEventContent,
         settings::ActerAppSettingsContent,
-        tasks::{TaskEventContent, TaskListEventContent},
+        tasks::{TaskAssignmentEventContent, TaskEventContent, TaskListEventContent},
     },
     spaces::is_acter_space,
     statics::PURPOSE_FIELD_DEV,
@@ -86,6 +86,7 @@ pub enum MemberPermission {
     CanPostEvent,
     CanPostTaskList,
     CanPostTask,
+    CanAssignTasks,
     // moderation tools
     CanBan,
     CanInvite,
@@ -266,6 +267,21 @@ impl Member {
                     return false;
                 }
             }
+            MemberPermission::CanAssignTasks => {
+                if self
+                    .acter_app_settings
+                    .as_ref()
+                    .map(|s| s.tasks().active())
+                    .unwrap_or_default()
+                {
+                    PermissionTest::Message(MessageLikeEventType::from(
+                        <TaskAssignmentEventContent as StaticEventContent>::TYPE,
+                    ))
+                } else {
+                    // Not an acter space or Tasks are not activated..
+                    return false;
+                }
+            }
             MemberPermission::CanUpgradeToActerSpace => {
                 if self.acter_app_settings.is_some() {
                     return false; // already an acter space
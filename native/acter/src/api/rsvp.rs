@@ -49,8 +49,8 @@ impl Client {
                     if let AnyActerModel::CalendarEvent(inner) = mdl {
                         let now = chrono::Utc::now();
                         let start_time = inner.utc_start();
-                        if now > start_time {
-                            // skip past events
+                        if now > start_time || inner.is_cancelled() {
+                            // skip past and cancelled events
                             continue;
                         }
                         if let Some(secs) = secs_from_now {
@@ -87,8 +87,8 @@ impl Client {
                     if let AnyActerModel::CalendarEvent(inner) = mdl {
                         let now = chrono::Utc::now();
                         let start_time = inner.utc_start();
-                        if now > start_time {
-                            // skip past events
+                        if now > start_time || inner.is_cancelled() {
+                            // skip past and cancelled events
                             continue;
                         }
                         if let Some(secs) = secs_from_now {
@@ -189,6 +189,14 @@ impl Rsvp {
     pub fn status(&self) -> String {
         self.inner.status.to_string()
     }
+
+    pub fn guests(&self) -> u32 {
+        self.inner.party_size() - 1
+    }
+
+    pub fn note(&self) -> Option<String> {
+        self.inner.note.clone()
+    }
 }
 
 pub struct RsvpDraft {
@@ -207,6 +215,21 @@ impl RsvpDraft {
         self
     }
 
+    pub fn guests(&mut self, guests: u32) -> &mut Self {
+        self.inner.guests(Some(guests));
+        self
+    }
+
+    pub fn note(&mut self, note: String) -> &mut Self {
+        self.inner.note(Some(note));
+        self
+    }
+
+    pub fn unset_note(&mut self) -> &mut Self {
+        self.inner.note(None);
+        self
+    }
+
     pub async fn send(&self) -> Result<OwnedEventId> {
         let room = self.room.clone();
         let my_id = self.client.user_id()?;
@@ -272,6 +295,33 @@ impl RsvpManager {
         *self.stats().total_rsvp_count()
     }
 
+    pub fn confirmed_count(&self) -> u32 {
+        *self.stats().confirmed_count()
+    }
+
+    pub fn waitlisted_count(&self) -> u32 {
+        *self.stats().waitlisted_count()
+    }
+
+    pub fn free_seats(&self) -> Option<u32> {
+        *self.stats().free_seats()
+    }
+
+    pub fn expected_attendance(&self) -> u32 {
+        *self.stats().expected_attendance()
+    }
+
+    /// The users waiting for a seat, in the order they will move up
+    pub async fn waitlisted_users(&self) -> Result<Vec<OwnedUserId>> {
+        let manager = self.inner.clone();
+        RUNTIME
+            .spawn(async move {
+                let attendance = manager.attendance().await?;
+                Ok(attendance.waitlisted().clone())
+            })
+            .await?
+    }
+
     pub async fn rsvp_entries(&self) -> Result<Vec<Rsvp>> {
         let manager = self.inner.clone();
         let client = self.client.clone();
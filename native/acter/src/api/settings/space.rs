@@ -12,7 +12,7 @@ use acter_core::events::{
     rsvp::RsvpEventContent,
     settings::ActerAppSettingsContentBuilder,
     stories::StoryEventContent,
-    tasks::{TaskEventContent, TaskListEventContent},
+    tasks::{TaskAssignmentEventContent, TaskEventContent, TaskListEventContent},
 };
 use anyhow::{bail, Context, Result};
 use matrix_sdk_base::{
@@ -97,6 +97,12 @@ impl RoomPowerLevels {
     pub fn tasks_key(&self) -> String {
         <TaskEventContent as StaticEventContent>::TYPE.into()
     }
+    pub fn task_assignments(&self) -> Option<i64> {
+        self.get_for_key(<TaskAssignmentEventContent as StaticEventContent>::TYPE.into())
+    }
+    pub fn task_assignments_key(&self) -> String {
+        <TaskAssignmentEventContent as StaticEventContent>::TYPE.into()
+    }
     pub fn pins(&self) -> Option<i64> {
         self.get_for_key(<PinEventContent as StaticEventContent>::TYPE.into())
     }
@@ -0,0 +1,168 @@
+use acter_core::task_import::{ImportedTask, ImportedTaskList, TaskImport};
+use anyhow::{bail, Result};
+use chrono::Datelike;
+use futures::stream::Stream;
+use tracing::warn;
+
+use super::{spaces::Space, CommentsManager, TaskList};
+
+impl Space {
+    /// Read the JSON export of another task manager to import into this space
+    ///
+    /// `source` is either `trello` or `todoist`.
+    pub fn task_importer(&self, source: String, file_name: String) -> Result<TaskImporter> {
+        if !self.inner.is_joined() {
+            bail!("Unable to create tasks for spaces we are not part on");
+        }
+        let data = std::fs::read_to_string(file_name)?;
+        let import = match source.as_str() {
+            "trello" => TaskImport::from_trello_json(&data)?,
+            "todoist" => TaskImport::from_todoist_json(&data)?,
+            _ => bail!("Unsupported import source: {source}"),
+        };
+        Ok(TaskImporter {
+            space: self.clone(),
+            import,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TaskImporter {
+    space: Space,
+    import: TaskImport,
+}
+
+/// How far an import got, after each task list, task or comment created
+#[derive(Clone, Debug)]
+pub struct TaskImportProgress {
+    done: u32,
+    total: u32,
+    error: Option<String>,
+}
+
+impl TaskImportProgress {
+    pub fn done_count(&self) -> u32 {
+        self.done
+    }
+
+    pub fn total(&self) -> u32 {
+        self.total
+    }
+
+    /// Why the import stopped, it doesn’t continue after an error
+    pub fn error(&self) -> Option<String> {
+        self.error.clone()
+    }
+}
+
+impl TaskImporter {
+    pub fn task_lists_count(&self) -> u32 {
+        self.import.task_lists.len() as u32
+    }
+
+    pub fn tasks_count(&self) -> u32 {
+        self.import.tasks_count()
+    }
+
+    /// How many task lists, tasks and comments are going to be created
+    pub fn total(&self) -> u32 {
+        self.import.total()
+    }
+
+    /// Create everything through the usual drafts, reporting progress
+    pub fn execute(&self) -> impl Stream<Item = TaskImportProgress> {
+        let me = self.clone();
+        let total = me.import.total();
+        async_stream::stream! {
+            let mut done = 0;
+            for list in &me.import.task_lists {
+                let task_list = match me.create_task_list(list).await {
+                    Ok(task_list) => task_list,
+                    Err(error) => {
+                        warn!(?error, name = list.name, "Importing task list failed");
+                        yield TaskImportProgress { done, total, error: Some(error.to_string()) };
+                        return;
+                    }
+                };
+                done += 1;
+                yield TaskImportProgress { done, total, error: None };
+
+                for task in &list.tasks {
+                    let comments = match me.create_task(&task_list, task).await {
+                        Ok(comments) => comments,
+                        Err(error) => {
+                            warn!(?error, title = task.title, "Importing task failed");
+                            yield TaskImportProgress { done, total, error: Some(error.to_string()) };
+                            return;
+                        }
+                    };
+                    done += 1;
+                    yield TaskImportProgress { done, total, error: None };
+
+                    for comment in &task.comments {
+                        let sent = match comments.comment_draft() {
+                            Ok(mut draft) => draft.content_text(comment.text()).send().await,
+                            Err(error) => Err(error),
+                        };
+                        if let Err(error) = sent {
+                            warn!(?error, title = task.title, "Importing comment failed");
+                            yield TaskImportProgress { done, total, error: Some(error.to_string()) };
+                            return;
+                        }
+                        done += 1;
+                        yield TaskImportProgress { done, total, error: None };
+                    }
+                }
+            }
+        }
+    }
+
+    async fn create_task_list(&self, list: &ImportedTaskList) -> Result<TaskList> {
+        let mut draft = self.space.task_list_draft()?;
+        draft.name(list.name.clone());
+        if let Some(description) = &list.description {
+            draft.description_markdown(description.clone());
+        }
+        let task_list_id = draft.send().await?;
+        self.space
+            .client
+            .task_list(task_list_id.to_string(), Some(30))
+            .await
+    }
+
+    /// Creates the task, returns the manager to add its comments with
+    async fn create_task(
+        &self,
+        task_list: &TaskList,
+        task: &ImportedTask,
+    ) -> Result<CommentsManager> {
+        let mut draft = task_list.task_builder()?;
+        draft.title(task.title.clone());
+        if let Some(description) = &task.description {
+            draft.description_markdown(description.clone());
+        }
+        if let Some(due_date) = &task.due_date {
+            draft.due_date(due_date.year(), due_date.month(), due_date.day());
+        }
+        if let Some(seconds) = task.utc_due_time_of_day {
+            draft.utc_due_time_of_day(seconds);
+        }
+        if let Some(seconds) = task.due_time_of_day {
+            draft.due_time_of_day(seconds);
+        }
+        if task.done {
+            draft.progress_percent(100);
+        }
+        if !task.keywords.is_empty() {
+            draft.keywords(&mut task.keywords.clone());
+        }
+        let task_id = draft.send().await?;
+        CommentsManager::new(
+            self.space.client.clone(),
+            self.space.inner.room.clone(),
+            task_id,
+        )
+        .await
+    }
+}
@@ -1,19 +1,32 @@
 use acter_core::{
     events::{
-        tasks::{self, Priority, TaskBuilder, TaskListBuilder},
-        Display, RefDetails as CoreRefDetails, RefPreview,
+        tasks::{
+            self, ChecklistChange, ChecklistItem, Priority, SpecialTaskListRole,
+            TaskAssignmentEventContent, TaskBuilder, TaskListBuilder, TaskState,
+            TaskTimeLogBuilder,
+        },
+        BelongsTo, Display, RecurrenceRule, RefDetails as CoreRefDetails, RefPreview,
+    },
+    models::{
+        self, can_redact, ActerModel, AnyActerModel, BurndownDay, TaskHistoryEntry,
+        TaskListDuplicateOptions, TaskQueryBuilder, TaskSortKey, TaskStats, TaskTimeLog,
     },
-    models::{self, can_redact, ActerModel, AnyActerModel, TaskStats},
     statics::KEYS,
 };
-use anyhow::{bail, Context, Result};
-use chrono::DateTime;
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::Tz;
 use futures::stream::StreamExt;
+use icalendar::{Calendar as iCalendar, Todo};
 use matrix_sdk::room::Room;
 use matrix_sdk_base::{
     ruma::{
-        events::{room::message::TextMessageEventContent, MessageLikeEventType},
-        EventId, OwnedEventId, OwnedRoomId, OwnedUserId,
+        events::{
+            room::{member::MembershipState, message::TextMessageEventContent},
+            MessageLikeEventType, StaticEventContent,
+        },
+        EventId, MilliSecondsSinceUnixEpoch, OwnedEventId, OwnedRoomId, OwnedUserId, RoomId,
+        UserId,
     },
     RoomState,
 };
@@ -21,14 +34,18 @@ use std::{
     collections::{hash_map::Entry, HashMap},
     ops::Deref,
 };
-use tokio::sync::broadcast::Receiver;
+use tokio::sync::broadcast::{error::RecvError, Receiver};
 use tokio_stream::{wrappers::BroadcastStream, Stream};
 use tracing::warn;
+use uuid::Uuid;
 
 use crate::MsgContent;
 
 use super::{client::Client, deep_linking::RefDetails, spaces::Space, RUNTIME};
 
+/// How many days a burndown covers at most
+const MAX_BURNDOWN_DAYS: i64 = 366;
+
 impl Client {
     pub async fn task_list(&self, key: String, timeout: Option<u8>) -> Result<TaskList> {
         let me = self.clone();
@@ -103,13 +120,25 @@ impl Client {
     }
 
     pub async fn my_open_tasks(&self) -> Result<Vec<Task>> {
+        self.my_tasks_in(KEYS::TASKS::MY_OPEN_TASKS).await
+    }
+
+    pub async fn my_overdue_tasks(&self) -> Result<Vec<Task>> {
+        self.my_tasks_in(KEYS::TASKS::MY_OVERDUE_TASKS).await
+    }
+
+    pub async fn my_due_soon_tasks(&self) -> Result<Vec<Task>> {
+        self.my_tasks_in(KEYS::TASKS::MY_DUE_SOON_TASKS).await
+    }
+
+    async fn my_tasks_in(&self, key: &'static str) -> Result<Vec<Task>> {
         let mut tasks = Vec::new();
         let mut rooms_map: HashMap<OwnedRoomId, Room> = HashMap::new();
         let me = self.clone();
         RUNTIME
             .spawn(async move {
                 let client = me.core.client();
-                for mdl in me.store().get_list(KEYS::TASKS::MY_OPEN_TASKS).await? {
+                for mdl in me.store().get_list(key).await? {
                     #[allow(irrefutable_let_patterns)]
                     if let AnyActerModel::Task(content) = mdl {
                         let room_id = content.room_id().to_owned();
@@ -131,10 +160,7 @@ impl Client {
                             content,
                         })
                     } else {
-                        warn!(
-                            "Non task list model found in `my open tasks` index: {:?}",
-                            mdl
-                        );
+                        warn!("Non task model found in `{key}` index: {:?}", mdl);
                     }
                 }
                 Ok(tasks)
@@ -142,6 +168,12 @@ impl Client {
             .await?
     }
 
+    /// Write my open tasks as iCalendar VTODOs to the file
+    pub async fn my_open_tasks_ical_for_sharing(&self, file_name: String) -> Result<bool> {
+        let tasks = self.my_open_tasks().await?;
+        write_ical_todos(tasks.iter().map(|t| t.content.as_ical_todo()), file_name)
+    }
+
     pub fn subscribe_my_open_tasks_stream(&self) -> impl Stream<Item = bool> {
         BroadcastStream::new(self.subscribe_my_open_tasks()).map(|_| true)
     }
@@ -150,6 +182,184 @@ impl Client {
         self.executor()
             .subscribe(KEYS::TASKS::MY_OPEN_TASKS.to_owned())
     }
+
+    pub fn subscribe_my_overdue_tasks_stream(&self) -> impl Stream<Item = bool> {
+        let receiver = self
+            .executor()
+            .subscribe(KEYS::TASKS::MY_OVERDUE_TASKS.to_owned());
+        BroadcastStream::new(receiver).map(|_| true)
+    }
+
+    pub fn subscribe_my_due_soon_tasks_stream(&self) -> impl Stream<Item = bool> {
+        let receiver = self
+            .executor()
+            .subscribe(KEYS::TASKS::MY_DUE_SOON_TASKS.to_owned());
+        BroadcastStream::new(receiver).map(|_| true)
+    }
+
+    pub fn task_query(&self) -> TaskQuery {
+        TaskQuery {
+            client: self.clone(),
+            content: Default::default(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct TaskQuery {
+    client: Client,
+    content: TaskQueryBuilder,
+}
+
+impl TaskQuery {
+    pub fn assignee(&mut self, user_id: String) -> Result<()> {
+        let user_id = UserId::parse(user_id)?;
+        self.content.assignee(user_id);
+        Ok(())
+    }
+
+    pub fn assigned_to_me(&mut self) -> Result<()> {
+        let user_id = self.client.user_id()?;
+        self.content.assignee(user_id);
+        Ok(())
+    }
+
+    pub fn unset_assignee(&mut self) -> &mut Self {
+        self.content.assignee(None);
+        self
+    }
+
+    pub fn due_before_rfc3339(&mut self, due_before: String) -> Result<()> {
+        let dt = DateTime::parse_from_rfc3339(&due_before)?.with_timezone(&Utc);
+        self.content.due_before(dt);
+        Ok(())
+    }
+
+    pub fn unset_due_before(&mut self) -> &mut Self {
+        self.content.due_before(None);
+        self
+    }
+
+    pub fn due_after_rfc3339(&mut self, due_after: String) -> Result<()> {
+        let dt = DateTime::parse_from_rfc3339(&due_after)?.with_timezone(&Utc);
+        self.content.due_after(dt);
+        Ok(())
+    }
+
+    pub fn unset_due_after(&mut self) -> &mut Self {
+        self.content.due_after(None);
+        self
+    }
+
+    pub fn priority_range(&mut self, highest: u8, lowest: u8) -> &mut Self {
+        self.content.priorities(highest..=lowest);
+        self
+    }
+
+    pub fn unset_priority_range(&mut self) -> &mut Self {
+        self.content.priorities(None);
+        self
+    }
+
+    pub fn keyword(&mut self, keyword: String) -> &mut Self {
+        self.content.keyword(keyword);
+        self
+    }
+
+    pub fn category(&mut self, category: String) -> &mut Self {
+        self.content.category(category);
+        self
+    }
+
+    pub fn space(&mut self, room_id: String) -> Result<()> {
+        let room_id = RoomId::parse(room_id)?;
+        self.content.space(room_id);
+        Ok(())
+    }
+
+    pub fn done(&mut self, done: bool) -> &mut Self {
+        self.content.done(done);
+        self
+    }
+
+    pub fn unset_done(&mut self) -> &mut Self {
+        self.content.done(None);
+        self
+    }
+
+    pub fn include_trashed(&mut self, include_trashed: bool) -> &mut Self {
+        self.content.include_trashed(include_trashed);
+        self
+    }
+
+    pub fn sort_by(&mut self, key: String, descending: bool) -> Result<()> {
+        let key = match key.as_str() {
+            "due_date" => TaskSortKey::DueDate,
+            "priority" => TaskSortKey::Priority,
+            "sort_order" => TaskSortKey::SortOrder,
+            _ => bail!("Unknown sort key {key}"),
+        };
+        self.content.sort_by(key, descending);
+        Ok(())
+    }
+
+    pub async fn results(&self) -> Result<Vec<Task>> {
+        let query = self.content.build()?;
+        let me = self.client.clone();
+        RUNTIME
+            .spawn(async move {
+                let client = me.core.client();
+                let mut rooms_map: HashMap<OwnedRoomId, Room> = HashMap::new();
+                let tasks = me
+                    .store()
+                    .get_list(KEYS::TASKS::ALL_TASKS)
+                    .await?
+                    .filter_map(|mdl| match mdl {
+                        AnyActerModel::Task(content) => Some(content),
+                        _ => None,
+                    });
+                let mut results = Vec::new();
+                for content in query.run(tasks) {
+                    let room_id = content.room_id().to_owned();
+                    let room = match rooms_map.entry(room_id) {
+                        Entry::Occupied(t) => t.get().clone(),
+                        Entry::Vacant(e) => {
+                            if let Some(room) = client.get_room(e.key()) {
+                                e.insert(room.clone());
+                                room
+                            } else {
+                                /// User not part of the room anymore, ignore
+                                continue;
+                            }
+                        }
+                    };
+                    results.push(Task {
+                        client: me.clone(),
+                        room,
+                        content,
+                    })
+                }
+                Ok(results)
+            })
+            .await?
+    }
+
+    /// The results, refreshed whenever any task changes
+    pub fn results_stream(&self) -> impl Stream<Item = Vec<Task>> {
+        let me = self.clone();
+        async_stream::stream! {
+            let mut changes = me.client.subscribe(KEYS::TASKS::ALL_TASKS.to_owned());
+            loop {
+                match me.results().await {
+                    Ok(tasks) => yield tasks,
+                    Err(error) => warn!(?error, "Running the task query failed"),
+                }
+                if let Err(RecvError::Closed) = changes.recv().await {
+                    break;
+                }
+            }
+        }
+    }
 }
 
 impl Space {
@@ -276,6 +486,22 @@ impl TaskListDraft {
         self
     }
 
+    pub fn add_state(
+        &mut self,
+        key: String,
+        name: String,
+        display: Option<Box<Display>>,
+    ) -> &mut Self {
+        self.content
+            .add_state(TaskState::new(key, name, display.map(|d| *d)));
+        self
+    }
+
+    pub fn unset_states(&mut self) -> &mut Self {
+        self.content.states(vec![]);
+        self
+    }
+
     pub async fn send(&self) -> Result<OwnedEventId> {
         let room = self.room.clone();
         let my_id = self.client.user_id()?;
@@ -365,6 +591,53 @@ impl TaskList {
         result
     }
 
+    pub fn is_inbox(&self) -> bool {
+        self.content.has_role(SpecialTaskListRole::Inbox)
+    }
+
+    pub fn is_trash(&self) -> bool {
+        self.content.has_role(SpecialTaskListRole::Trash)
+    }
+
+    pub fn states(&self) -> Vec<TaskState> {
+        self.content.states.clone()
+    }
+
+    pub fn tasks_count_in_state(&self, state: Option<String>) -> u32 {
+        self.content.tasks_count_in_state(state.as_deref())
+    }
+
+    /// How much time has been logged on the tasks of this list, in seconds
+    pub fn time_spent_seconds(&self) -> u64 {
+        self.content.stats().time_spent_seconds()
+    }
+
+    /// How much time the member logged on the tasks of this list, in seconds
+    pub fn time_spent_by(&self, user_id: String) -> Result<u64> {
+        let user_id = UserId::parse(user_id)?;
+        Ok(self.content.stats().time_spent_by(&user_id))
+    }
+
+    /// The estimates of the tasks of this list added up, in seconds
+    pub fn estimate_seconds(&self) -> u64 {
+        self.content.stats().estimate_seconds()
+    }
+
+    /// The open and done counts at the end of every day from `start` to `end`
+    ///
+    /// Both are given as `YYYY-MM-DD`, days end in the time zone of this list.
+    pub fn burndown(&self, start: String, end: String) -> Result<Vec<BurndownDay>> {
+        let start = NaiveDate::parse_from_str(&start, "%Y-%m-%d")?;
+        let end = NaiveDate::parse_from_str(&end, "%Y-%m-%d")?;
+        if end < start {
+            bail!("The end of the range has to be after its start");
+        }
+        if (end - start).num_days() > MAX_BURNDOWN_DAYS {
+            bail!("The range can span {MAX_BURNDOWN_DAYS} days at most");
+        }
+        Ok(self.content.burndown(start, end))
+    }
+
     pub fn space(&self) -> Space {
         Space::new(
             self.client.clone(),
@@ -481,6 +754,31 @@ impl TaskList {
         self.tasks_with_filter(|_| true).await
     }
 
+    /// The tasks grouped by the workflow states of this list, in order
+    ///
+    /// The first column holds the tasks without a (known) state.
+    pub async fn tasks_by_state(&self) -> Result<Vec<TaskColumn>> {
+        let mut columns = vec![TaskColumn {
+            state: None,
+            tasks: vec![],
+        }];
+        columns.extend(self.content.states.iter().map(|state| TaskColumn {
+            state: Some(state.clone()),
+            tasks: vec![],
+        }));
+        for task in self.tasks().await? {
+            let column = self
+                .content
+                .states
+                .iter()
+                .position(|s| task.content.state.as_ref() == Some(&s.key))
+                .map(|idx| idx + 1)
+                .unwrap_or_default();
+            columns[column].tasks.push(task);
+        }
+        Ok(columns)
+    }
+
     pub async fn task(&self, task_id: String) -> Result<Task> {
         let event_id = EventId::parse(task_id)?;
         self.tasks_with_filter(move |t| t.event_id() == event_id)
@@ -490,6 +788,123 @@ impl TaskList {
             .context("Task not found")
     }
 
+    /// Write the tasks of this list as iCalendar VTODOs to the file
+    pub async fn ical_for_sharing(&self, file_name: String) -> Result<bool> {
+        let tasks = self.tasks().await?;
+        write_ical_todos(tasks.iter().map(|t| t.content.as_ical_todo()), file_name)
+    }
+
+    /// Create tasks in this list for the VTODOs of the iCalendar file
+    ///
+    /// Todos already in this list are skipped by their UID. Returns how many
+    /// tasks have been created.
+    pub async fn import_ical(&self, file_name: String) -> Result<u32> {
+        if !self.is_joined() {
+            bail!("Can only create tasks in joined rooms");
+        }
+        let client = self.client.clone();
+        let room = self.room.clone();
+        let task_list = self.content.clone();
+        let my_id = self.client.user_id()?;
+
+        RUNTIME
+            .spawn(async move {
+                let ical = std::fs::read_to_string(file_name)?;
+                let builders = task_list.tasks_from_ical(client.store(), &ical).await?;
+                let permitted = room
+                    .can_user_send_message(&my_id, MessageLikeEventType::RoomMessage)
+                    .await?;
+                if !permitted {
+                    bail!("No permissions to send message in this room");
+                }
+                let mut created = 0;
+                for builder in builders {
+                    room.send(builder.build()?).await?;
+                    created += 1;
+                }
+                Ok(created)
+            })
+            .await?
+    }
+
+    /// Create a copy of this list with all its tasks in the given space
+    ///
+    /// `shift_days` moves all due and start dates, `reset_progress` starts
+    /// every copy undone. Assignees are only kept for members of the target
+    /// space. Returns the id of the new task list.
+    pub async fn duplicate(
+        &self,
+        target_space: String,
+        reset_progress: bool,
+        shift_days: i64,
+        keep_assignees: bool,
+    ) -> Result<OwnedEventId> {
+        let client = self.client.clone();
+        let room = client.room_by_id_typed(&RoomId::parse(target_space)?)?;
+        if room.state() != RoomState::Joined {
+            bail!("Can only create task lists in joined rooms");
+        }
+        let task_list = self.content.clone();
+        let tasks = self.tasks().await?;
+        let my_id = client.user_id()?;
+        let options = TaskListDuplicateOptions {
+            reset_progress,
+            shift_days,
+            keep_assignees,
+        };
+        // fail on shifts out of range before anything is sent
+        for task in &tasks {
+            task.content.duplicate_builder(
+                task_list.event_id().to_owned(),
+                &options,
+                &HashMap::new(),
+            )?;
+        }
+
+        RUNTIME
+            .spawn(async move {
+                let permitted = room
+                    .can_user_send_message(&my_id, MessageLikeEventType::RoomMessage)
+                    .await?;
+                if !permitted {
+                    bail!("No permissions to send message in this room");
+                }
+                let task_list_id = room
+                    .send(task_list.duplicate_builder().build()?)
+                    .await?
+                    .event_id;
+
+                let tasks = tasks.into_iter().map(|t| t.content).collect();
+                let mut copied = HashMap::new();
+                for task in models::Task::duplication_order(tasks) {
+                    let content = task
+                        .duplicate_builder(task_list_id.clone(), &options, &copied)?
+                        .build()?;
+                    let task_id = room.send(content).await?.event_id;
+                    if options.keep_assignees {
+                        for assignee in task.assignees() {
+                            let is_member = room
+                                .get_member(&assignee)
+                                .await?
+                                .is_some_and(|m| m.membership() == &MembershipState::Join);
+                            if !is_member {
+                                continue;
+                            }
+                            room.send(TaskAssignmentEventContent {
+                                task: task_id.clone().into(),
+                                assignee,
+                                assigned: true,
+                            })
+                            .await?;
+                        }
+                    }
+                    copied.insert(task.meta.event_id.clone(), task_id);
+                }
+                Ok(task_list_id)
+            })
+            .await?
+    }
+
     async fn tasks_with_filter<F>(&self, filter: F) -> Result<Vec<Task>>
     where
         F: Fn(&acter_core::models::Task) -> bool + Send + Sync + 'static,
@@ -538,6 +953,26 @@ impl TaskList {
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct TaskColumn {
+    state: Option<TaskState>,
+    tasks: Vec<Task>,
+}
+
+impl TaskColumn {
+    pub fn state_key(&self) -> Option<String> {
+        self.state.as_ref().map(TaskState::key)
+    }
+
+    pub fn state(&self) -> Option<TaskState> {
+        self.state.clone()
+    }
+
+    pub fn tasks(&self) -> Vec<Task> {
+        self.tasks.clone()
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Task {
     client: Client,
@@ -570,6 +1005,10 @@ impl Task {
         self.content.task_list_id.event_id.to_string()
     }
 
+    pub fn parent_task_id_str(&self) -> Option<String> {
+        self.content.parent_task_id().map(|p| p.to_string())
+    }
+
     pub fn description(&self) -> Option<MsgContent> {
         self.content.description.as_ref().map(MsgContent::from)
     }
@@ -618,7 +1057,99 @@ impl Task {
     }
 
     pub fn progress_percent(&self) -> Option<u8> {
-        self.content.progress_percent
+        self.content.percent()
+    }
+
+    pub fn estimate_seconds(&self) -> Option<u32> {
+        self.content.estimate_seconds
+    }
+
+    /// How much time has been logged on this task, in seconds
+    pub fn time_spent_seconds(&self) -> u64 {
+        self.content.time_spent_seconds()
+    }
+
+    /// How much time the member logged on this task, in seconds
+    pub fn time_spent_by(&self, user_id: String) -> Result<u64> {
+        let user_id = UserId::parse(user_id)?;
+        Ok(self
+            .content
+            .time_spent_per_user()
+            .get(&user_id)
+            .copied()
+            .unwrap_or_default())
+    }
+
+    pub fn checklist(&self) -> Vec<ChecklistItem> {
+        self.content.checklist.clone()
+    }
+
+    pub fn checklist_progress(&self) -> Option<u8> {
+        self.content.checklist_progress()
+    }
+
+    pub fn progress_from_checklist(&self) -> bool {
+        self.content.progress_from_checklist
+    }
+
+    pub fn has_subtasks(&self) -> bool {
+        self.content.has_subtasks()
+    }
+
+    pub fn subtasks_count(&self) -> u32 {
+        self.content.subtasks_count()
+    }
+
+    pub fn done_subtasks_count(&self) -> u32 {
+        self.content.done_subtasks_count()
+    }
+
+    pub fn subtasks_done_percent(&self) -> Option<u8> {
+        self.content.subtasks_done_percent()
+    }
+
+    pub fn is_blocked(&self) -> bool {
+        self.content.is_blocked()
+    }
+
+    pub fn recurrence(&self) -> Option<String> {
+        self.content.recurrence.clone()
+    }
+
+    pub fn state(&self) -> Option<String> {
+        self.content.state.clone()
+    }
+
+    pub fn is_trashed(&self) -> bool {
+        self.content.is_trashed()
+    }
+
+    pub fn due_time_of_day(&self) -> Option<i32> {
+        self.content.due_time_of_day
+    }
+
+    pub fn time_zone(&self) -> Option<String> {
+        self.content.time_zone.as_ref().map(ToString::to_string)
+    }
+
+    pub fn resolved_time_zone(&self) -> Option<String> {
+        self.content
+            .resolved_time_zone()
+            .as_ref()
+            .map(ToString::to_string)
+    }
+
+    pub fn is_overdue(&self) -> bool {
+        self.content.is_overdue_at(&Utc::now())
+    }
+
+    pub fn is_overdue_at_rfc3339(&self, instant: String) -> Result<bool> {
+        let instant = DateTime::parse_from_rfc3339(&instant)?.into();
+        Ok(self.content.is_overdue_at(&instant))
+    }
+
+    pub fn trashed_at_ts(&self) -> Option<u64> {
+        self.content.trashed_at().map(|ts| ts.get().into())
     }
 
     pub fn keywords(&self) -> Vec<String> {
@@ -733,34 +1264,257 @@ impl Task {
             .await?
     }
 
-    pub fn update_builder(&self) -> Result<TaskUpdateBuilder> {
-        if !self.is_joined() {
-            bail!("Can only update tasks in joined rooms");
-        }
-        Ok(TaskUpdateBuilder {
-            client: self.client.clone(),
-            room: self.room.clone(),
-            content: self.content.updater(),
-        })
+    pub async fn assign(&self, user_id: String) -> Result<OwnedEventId> {
+        self.send_assignment(user_id, true).await
     }
 
-    pub fn subscribe_stream(&self) -> impl Stream<Item = bool> {
-        BroadcastStream::new(self.subscribe()).map(|_| true)
+    pub async fn unassign(&self, user_id: String) -> Result<OwnedEventId> {
+        self.send_assignment(user_id, false).await
     }
 
-    pub fn subscribe(&self) -> Receiver<()> {
-        let key = self.content.event_id().to_string();
-        self.client.subscribe(key)
+    async fn send_assignment(&self, user_id: String, assigned: bool) -> Result<OwnedEventId> {
+        if !self.is_joined() {
+            bail!("Can only update tasks in joined rooms");
+        }
+        let room = self.room.clone();
+        let my_id = self.client.user_id()?;
+        let assignee = UserId::parse(user_id)?;
+        let content = self.content.assignment_event_content(assignee, assigned);
+
+        RUNTIME
+            .spawn(async move {
+                let permitted = room
+                    .can_user_send_message(
+                        &my_id,
+                        MessageLikeEventType::from(
+                            <TaskAssignmentEventContent as StaticEventContent>::TYPE,
+                        ),
+                    )
+                    .await?;
+                if !permitted {
+                    bail!("No permissions to assign tasks in this room");
+                }
+                let response = room.send(content).await?;
+                Ok(response.event_id)
+            })
+            .await?
     }
 
-    pub async fn comments(&self) -> Result<crate::CommentsManager> {
+    /// Move this task into the trash list of its space
+    pub async fn trash(&self) -> Result<OwnedEventId> {
+        if !self.is_joined() {
+            bail!("Can only update tasks in joined rooms");
+        }
+        if self.content.is_trashed() {
+            bail!("Task is in the trash already");
+        }
         let client = self.client.clone();
         let room = self.room.clone();
-        let event_id = self.content.event_id().to_owned();
-        crate::CommentsManager::new(client, room, event_id).await
-    }
+        let task = self.content.clone();
 
-    pub async fn attachments(&self) -> Result<crate::AttachmentsManager> {
+        RUNTIME
+            .spawn(async move {
+                let trash = special_task_list(&client, &room, SpecialTaskListRole::Trash).await?;
+                let content = task.trash_updater(trash.event_id().to_owned()).build()?;
+                send_task_update(&client, &room, content).await
+            })
+            .await?
+    }
+
+    /// Move this task out of the trash into the list it was in before
+    ///
+    /// If that list is gone, the task is restored into the inbox list.
+    pub async fn restore(&self) -> Result<OwnedEventId> {
+        if !self.is_joined() {
+            bail!("Can only update tasks in joined rooms");
+        }
+        let Some(trashed_from) = self.content.trashed_from.clone() else {
+            bail!("Task isn’t in the trash");
+        };
+        let client = self.client.clone();
+        let room = self.room.clone();
+        let task = self.content.clone();
+
+        RUNTIME
+            .spawn(async move {
+                let task_list_id = match client.store().get(trashed_from.event_id.as_str()).await {
+                    Ok(AnyActerModel::TaskList(_)) => trashed_from.event_id,
+                    _ => special_task_list(&client, &room, SpecialTaskListRole::Inbox)
+                        .await?
+                        .event_id()
+                        .to_owned(),
+                };
+                let content = task.restore_updater(task_list_id).build()?;
+                send_task_update(&client, &room, content).await
+            })
+            .await?
+    }
+
+    pub fn update_builder(&self) -> Result<TaskUpdateBuilder> {
+        if !self.is_joined() {
+            bail!("Can only update tasks in joined rooms");
+        }
+        Ok(TaskUpdateBuilder {
+            client: self.client.clone(),
+            room: self.room.clone(),
+            content: self.content.updater(),
+        })
+    }
+
+    pub fn subtask_builder(&self) -> Result<TaskDraft> {
+        if !self.is_joined() {
+            bail!("Can only create tasks in joined rooms");
+        }
+        let parent: BelongsTo = self.content.event_id().to_owned().into();
+        let mut content = TaskBuilder::default();
+        content.task_list_id(self.content.task_list_id.event_id.clone());
+        content.parent_task_id(Some(parent));
+        Ok(TaskDraft {
+            client: self.client.clone(),
+            room: self.room.clone(),
+            content,
+        })
+    }
+
+    pub fn next_occurrence_builder(&self) -> Result<TaskDraft> {
+        if !self.is_joined() {
+            bail!("Can only create tasks in joined rooms");
+        }
+        let Some(content) = self.content.next_occurrence() else {
+            bail!("Task doesn’t have a next occurrence");
+        };
+        Ok(TaskDraft {
+            client: self.client.clone(),
+            room: self.room.clone(),
+            content,
+        })
+    }
+
+    pub async fn blocked_by(&self) -> Result<Vec<Task>> {
+        self.tasks_by_ids(self.content.blocked_by.clone()).await
+    }
+
+    pub async fn blocking(&self) -> Result<Vec<Task>> {
+        self.tasks_by_ids(self.content.blocking()).await
+    }
+
+    async fn tasks_by_ids(&self, task_ids: Vec<OwnedEventId>) -> Result<Vec<Task>> {
+        let client = self.client.clone();
+        let room = self.room.clone();
+        RUNTIME
+            .spawn(async move {
+                let res = client
+                    .store()
+                    .get_many(task_ids.iter().map(ToString::to_string).collect())
+                    .await
+                    .into_iter()
+                    .filter_map(|e| match e {
+                        Some(AnyActerModel::Task(content)) => Some(Task {
+                            client: client.clone(),
+                            room: room.clone(),
+                            content,
+                        }),
+                        _ => None,
+                    })
+                    .collect();
+                Ok(res)
+            })
+            .await?
+    }
+
+    pub async fn subtasks(&self) -> Result<Vec<Task>> {
+        let subtasks_key = self.content.subtasks_key();
+        let client = self.client.clone();
+        let room = self.room.clone();
+        RUNTIME
+            .spawn(async move {
+                let res = client
+                    .store()
+                    .get_list(&subtasks_key)
+                    .await?
+                    .filter_map(|e| match e {
+                        AnyActerModel::Task(content) => Some(Task {
+                            client: client.clone(),
+                            room: room.clone(),
+                            content,
+                        }),
+                        _ => None,
+                    })
+                    .collect();
+                Ok(res)
+            })
+            .await?
+    }
+
+    pub fn subscribe_stream(&self) -> impl Stream<Item = bool> {
+        BroadcastStream::new(self.subscribe()).map(|_| true)
+    }
+
+    pub fn subscribe(&self) -> Receiver<()> {
+        let key = self.content.event_id().to_string();
+        self.client.subscribe(key)
+    }
+
+    pub async fn history(&self) -> Result<Vec<TaskHistoryEntry>> {
+        let client = self.client.clone();
+        let task_id = self.content.event_id().to_owned();
+        RUNTIME
+            .spawn(async move { Ok(TaskHistoryEntry::for_task(client.store(), &task_id).await?) })
+            .await?
+    }
+
+    pub fn subscribe_history_stream(&self) -> impl Stream<Item = bool> {
+        BroadcastStream::new(self.client.subscribe(self.content.history_key())).map(|_| true)
+    }
+
+    /// Log time spent working on this task
+    pub async fn log_time(&self, seconds: u32, note: Option<String>) -> Result<OwnedEventId> {
+        if !self.is_joined() {
+            bail!("Can only update tasks in joined rooms");
+        }
+        let room = self.room.clone();
+        let my_id = self.client.user_id()?;
+        let content = TaskTimeLogBuilder::default()
+            .task(self.content.event_id().to_owned())
+            .seconds(seconds)
+            .note(note)
+            .build()?;
+
+        RUNTIME
+            .spawn(async move {
+                let permitted = room
+                    .can_user_send_message(&my_id, MessageLikeEventType::RoomMessage)
+                    .await?;
+                if !permitted {
+                    bail!("No permissions to send message in this room");
+                }
+                let response = room.send(content).await?;
+                Ok(response.event_id)
+            })
+            .await?
+    }
+
+    /// The time logged on this task, oldest entry first
+    pub async fn time_logs(&self) -> Result<Vec<TaskTimeLog>> {
+        let client = self.client.clone();
+        let task_id = self.content.event_id().to_owned();
+        RUNTIME
+            .spawn(async move { Ok(TaskTimeLog::for_task(client.store(), &task_id).await?) })
+            .await?
+    }
+
+    pub fn subscribe_time_logs_stream(&self) -> impl Stream<Item = bool> {
+        BroadcastStream::new(self.client.subscribe(self.content.time_log_key())).map(|_| true)
+    }
+
+    pub async fn comments(&self) -> Result<crate::CommentsManager> {
+        let client = self.client.clone();
+        let room = self.room.clone();
+        let event_id = self.content.event_id().to_owned();
+        crate::CommentsManager::new(client, room, event_id).await
+    }
+
+    pub async fn attachments(&self) -> Result<crate::AttachmentsManager> {
         let client = self.client.clone();
         let room = self.room.clone();
         let event_id = self.content.event_id().to_owned();
@@ -787,6 +1541,12 @@ impl TaskDraft {
         self
     }
 
+    pub fn description_markdown(&mut self, body: String) -> &mut Self {
+        let desc = TextMessageEventContent::markdown(body);
+        self.content.description(Some(desc));
+        self
+    }
+
     pub fn description_html(&mut self, body: String, html_body: String) -> &mut Self {
         let desc = TextMessageEventContent::html(body, html_body);
         self.content.description(Some(desc));
@@ -823,6 +1583,38 @@ impl TaskDraft {
         self
     }
 
+    pub fn due_time_of_day(&mut self, seconds: i32) -> &mut Self {
+        self.content.due_time_of_day(Some(seconds));
+        self
+    }
+
+    pub fn unset_due_time_of_day(&mut self) -> &mut Self {
+        self.content.due_time_of_day(None);
+        self
+    }
+
+    pub fn time_zone(&mut self, time_zone: String) -> Result<()> {
+        let time_zone = time_zone.parse::<Tz>().map_err(|e| anyhow!(e))?;
+        self.content.time_zone(Some(time_zone));
+        Ok(())
+    }
+
+    pub fn unset_time_zone(&mut self) -> &mut Self {
+        self.content.time_zone(None);
+        self
+    }
+
+    pub fn recurrence(&mut self, rrule: String) -> Result<()> {
+        let rule = rrule.parse::<RecurrenceRule>().map_err(|e| anyhow!(e))?;
+        self.content.recurrence(Some(rule.to_string()));
+        Ok(())
+    }
+
+    pub fn unset_recurrence(&mut self) -> &mut Self {
+        self.content.recurrence(None);
+        self
+    }
+
     pub fn utc_start_from_rfc3339(&mut self, utc_start: String) -> Result<()> {
         let dt = DateTime::parse_from_rfc3339(&utc_start)?.into();
         self.content.utc_start(Some(dt));
@@ -860,6 +1652,42 @@ impl TaskDraft {
         self
     }
 
+    pub fn estimate_seconds(&mut self, seconds: u32) -> &mut Self {
+        self.content.estimate_seconds(Some(seconds));
+        self
+    }
+
+    pub fn unset_estimate_seconds(&mut self) -> &mut Self {
+        self.content.estimate_seconds(None);
+        self
+    }
+
+    pub fn add_checklist_item(&mut self, title: String) -> &mut Self {
+        let item = ChecklistItem::new(Uuid::new_v4().to_string(), title);
+        self.content.checklist_item(item);
+        self
+    }
+
+    pub fn unset_checklist(&mut self) -> &mut Self {
+        self.content.checklist(vec![]);
+        self
+    }
+
+    pub fn state(&mut self, state: String) -> &mut Self {
+        self.content.state(Some(state));
+        self
+    }
+
+    pub fn unset_state(&mut self) -> &mut Self {
+        self.content.state(None);
+        self
+    }
+
+    pub fn progress_from_checklist(&mut self, value: bool) -> &mut Self {
+        self.content.progress_from_checklist(value);
+        self
+    }
+
     #[allow(clippy::ptr_arg)]
     pub fn keywords(&mut self, keywords: &mut Vec<String>) -> &mut Self {
         self.content.keywords(keywords.to_vec());
@@ -882,6 +1710,21 @@ impl TaskDraft {
         self
     }
 
+    #[allow(clippy::ptr_arg)]
+    pub fn blocked_by(&mut self, task_ids: &mut Vec<String>) -> Result<()> {
+        let task_ids = task_ids
+            .iter()
+            .map(EventId::parse)
+            .collect::<Result<Vec<OwnedEventId>, _>>()?;
+        self.content.blocked_by(task_ids);
+        Ok(())
+    }
+
+    pub fn unset_blocked_by(&mut self) -> &mut Self {
+        self.content.blocked_by(vec![]);
+        self
+    }
+
     pub fn display(&mut self, display: Box<Display>) -> &mut Self {
         self.content.display(Some(*display));
         self
@@ -892,13 +1735,24 @@ impl TaskDraft {
         self
     }
 
+    /// If no task list has been set, the task is sent to the inbox list of the space
     pub async fn send(&self) -> Result<OwnedEventId> {
         let room = self.room.clone();
+        let client = self.client.clone();
         let my_id = self.client.user_id()?;
-        let content = self.content.build()?;
+        let mut builder = self.content.clone();
 
         RUNTIME
             .spawn(async move {
+                if !builder.has_task_list_id() {
+                    let inbox =
+                        special_task_list(&client, &room, SpecialTaskListRole::Inbox).await?;
+                    builder.task_list_id(inbox.event_id().to_owned());
+                }
+                let content = builder.build()?;
+                if let Some(state) = &content.state {
+                    ensure_task_list_state(&client, &content.task_list_id.event_id, state).await?;
+                }
                 let permitted = room
                     .can_user_send_message(&my_id, MessageLikeEventType::RoomMessage)
                     .await?;
@@ -930,6 +1784,17 @@ impl TaskUpdateBuilder {
         self
     }
 
+    pub fn task_list_id(&mut self, task_list_id: String) -> Result<()> {
+        let task_list_id = EventId::parse(task_list_id)?;
+        self.content.task_list_id(Some(task_list_id.into()));
+        Ok(())
+    }
+
+    pub fn unset_task_list_id_update(&mut self) -> &mut Self {
+        self.content.task_list_id(None);
+        self
+    }
+
     pub fn description_text(&mut self, body: String) -> &mut Self {
         let desc = TextMessageEventContent::plain(body);
         self.content.description(Some(Some(desc)));
@@ -1048,6 +1913,53 @@ impl TaskUpdateBuilder {
         self
     }
 
+    pub fn due_time_of_day(&mut self, seconds: i32) -> &mut Self {
+        self.content.due_time_of_day(Some(Some(seconds)));
+        self
+    }
+
+    pub fn unset_due_time_of_day(&mut self) -> &mut Self {
+        self.content.due_time_of_day(Some(None));
+        self
+    }
+
+    pub fn unset_due_time_of_day_update(&mut self) -> &mut Self {
+        self.content.due_time_of_day(None);
+        self
+    }
+
+    pub fn time_zone(&mut self, time_zone: String) -> Result<()> {
+        let time_zone = time_zone.parse::<Tz>().map_err(|e| anyhow!(e))?;
+        self.content.time_zone(Some(Some(time_zone)));
+        Ok(())
+    }
+
+    pub fn unset_time_zone(&mut self) -> &mut Self {
+        self.content.time_zone(Some(None));
+        self
+    }
+
+    pub fn unset_time_zone_update(&mut self) -> &mut Self {
+        self.content.time_zone(None);
+        self
+    }
+
+    pub fn recurrence(&mut self, rrule: String) -> Result<()> {
+        let rule = rrule.parse::<RecurrenceRule>().map_err(|e| anyhow!(e))?;
+        self.content.recurrence(Some(Some(rule.to_string())));
+        Ok(())
+    }
+
+    pub fn unset_recurrence(&mut self) -> &mut Self {
+        self.content.recurrence(Some(None));
+        self
+    }
+
+    pub fn unset_recurrence_update(&mut self) -> &mut Self {
+        self.content.recurrence(None);
+        self
+    }
+
     pub fn utc_start_from_rfc3339(&mut self, utc_start: String) -> Result<()> {
         let dt = DateTime::parse_from_rfc3339(&utc_start)?.into();
         self.content.utc_start(Some(Some(dt)));
@@ -1095,13 +2007,144 @@ impl TaskUpdateBuilder {
         self
     }
 
+    pub fn estimate_seconds(&mut self, seconds: u32) -> &mut Self {
+        self.content.estimate_seconds(Some(Some(seconds)));
+        self
+    }
+
+    pub fn unset_estimate_seconds(&mut self) -> &mut Self {
+        self.content.estimate_seconds(Some(None));
+        self
+    }
+
+    pub fn unset_estimate_seconds_update(&mut self) -> &mut Self {
+        self.content.estimate_seconds(None);
+        self
+    }
+
+    pub fn add_checklist_item(&mut self, title: String) -> &mut Self {
+        let item = ChecklistItem::new(Uuid::new_v4().to_string(), title);
+        self.content.checklist_change(ChecklistChange::Add {
+            item,
+            position: None,
+        });
+        self
+    }
+
+    pub fn remove_checklist_item(&mut self, id: String) -> &mut Self {
+        self.content
+            .checklist_change(ChecklistChange::Remove { id });
+        self
+    }
+
+    pub fn check_checklist_item(&mut self, id: String) -> &mut Self {
+        self.content
+            .checklist_change(ChecklistChange::SetDone { id, done: true });
+        self
+    }
+
+    pub fn uncheck_checklist_item(&mut self, id: String) -> &mut Self {
+        self.content
+            .checklist_change(ChecklistChange::SetDone { id, done: false });
+        self
+    }
+
+    pub fn rename_checklist_item(&mut self, id: String, title: String) -> &mut Self {
+        self.content
+            .checklist_change(ChecklistChange::Rename { id, title });
+        self
+    }
+
+    pub fn move_checklist_item(&mut self, id: String, position: u32) -> &mut Self {
+        self.content
+            .checklist_change(ChecklistChange::Move { id, position });
+        self
+    }
+
+    pub fn progress_from_checklist(&mut self, value: bool) -> &mut Self {
+        self.content.progress_from_checklist(Some(value));
+        self
+    }
+
+    pub fn unset_progress_from_checklist_update(&mut self) -> &mut Self {
+        self.content.progress_from_checklist(None);
+        self
+    }
+
+    pub fn state(&mut self, state: String) -> &mut Self {
+        self.content.state(Some(Some(state)));
+        self
+    }
+
+    pub fn unset_state(&mut self) -> &mut Self {
+        self.content.state(Some(None));
+        self
+    }
+
+    pub fn unset_state_update(&mut self) -> &mut Self {
+        self.content.state(None);
+        self
+    }
+
+    #[allow(clippy::ptr_arg)]
+    pub fn blocked_by(&mut self, task_ids: &mut Vec<String>) -> Result<()> {
+        let task_ids = task_ids
+            .iter()
+            .map(EventId::parse)
+            .collect::<Result<Vec<OwnedEventId>, _>>()?;
+        self.content.blocked_by(Some(task_ids));
+        Ok(())
+    }
+
+    pub fn unset_blocked_by(&mut self) -> &mut Self {
+        self.content.blocked_by(Some(vec![]));
+        self
+    }
+
+    pub fn unset_blocked_by_update(&mut self) -> &mut Self {
+        self.content.blocked_by(None);
+        self
+    }
+
     pub async fn send(&self) -> Result<OwnedEventId> {
         let room = self.room.clone();
+        let client = self.client.clone();
         let my_id = self.client.user_id()?;
         let content = self.content.build()?;
 
         RUNTIME
             .spawn(async move {
+                if let Some(task_list) = &content.task_list_id {
+                    match client.store().get(task_list.event_id.as_str()).await {
+                        Ok(AnyActerModel::TaskList(list)) if list.room_id() == room.room_id() => {}
+                        Ok(AnyActerModel::TaskList(_)) => {
+                            bail!("Tasks can only be moved to task lists in the same space")
+                        }
+                        _ => bail!("Task list not found"),
+                    }
+                }
+                if let Some(Some(state)) = &content.state {
+                    let task_list_id = match &content.task_list_id {
+                        Some(task_list) => task_list.event_id.clone(),
+                        None => {
+                            let AnyActerModel::Task(task) =
+                                client.store().get(content.task.event_id.as_str()).await?
+                            else {
+                                bail!("Task not found");
+                            };
+                            task.task_list_id.event_id.clone()
+                        }
+                    };
+                    ensure_task_list_state(&client, &task_list_id, state).await?;
+                }
+                if let Some(blocked_by) = &content.blocked_by {
+                    let task_id = &content.task.event_id;
+                    if models::Task::creates_dependency_cycle(client.store(), task_id, blocked_by)
+                        .await
+                    {
+                        bail!("Tasks can’t block each other in a cycle");
+                    }
+                }
                 let permitted = room
                     .can_user_send_message(&my_id, MessageLikeEventType::RoomMessage)
                     .await?;
@@ -1208,6 +2251,27 @@ impl TaskListUpdateBuilder {
         self
     }
 
+    pub fn add_state(
+        &mut self,
+        key: String,
+        name: String,
+        display: Option<Box<Display>>,
+    ) -> &mut Self {
+        self.content
+            .add_state(TaskState::new(key, name, display.map(|d| *d)));
+        self
+    }
+
+    pub fn unset_states(&mut self) -> &mut Self {
+        self.content.states(Some(vec![]));
+        self
+    }
+
+    pub fn unset_states_update(&mut self) -> &mut Self {
+        self.content.states(None);
+        self
+    }
+
     pub async fn send(&self) -> Result<OwnedEventId> {
         let room = self.room.clone();
         let my_id = self.client.user_id()?;
@@ -1250,4 +2314,178 @@ impl Space {
             content,
         })
     }
+
+    /// Draft a task without a list, it is sent to the inbox list
+    pub fn task_draft(&self) -> Result<TaskDraft> {
+        if !self.inner.is_joined() {
+            bail!("Unable to create tasks for spaces we are not part on");
+        }
+        Ok(TaskDraft {
+            client: self.client.clone(),
+            room: self.inner.room.clone(),
+            content: Default::default(),
+        })
+    }
+
+    pub async fn inbox_task_list(&self) -> Result<TaskList> {
+        self.special_task_list(SpecialTaskListRole::Inbox).await
+    }
+
+    pub async fn trash_task_list(&self) -> Result<TaskList> {
+        self.special_task_list(SpecialTaskListRole::Trash).await
+    }
+
+    async fn special_task_list(&self, role: SpecialTaskListRole) -> Result<TaskList> {
+        if !self.inner.is_joined() {
+            bail!("Unable to create tasks for spaces we are not part on");
+        }
+        let client = self.client.clone();
+        let room = self.inner.room.clone();
+
+        RUNTIME
+            .spawn(async move {
+                let content = special_task_list(&client, &room, role).await?;
+                Ok(TaskList {
+                    client,
+                    room,
+                    content,
+                })
+            })
+            .await?
+    }
+
+    /// Redact the tasks that have been in the trash for longer than the given days
+    ///
+    /// Returns how many tasks have been redacted, tasks we aren’t allowed to
+    /// redact are left in the trash.
+    pub async fn purge_trash(&self, older_than_days: u32) -> Result<u32> {
+        if !self.inner.is_joined() {
+            bail!("Unable to purge tasks for spaces we are not part on");
+        }
+        let client = self.client.clone();
+        let room = self.inner.room.clone();
+        let max_age = u64::from(older_than_days) * 24 * 60 * 60 * 1000;
+        let cutoff = u64::from(MilliSecondsSinceUnixEpoch::now().get()).saturating_sub(max_age);
+
+        RUNTIME
+            .spawn(async move {
+                let Some(trash) =
+                    find_special_task_list(&client, room.room_id(), SpecialTaskListRole::Trash)
+                        .await?
+                else {
+                    return Ok(0);
+                };
+                let mut purged = 0;
+                for mdl in client.store().get_list(&trash.tasks_key()).await? {
+                    let AnyActerModel::Task(task) = mdl else {
+                        continue;
+                    };
+                    let trashed_at = task.trashed_at().unwrap_or(task.meta.origin_server_ts);
+                    if u64::from(trashed_at.get()) > cutoff {
+                        continue;
+                    }
+                    if !can_redact(&room, task.sender()).await? {
+                        warn!(task_id = ?task.event_id(), "Not allowed to purge task from trash");
+                        continue;
+                    }
+                    room.redact(task.event_id(), Some("Purged from trash"), None)
+                        .await?;
+                    purged += 1;
+                }
+                Ok(purged)
+            })
+            .await?
+    }
+}
+
+fn write_ical_todos(todos: impl Iterator<Item = Todo>, file_name: String) -> Result<bool> {
+    let ical_data: String = (&todos.collect::<iCalendar>()).try_into()?;
+    std::fs::write(file_name, ical_data)?;
+    Ok(true)
+}
+
+async fn send_task_update(
+    client: &Client,
+    room: &Room,
+    content: tasks::TaskUpdateEventContent,
+) -> Result<OwnedEventId> {
+    let my_id = client.user_id()?;
+    let permitted = room
+        .can_user_send_message(&my_id, MessageLikeEventType::RoomMessage)
+        .await?;
+    if !permitted {
+        bail!("No permissions to send message in this room");
+    }
+    let response = room.send(content).await?;
+    Ok(response.event_id)
+}
+
+/// The special task list of the given role in the room, if it exists
+///
+/// Should several exist because they were created concurrently, everyone
+/// agrees on the one with the lowest event id.
+async fn find_special_task_list(
+    client: &Client,
+    room_id: &RoomId,
+    role: SpecialTaskListRole,
+) -> Result<Option<models::TaskList>> {
+    let key = format!("{room_id}::{}", KEYS::TASKS::TASKS);
+    let task_list = client
+        .store()
+        .get_list(&key)
+        .await?
+        .filter_map(|mdl| match mdl {
+            AnyActerModel::TaskList(task_list) if task_list.has_role(role) => Some(task_list),
+            _ => None,
+        })
+        .min_by(|a, b| a.event_id().cmp(b.event_id()));
+    Ok(task_list)
+}
+
+/// The special task list of the given role in the room, created on first use
+async fn special_task_list(
+    client: &Client,
+    room: &Room,
+    role: SpecialTaskListRole,
+) -> Result<models::TaskList> {
+    if let Some(task_list) = find_special_task_list(client, room.room_id(), role).await? {
+        return Ok(task_list);
+    }
+    let name = match role {
+        SpecialTaskListRole::Inbox => "Inbox",
+        SpecialTaskListRole::Trash => "Trash",
+    };
+    let content = TaskListBuilder::default()
+        .name(name.to_owned())
+        .role(role)
+        .build()?;
+    let my_id = client.user_id()?;
+    let permitted = room
+        .can_user_send_message(&my_id, MessageLikeEventType::RoomMessage)
+        .await?;
+    if !permitted {
+        bail!("No permissions to send message in this room");
+    }
+    let event_id = room.send(content).await?.event_id;
+    let AnyActerModel::TaskList(task_list) =
+        client.wait_for(event_id.to_string(), Some(30)).await?
+    else {
+        bail!("{event_id} is not a task list");
+    };
+    Ok(task_list)
+}
+
+async fn ensure_task_list_state(
+    client: &Client,
+    task_list_id: &EventId,
+    state: &str,
+) -> Result<()> {
+    let AnyActerModel::TaskList(task_list) = client.store().get(task_list_id.as_str()).await?
+    else {
+        bail!("Task list not found");
+    };
+    if !task_list.has_state(state) {
+        bail!("Task list doesn’t have a state {state}");
+    }
+    Ok(())
 }
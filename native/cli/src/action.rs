@@ -1,11 +1,13 @@
 use anyhow::Result;
 use clap::Subcommand;
 
+mod caldav;
 mod execute;
 mod history;
 mod list;
 mod manage;
 
+pub use caldav::CaldavServe;
 pub use execute::ExecuteOpts;
 pub use history::HistoryOpts;
 pub use list::List;
@@ -21,6 +23,8 @@ pub enum Action {
     History(HistoryOpts),
     /// Template Execution
     Execute(ExecuteOpts),
+    /// Serve the space calendars over read-only CalDAV
+    CaldavServe(CaldavServe),
 }
 
 impl Action {
@@ -30,6 +34,7 @@ impl Action {
             Action::List(config) => config.run().await?,
             Action::History(config) => config.run().await?,
             Action::Execute(config) => config.run().await?,
+            Action::CaldavServe(config) => config.run().await?,
         };
         Ok(())
     }
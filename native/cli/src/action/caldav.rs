@@ -0,0 +1,622 @@
+use acter::api::{CalendarEvent, Client, Space};
+use acter_core::models::{self, ical_calendar, ical_feed};
+use anyhow::{bail, Result};
+use clap::Parser;
+use futures::stream::StreamExt;
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    net::SocketAddr,
+    ops::Deref,
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+    time::timeout,
+};
+use tracing::{info, warn};
+
+use crate::config::LoginConfig;
+
+/// Requests with a larger body are refused
+const MAX_BODY_SIZE: usize = 1024 * 1024;
+/// Requests with a longer request or header line are refused
+const MAX_LINE_LENGTH: usize = 8 * 1024;
+/// Requests with more headers are refused
+const MAX_HEADERS: usize = 100;
+/// How long to wait for each part of a request before giving up on it
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+const CALENDAR_HOME: &str = "/calendars/";
+const PRINCIPAL: &str = "/principal/";
+
+/// Serve the calendars of the joined spaces over a read-only CalDAV endpoint
+#[derive(Parser, Debug)]
+pub struct CaldavServe {
+    #[clap(flatten)]
+    pub login: LoginConfig,
+
+    /// Address to listen on; there is no authentication, so keep it local
+    #[clap(long, default_value = "127.0.0.1:5232")]
+    pub bind: SocketAddr,
+}
+
+impl CaldavServe {
+    pub async fn run(&self) -> Result<()> {
+        let mut client = self.login.client().await?;
+        info!(" - Syncing -");
+        let sync_state = client.start_sync();
+
+        let mut is_synced = sync_state.first_synced_rx();
+        while is_synced.next().await != Some(true) {} // let's wait for it to have synced
+        info!(" - First Sync finished - ");
+
+        if !self.bind.ip().is_loopback() {
+            warn!(
+                "Serving calendars on {} without authentication, anyone reaching it can read them",
+                self.bind
+            );
+        }
+        let listener = TcpListener::bind(self.bind).await?;
+        println!("Serving CalDAV on http://{}{CALENDAR_HOME}", self.bind);
+
+        let bind = self.bind;
+        loop {
+            let (stream, peer) = listener.accept().await?;
+            let client = client.clone();
+            tokio::spawn(async move {
+                if let Err(error) = serve_connection(client, stream, bind).await {
+                    warn!(?error, %peer, "Failed to answer CalDAV request");
+                }
+            });
+        }
+    }
+}
+
+/// What a request path points to
+enum Target {
+    Principal,
+    WellKnown,
+    CalendarHome,
+    Calendar(String),
+    Event(String, String),
+    Unknown,
+}
+
+impl Target {
+    fn from_path(path: &str) -> Self {
+        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        match segments.as_slice() {
+            [] | ["principal"] => Target::Principal,
+            [".well-known", "caldav"] => Target::WellKnown,
+            ["calendars"] => Target::CalendarHome,
+            ["calendars", room_id] => Target::Calendar(room_id.to_string()),
+            ["calendars", room_id, file] => match file.strip_suffix(".ics") {
+                Some(event_id) => Target::Event(room_id.to_string(), event_id.to_owned()),
+                None => Target::Unknown,
+            },
+            _ => Target::Unknown,
+        }
+    }
+}
+
+struct Request {
+    method: String,
+    path: String,
+    host: Option<String>,
+    depth: String,
+    body: String,
+}
+
+struct Response {
+    status: &'static str,
+    headers: Vec<(&'static str, String)>,
+    body: String,
+}
+
+impl Response {
+    fn new(status: &'static str) -> Self {
+        Response {
+            status,
+            headers: vec![],
+            body: String::new(),
+        }
+    }
+
+    fn with_header(mut self, name: &'static str, value: impl Into<String>) -> Self {
+        self.headers.push((name, value.into()));
+        self
+    }
+
+    fn with_body(self, content_type: &'static str, body: String) -> Self {
+        Response { body, ..self }.with_header("Content-Type", content_type)
+    }
+
+    fn calendar(body: String) -> Self {
+        Response::new("200 OK").with_body("text/calendar; charset=utf-8", body)
+    }
+
+    fn multistatus(responses: Vec<String>) -> Self {
+        let body = format!(
+            "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<d:multistatus xmlns:d=\"DAV:\" xmlns:c=\"urn:ietf:params:xml:ns:caldav\" xmlns:cs=\"http://calendarserver.org/ns/\">{}</d:multistatus>",
+            responses.concat()
+        );
+        Response::new("207 Multi-Status").with_body("application/xml; charset=utf-8", body)
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        let mut head = format!(
+            "HTTP/1.1 {}\r\nDAV: 1, calendar-access\r\nConnection: close\r\nContent-Length: {}\r\n",
+            self.status,
+            self.body.len()
+        );
+        for (name, value) in self.headers {
+            head.push_str(&format!("{name}: {value}\r\n"));
+        }
+        head.push_str("\r\n");
+        let mut bytes = head.into_bytes();
+        bytes.extend(self.body.into_bytes());
+        bytes
+    }
+}
+
+async fn serve_connection(client: Client, stream: TcpStream, bind: SocketAddr) -> Result<()> {
+    let mut reader = BufReader::new(stream);
+    let Some(request) = read_request(&mut reader).await? else {
+        return Ok(());
+    };
+    let allowed = request
+        .host
+        .as_deref()
+        .map(|host| is_own_host(host, &bind))
+        .unwrap_or_default();
+    let response = if !allowed {
+        warn!(host = ?request.host, path = %request.path, "Refusing CalDAV request for another host");
+        Response::new("421 Misdirected Request")
+    } else {
+        match respond(&client, &request).await {
+            Ok(response) => response,
+            Err(error) => {
+                warn!(?error, method = %request.method, path = %request.path, "CalDAV request failed");
+                Response::new("500 Internal Server Error")
+            }
+        }
+    };
+    let mut stream = reader.into_inner();
+    stream.write_all(&response.into_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+/// A line of the request head, empty once the connection has been closed
+async fn read_line(reader: &mut BufReader<TcpStream>) -> Result<String> {
+    let mut line = vec![];
+    let mut limited = (&mut *reader).take(MAX_LINE_LENGTH as u64 + 1);
+    timeout(READ_TIMEOUT, limited.read_until(b'\n', &mut line)).await??;
+    if line.len() > MAX_LINE_LENGTH {
+        bail!("Request line longer than {MAX_LINE_LENGTH} bytes");
+    }
+    Ok(String::from_utf8_lossy(&line).into_owned())
+}
+
+async fn read_request(reader: &mut BufReader<TcpStream>) -> Result<Option<Request>> {
+    let request_line = read_line(reader).await?;
+    if request_line.is_empty() {
+        return Ok(None);
+    }
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_ascii_uppercase();
+    let target = parts.next().unwrap_or("/");
+    let path = percent_decode(target.split('?').next().unwrap_or_default());
+
+    let mut host = None;
+    let mut depth = "0".to_owned();
+    let mut content_length = 0;
+    let mut headers = 0;
+    loop {
+        let line = read_line(reader).await?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        headers += 1;
+        if headers > MAX_HEADERS {
+            bail!("Request with more than {MAX_HEADERS} headers");
+        }
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        match name.trim().to_ascii_lowercase().as_str() {
+            "host" => host = Some(value.trim().to_owned()),
+            "depth" => depth = value.trim().to_owned(),
+            "content-length" => content_length = value.trim().parse()?,
+            _ => {}
+        }
+    }
+    if content_length > MAX_BODY_SIZE {
+        bail!("Request body of {content_length} bytes is too large");
+    }
+    let mut body = vec![0; content_length];
+    timeout(READ_TIMEOUT, reader.read_exact(&mut body)).await??;
+
+    Ok(Some(Request {
+        method,
+        path,
+        host,
+        depth,
+        body: String::from_utf8_lossy(&body).into_owned(),
+    }))
+}
+
+async fn respond(client: &Client, request: &Request) -> Result<Response> {
+    let target = Target::from_path(&request.path);
+    let response = match (request.method.as_str(), target) {
+        ("OPTIONS", _) => {
+            Response::new("200 OK").with_header("Allow", "OPTIONS, GET, PROPFIND, REPORT")
+        }
+        (_, Target::WellKnown) => {
+            Response::new("301 Moved Permanently").with_header("Location", PRINCIPAL)
+        }
+        ("PUT" | "DELETE" | "PROPPATCH" | "MKCALENDAR" | "MKCOL" | "MOVE" | "COPY", _) => {
+            Response::new("403 Forbidden").with_body(
+                "text/plain; charset=utf-8",
+                "This calendar is read-only".to_owned(),
+            )
+        }
+        ("PROPFIND", Target::Principal) => {
+            let props = format!(
+                "<d:resourcetype><d:principal/><d:collection/></d:resourcetype><d:displayname>{}</d:displayname><d:current-user-principal><d:href>{PRINCIPAL}</d:href></d:current-user-principal><c:calendar-home-set><d:href>{CALENDAR_HOME}</d:href></c:calendar-home-set>",
+                xml_escape(client.user_id()?.as_str())
+            );
+            Response::multistatus(vec![prop_response(&request.path, &props)])
+        }
+        ("PROPFIND", Target::CalendarHome) => {
+            let props = format!(
+                "<d:resourcetype><d:collection/></d:resourcetype><d:current-user-principal><d:href>{PRINCIPAL}</d:href></d:current-user-principal>"
+            );
+            let mut responses = vec![prop_response(CALENDAR_HOME, &props)];
+            if request.depth != "0" {
+                for space in joined_spaces(client).await? {
+                    let events = space.calendar_events().await?;
+                    responses.push(calendar_response(&space, &events).await?);
+                }
+            }
+            Response::multistatus(responses)
+        }
+        ("PROPFIND", Target::Calendar(room_id)) => {
+            let Some(space) = find_space(client, &room_id).await? else {
+                return Ok(Response::new("404 Not Found"));
+            };
+            let events = space.calendar_events().await?;
+            let mut responses = vec![calendar_response(&space, &events).await?];
+            if request.depth != "0" {
+                for event in &events {
+                    responses.push(event_response(&space, event, None).await?);
+                }
+            }
+            Response::multistatus(responses)
+        }
+        ("PROPFIND", Target::Event(room_id, event_id)) => {
+            let Some((space, event)) = find_event(client, &room_id, &event_id).await? else {
+                return Ok(Response::new("404 Not Found"));
+            };
+            Response::multistatus(vec![event_response(&space, &event, None).await?])
+        }
+        ("REPORT", Target::Calendar(room_id)) => {
+            let Some(space) = find_space(client, &room_id).await? else {
+                return Ok(Response::new("404 Not Found"));
+            };
+            let mut events = space.calendar_events().await?;
+            if request.body.contains("calendar-multiget") {
+                let wanted = requested_hrefs(&request.body);
+                events.retain(|event| wanted.contains(&event_href(&space, event)));
+            }
+            let mut responses = vec![];
+            for event in &events {
+                let data = event_feed(client, std::slice::from_ref(event)).await?;
+                responses.push(event_response(&space, event, Some(&data)).await?);
+            }
+            Response::multistatus(responses)
+        }
+        ("GET", Target::Calendar(room_id)) => {
+            let Some(space) = find_space(client, &room_id).await? else {
+                return Ok(Response::new("404 Not Found"));
+            };
+            let events = space.calendar_events().await?;
+            Response::calendar(event_feed(client, &events).await?)
+                .with_header("ETag", format!("\"{}\"", calendar_tag(&events).await?))
+        }
+        ("GET", Target::Event(room_id, event_id)) => {
+            let Some((_space, event)) = find_event(client, &room_id, &event_id).await? else {
+                return Ok(Response::new("404 Not Found"));
+            };
+            let data = event_feed(client, std::slice::from_ref(&event)).await?;
+            Response::calendar(data)
+                .with_header("ETag", format!("\"{}\"", event_tag(&event).await?))
+        }
+        ("GET" | "PROPFIND" | "REPORT", Target::Unknown) => Response::new("404 Not Found"),
+        _ => Response::new("405 Method Not Allowed")
+            .with_header("Allow", "OPTIONS, GET, PROPFIND, REPORT"),
+    };
+    Ok(response)
+}
+
+/// The calendar events as iCalendar, with the RSVP of the logged in user
+async fn event_feed(client: &Client, events: &[CalendarEvent]) -> Result<String> {
+    let events: Vec<models::CalendarEvent> = events.iter().map(|e| e.deref().clone()).collect();
+    if events.is_empty() {
+        return Ok(ical_calendar(&events));
+    }
+    Ok(ical_feed(client.store(), &events, &client.user_id()?).await?)
+}
+
+async fn joined_spaces(client: &Client) -> Result<Vec<Space>> {
+    let spaces = client.spaces().await?;
+    Ok(spaces.into_iter().filter(|s| s.is_joined()).collect())
+}
+
+async fn find_space(client: &Client, room_id: &str) -> Result<Option<Space>> {
+    let spaces = joined_spaces(client).await?;
+    Ok(spaces.into_iter().find(|s| s.room_id().as_str() == room_id))
+}
+
+async fn find_event(
+    client: &Client,
+    room_id: &str,
+    event_id: &str,
+) -> Result<Option<(Space, CalendarEvent)>> {
+    let Some(space) = find_space(client, room_id).await? else {
+        return Ok(None);
+    };
+    let events = space.calendar_events().await?;
+    let event = events
+        .into_iter()
+        .find(|e| e.event_id().as_str() == event_id);
+    Ok(event.map(|e| (space, e)))
+}
+
+/// Whether the `Host` of a request names this server
+///
+/// Anything else might be a domain rebound to a local address, for a web page
+/// to read the calendars through the browser.
+fn is_own_host(host: &str, bind: &SocketAddr) -> bool {
+    let bound = bind.to_string();
+    let bound_name = bound.rsplit_once(':').map(|(name, _)| name);
+    let (name, port) = match host.rsplit_once(':') {
+        Some((name, port)) if !port.ends_with(']') => (name, port.parse().ok()),
+        // the default port may be left out
+        _ => (host, Some(80)),
+    };
+    port == Some(bind.port())
+        && ["localhost", "127.0.0.1", "[::1]"]
+            .into_iter()
+            .chain(bound_name)
+            .any(|own| own.eq_ignore_ascii_case(name))
+}
+
+fn calendar_href(space: &Space) -> String {
+    format!("{CALENDAR_HOME}{}/", space.room_id())
+}
+
+fn event_href(space: &Space, event: &CalendarEvent) -> String {
+    format!("{}{}.ics", calendar_href(space), event.event_id())
+}
+
+/// Changes whenever an event of the calendar is added, changed or removed,
+/// or the logged in user answers one differently
+async fn calendar_tag(events: &[CalendarEvent]) -> Result<String> {
+    let mut hasher = DefaultHasher::new();
+    for event in events {
+        event.event_id().hash(&mut hasher);
+        event_tag(event).await?.hash(&mut hasher);
+    }
+    Ok(format!("{:x}", hasher.finish()))
+}
+
+/// Changes whenever the event is updated or the logged in user answers it
+/// differently, as both end up in its calendar data
+async fn event_tag(event: &CalendarEvent) -> Result<String> {
+    let mut hasher = DefaultHasher::new();
+    event.sequence().hash(&mut hasher);
+    event
+        .responded_by_me()
+        .await?
+        .status_str()
+        .hash(&mut hasher);
+    Ok(format!("{:x}", hasher.finish()))
+}
+
+async fn calendar_response(space: &Space, events: &[CalendarEvent]) -> Result<String> {
+    let name = space.compute_display_name().await?.to_string();
+    let props = format!(
+        "<d:resourcetype><d:collection/><c:calendar/></d:resourcetype><d:displayname>{}</d:displayname><c:supported-calendar-component-set><c:comp name=\"VEVENT\"/></c:supported-calendar-component-set><d:current-user-privilege-set><d:privilege><d:read/></d:privilege></d:current-user-privilege-set><cs:getctag>{}</cs:getctag>",
+        xml_escape(&name),
+        calendar_tag(events).await?
+    );
+    Ok(prop_response(&calendar_href(space), &props))
+}
+
+async fn event_response(
+    space: &Space,
+    event: &CalendarEvent,
+    data: Option<&str>,
+) -> Result<String> {
+    let mut props = format!(
+        "<d:getetag>\"{}\"</d:getetag><d:getcontenttype>text/calendar; charset=utf-8</d:getcontenttype>",
+        event_tag(event).await?
+    );
+    if let Some(data) = data {
+        props.push_str(&format!(
+            "<c:calendar-data>{}</c:calendar-data>",
+            xml_escape(data)
+        ));
+    }
+    Ok(prop_response(&event_href(space, event), &props))
+}
+
+fn prop_response(href: &str, props: &str) -> String {
+    format!(
+        "<d:response><d:href>{}</d:href><d:propstat><d:prop>{props}</d:prop><d:status>HTTP/1.1 200 OK</d:status></d:propstat></d:response>",
+        xml_escape(href)
+    )
+}
+
+/// The decoded paths in the `href` elements of a multiget report
+fn requested_hrefs(body: &str) -> Vec<String> {
+    body.split("href>")
+        .skip(1)
+        .step_by(2)
+        .filter_map(|rest| rest.split('<').next())
+        .map(|href| percent_decode(&xml_unescape(href.trim())))
+        .map(|href| match href.find("://") {
+            // absolute URLs carry the host first
+            Some(scheme_end) => {
+                let after_host = &href[scheme_end + 3..];
+                after_host
+                    .find('/')
+                    .map(|path| after_host[path..].to_owned())
+                    .unwrap_or_default()
+            }
+            None => href,
+        })
+        .collect()
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn xml_unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let escaped = bytes
+            .get(index + 1..index + 3)
+            .filter(|_| bytes[index] == b'%')
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            None => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_percent_escapes() {
+        assert_eq!(
+            percent_decode("/calendars/%21room%3Aexample.org/%24event.ics"),
+            "/calendars/!room:example.org/$event.ics"
+        );
+        assert_eq!(percent_decode("caf%C3%A9"), "café");
+        // broken escapes are kept as they are
+        assert_eq!(percent_decode("100%zz"), "100%zz");
+        assert_eq!(percent_decode("trailing%4"), "trailing%4");
+    }
+
+    #[test]
+    fn unescapes_xml() {
+        assert_eq!(
+            xml_unescape("&lt;d:href&gt; &quot;a&apos;s&quot; &amp;lt;"),
+            "<d:href> \"a's\" &lt;"
+        );
+    }
+
+    #[test]
+    fn finds_requested_hrefs() {
+        let body = r#"<?xml version="1.0" encoding="utf-8"?>
+<c:calendar-multiget xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:prop><d:getetag/><c:calendar-data/></d:prop>
+  <d:href>/calendars/%21room%3Aexample.org/%24one.ics</d:href>
+  <d:href> https://dav.example.org:5232/calendars/!room:example.org/$two.ics </d:href>
+  <href>/calendars/!room:example.org/$three&amp;four.ics</href>
+</c:calendar-multiget>"#;
+        assert_eq!(
+            requested_hrefs(body),
+            [
+                "/calendars/!room:example.org/$one.ics",
+                "/calendars/!room:example.org/$two.ics",
+                "/calendars/!room:example.org/$three&four.ics",
+            ]
+        );
+    }
+
+    #[test]
+    fn accepts_only_own_hosts() {
+        let bind: SocketAddr = "127.0.0.1:5232".parse().unwrap();
+        for host in [
+            "127.0.0.1:5232",
+            "localhost:5232",
+            "LOCALHOST:5232",
+            "[::1]:5232",
+        ] {
+            assert!(is_own_host(host, &bind), "{host} wasn’t accepted");
+        }
+        for host in [
+            "attacker.example.org:5232",
+            "127.0.0.1.example.org:5232",
+            "localhost:8080",
+            "localhost",
+            "",
+        ] {
+            assert!(!is_own_host(host, &bind), "{host} was accepted");
+        }
+        let default_port: SocketAddr = "[::1]:80".parse().unwrap();
+        assert!(is_own_host("[::1]", &default_port));
+        assert!(is_own_host("localhost", &default_port));
+    }
+
+    #[test]
+    fn resolves_paths() {
+        assert!(matches!(Target::from_path("/"), Target::Principal));
+        assert!(matches!(Target::from_path(PRINCIPAL), Target::Principal));
+        assert!(matches!(
+            Target::from_path("/.well-known/caldav"),
+            Target::WellKnown
+        ));
+        assert!(matches!(
+            Target::from_path(CALENDAR_HOME),
+            Target::CalendarHome
+        ));
+        assert!(matches!(
+            Target::from_path("/calendars/!room:example.org/"),
+            Target::Calendar(room_id) if room_id == "!room:example.org"
+        ));
+        assert!(matches!(
+            Target::from_path("/calendars/!room:example.org/$event.ics"),
+            Target::Event(room_id, event_id)
+                if room_id == "!room:example.org" && event_id == "$event"
+        ));
+        for unknown in [
+            "/calendars/!room:example.org/notes.txt",
+            "/calendars/!room:example.org/$event.ics/more",
+            "/addressbooks/",
+        ] {
+            assert!(matches!(Target::from_path(unknown), Target::Unknown));
+        }
+    }
+}
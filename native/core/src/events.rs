@@ -42,6 +42,8 @@ pub enum AnyActerEvent {
     TaskUpdate(tasks::TaskUpdateEvent),
     TaskSelfAssign(tasks::TaskSelfAssignEvent),
     TaskSelfUnassign(tasks::TaskSelfUnassignEvent),
+    TaskAssignment(tasks::TaskAssignmentEvent),
+    TaskTimeLog(tasks::TaskTimeLogEvent),
 
     // Generic Relative Features
     Comment(comments::CommentEvent),
@@ -76,6 +78,8 @@ impl AnyActerEvent {
             AnyActerEvent::TaskUpdate(e) => e.room_id(),
             AnyActerEvent::TaskSelfAssign(e) => e.room_id(),
             AnyActerEvent::TaskSelfUnassign(e) => e.room_id(),
+            AnyActerEvent::TaskAssignment(e) => e.room_id(),
+            AnyActerEvent::TaskTimeLog(e) => e.room_id(),
             AnyActerEvent::Comment(e) => e.room_id(),
             AnyActerEvent::CommentUpdate(e) => e.room_id(),
             AnyActerEvent::AttachmentUpdate(e) => e.room_id(),
@@ -174,6 +178,18 @@ impl<'de> serde::Deserialize<'de> for AnyActerEvent {
                 Ok(Self::TaskSelfUnassign(event))
             }
 
+            tasks::TaskAssignmentEventContent::TYPE => {
+                let event = smart_serde_json::from_str::<tasks::TaskAssignmentEvent>(json.get())
+                    .map_err(D::Error::custom)?;
+                Ok(Self::TaskAssignment(event))
+            }
+
+            tasks::TaskTimeLogEventContent::TYPE => {
+                let event = smart_serde_json::from_str::<tasks::TaskTimeLogEvent>(json.get())
+                    .map_err(D::Error::custom)?;
+                Ok(Self::TaskTimeLog(event))
+            }
+
             comments::CommentEventContent::TYPE => {
                 let event = smart_serde_json::from_str::<comments::CommentEvent>(json.get())
                     .map_err(D::Error::custom)?;
@@ -243,6 +259,8 @@ impl<'de> serde::Deserialize<'de> for AnyActerEvent {
                             tasks::TaskUpdateEventContent::TYPE,
                             tasks::TaskSelfAssignEventContent::TYPE,
                             tasks::TaskSelfUnassignEventContent::TYPE,
+                            tasks::TaskAssignmentEventContent::TYPE,
+                            tasks::TaskTimeLogEventContent::TYPE,
                             comments::CommentEventContent::TYPE,
                             comments::CommentUpdateEventContent::TYPE,
                             attachments::AttachmentEventContent::TYPE,
@@ -279,6 +297,8 @@ pub enum SyncAnyActerEvent {
     TaskUpdate(tasks::SyncTaskUpdateEvent),
     TaskSelfAssign(tasks::SyncTaskSelfAssignEvent),
     TaskSelfUnassign(tasks::SyncTaskSelfUnassignEvent),
+    TaskAssignment(tasks::SyncTaskAssignmentEvent),
+    TaskTimeLog(tasks::SyncTaskTimeLogEvent),
 
     // Generic Relative Features
     Comment(comments::SyncCommentEvent),
@@ -314,6 +334,8 @@ impl SyncAnyActerEvent {
             Self::TaskSelfUnassign(e) => {
                 AnyActerEvent::TaskSelfUnassign(e.into_full_event(room_id))
             }
+            Self::TaskAssignment(e) => AnyActerEvent::TaskAssignment(e.into_full_event(room_id)),
+            Self::TaskTimeLog(e) => AnyActerEvent::TaskTimeLog(e.into_full_event(room_id)),
             Self::Comment(e) => AnyActerEvent::Comment(e.into_full_event(room_id)),
             Self::CommentUpdate(e) => AnyActerEvent::CommentUpdate(e.into_full_event(room_id)),
             Self::Attachment(e) => AnyActerEvent::Attachment(e.into_full_event(room_id)),
@@ -421,6 +443,19 @@ impl<'de> serde::Deserialize<'de> for SyncAnyActerEvent {
                 Ok(Self::TaskSelfUnassign(event))
             }
 
+            tasks::TaskAssignmentEventContent::TYPE => {
+                let event =
+                    smart_serde_json::from_str::<tasks::SyncTaskAssignmentEvent>(json.get())
+                        .map_err(D::Error::custom)?;
+                Ok(Self::TaskAssignment(event))
+            }
+
+            tasks::TaskTimeLogEventContent::TYPE => {
+                let event = smart_serde_json::from_str::<tasks::SyncTaskTimeLogEvent>(json.get())
+                    .map_err(D::Error::custom)?;
+                Ok(Self::TaskTimeLog(event))
+            }
+
             comments::CommentEventContent::TYPE => {
                 let event = smart_serde_json::from_str::<comments::SyncCommentEvent>(json.get())
                     .map_err(D::Error::custom)?;
@@ -486,6 +521,8 @@ impl<'de> serde::Deserialize<'de> for SyncAnyActerEvent {
                     tasks::TaskUpdateEventContent::TYPE,
                     tasks::TaskSelfAssignEventContent::TYPE,
                     tasks::TaskSelfUnassignEventContent::TYPE,
+                    tasks::TaskAssignmentEventContent::TYPE,
+                    tasks::TaskTimeLogEventContent::TYPE,
                     comments::CommentEventContent::TYPE,
                     comments::CommentUpdateEventContent::TYPE,
                     attachments::AttachmentEventContent::TYPE,
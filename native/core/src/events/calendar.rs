@@ -2,6 +2,8 @@ use derive_builder::Builder;
 use derive_getters::Getters;
 use matrix_sdk_base::ruma::events::{macros::EventContent, room::message::TextMessageEventContent};
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use strum::ParseError;
 use tracing::trace;
 
 use crate::{models::TextMessageContent, util::deserialize_some, Result};
@@ -10,7 +12,35 @@ use crate::{models::TextMessageContent, util::deserialize_some, Result};
 /// modeled after [JMAP Calendar Events](https://jmap.io/spec-calendars.html#calendar-events), extensions to
 /// [ietf rfc8984](https://www.rfc-editor.org/rfc/rfc8984.html#name-event).
 ///
-use super::{Display, Icon, Update, UtcDateTime};
+use super::{Display, Icon, RecurrenceRule, Update, UtcDateTime};
+
+/// Whether an event is going to take place
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize, strum::Display)]
+#[serde(rename_all = "kebab-case")]
+#[strum(serialize_all = "kebab-case")]
+pub enum EventStatus {
+    #[default]
+    Confirmed,
+    Tentative,
+    /// Not taking place, kept around for those who planned to attend
+    Cancelled,
+    /// Not taking place at the planned time, a new one is to be found
+    Postponed,
+}
+
+impl FromStr for EventStatus {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "confirmed" => Ok(EventStatus::Confirmed),
+            "tentative" => Ok(EventStatus::Tentative),
+            "cancelled" => Ok(EventStatus::Cancelled),
+            "postponed" => Ok(EventStatus::Postponed),
+            _ => Err(ParseError::VariantNotFound),
+        }
+    }
+}
 
 /// Event Location
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -55,6 +85,29 @@ pub enum EventLocation {
     },
 }
 
+/// A change to a single occurrence of a recurring event
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OccurrenceOverride {
+    /// The original start of the occurrence this applies to
+    pub recurrence_id: UtcDateTime,
+
+    /// The occurrence has been moved to start at this time instead
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub utc_start: Option<UtcDateTime>,
+
+    /// The occurrence has been moved to end at this time instead
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub utc_end: Option<UtcDateTime>,
+
+    /// A different title just for this occurrence
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+
+    /// This occurrence doesn’t take place
+    #[serde(default)]
+    pub cancelled: bool,
+}
+
 pub struct EventLocationInfo {
     pub inner: EventLocation,
 }
@@ -145,7 +198,7 @@ impl EventLocationInfo {
 ///
 /// modeled after [JMAP Calendar Events](https://jmap.io/spec-calendars.html#calendar-events)
 /// see also the [IETF CalendarEvent](https://www.rfc-editor.org/rfc/rfc8984.html#name-event)
-/// but all timezones have been dumbed down to UTC-only.
+/// with times kept in UTC, an optional time zone tells where they were planned.
 #[derive(Clone, Debug, Deserialize, Serialize, EventContent, Builder, Getters)]
 #[ruma_event(type = "global.acter.dev.calendar_event", kind = MessageLike)]
 #[builder(name = "CalendarEventBuilder", derive(Debug))]
@@ -176,6 +229,13 @@ pub struct CalendarEventEventContent {
     #[serde(default)]
     pub show_without_time: bool,
 
+    /// The time zone the event was planned in, recurrences keep their local
+    /// time of day in it. Kept as the IANA name, so zones we don’t know yet
+    /// don’t break the event
+    #[builder(setter(into), default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_zone: Option<String>,
+
     /// Where is this event happening?
     #[builder(default)]
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
@@ -189,6 +249,50 @@ pub struct CalendarEventEventContent {
     #[builder(setter(into), default)]
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub categories: Vec<String>,
+
+    /// Repeat this event, starting from `utc_start`, as defined by this
+    /// `RRULE` value, see [`RecurrenceRule`]
+    #[builder(setter(into), default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recurrence: Option<String>,
+
+    /// Further starts of this event, in addition to the rule (`RDATE`)
+    #[builder(setter(into), default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recurrence_dates: Vec<UtcDateTime>,
+
+    /// Starts the rule yields on which the event doesn’t happen (`EXDATE`)
+    #[builder(setter(into), default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub excluded_dates: Vec<UtcDateTime>,
+
+    /// Single occurrences that have been moved, renamed or cancelled
+    #[builder(setter(into), default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub occurrence_overrides: Vec<OccurrenceOverride>,
+
+    /// Identifies the event this one was imported from, e.g. the UID of a
+    /// VEVENT
+    #[builder(setter(into), default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub external_uid: Option<String>,
+
+    /// How many people can attend, further yes-RSVPs go onto a waitlist
+    #[builder(setter(into), default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capacity: Option<u32>,
+
+    /// Whether the event is going to take place
+    #[builder(default)]
+    #[serde(default)]
+    pub status: EventStatus,
+}
+
+impl CalendarEventEventContent {
+    /// The rule to repeat this event by, None if it has none we support
+    pub fn recurrence_rule(&self) -> Option<RecurrenceRule> {
+        RecurrenceRule::from_event(self.recurrence.as_deref()?)
+    }
 }
 
 impl CalendarEventBuilder {
@@ -200,6 +304,20 @@ impl CalendarEventBuilder {
             .push(event_loc);
         self.clone()
     }
+
+    /// Have the event occur at this time in addition to its rule
+    pub fn add_recurrence_date(&mut self, date: UtcDateTime) -> &mut Self {
+        self.recurrence_dates
+            .get_or_insert_with(Vec::new)
+            .push(date);
+        self
+    }
+
+    /// Skip the occurrence the rule yields at this time
+    pub fn add_excluded_date(&mut self, date: UtcDateTime) -> &mut Self {
+        self.excluded_dates.get_or_insert_with(Vec::new).push(date);
+        self
+    }
 }
 
 /// The CalendarEvent Update Event
@@ -257,6 +375,15 @@ pub struct CalendarEventUpdateEventContent {
     )]
     pub show_without_time: Option<bool>,
 
+    /// The time zone the event was planned in
+    #[builder(default)]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_some"
+    )]
+    pub time_zone: Option<Option<String>>,
+
     /// Where is this event happening?
     #[builder(default)]
     #[serde(
@@ -291,6 +418,60 @@ pub struct CalendarEventUpdateEventContent {
         deserialize_with = "deserialize_some"
     )]
     pub display: Option<Option<Display>>,
+
+    /// Repeat this event as defined by this rule
+    #[builder(default)]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_some"
+    )]
+    pub recurrence: Option<Option<String>>,
+
+    /// Further starts of this event, in addition to the rule
+    #[builder(default)]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_some"
+    )]
+    pub recurrence_dates: Option<Vec<UtcDateTime>>,
+
+    /// Starts the rule yields on which the event doesn’t happen
+    #[builder(default)]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_some"
+    )]
+    pub excluded_dates: Option<Vec<UtcDateTime>>,
+
+    /// Single occurrences that have been moved, renamed or cancelled
+    #[builder(default)]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_some"
+    )]
+    pub occurrence_overrides: Option<Vec<OccurrenceOverride>>,
+
+    /// How many people can attend
+    #[builder(default)]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_some"
+    )]
+    pub capacity: Option<Option<u32>>,
+
+    /// Whether the event is going to take place
+    #[builder(default)]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_some"
+    )]
+    pub status: Option<EventStatus>,
 }
 
 impl CalendarEventUpdateEventContent {
@@ -326,6 +507,11 @@ impl CalendarEventUpdateEventContent {
             updated = true;
         }
 
+        if let Some(time_zone) = &self.time_zone {
+            calendar_event.time_zone.clone_from(time_zone);
+            updated = true;
+        }
+
         if let Some(display) = &self.display {
             calendar_event.display.clone_from(display);
             updated = true;
@@ -341,6 +527,38 @@ impl CalendarEventUpdateEventContent {
             updated = true;
         }
 
+        if let Some(recurrence) = &self.recurrence {
+            calendar_event.recurrence.clone_from(recurrence);
+            updated = true;
+        }
+
+        if let Some(recurrence_dates) = &self.recurrence_dates {
+            calendar_event.recurrence_dates.clone_from(recurrence_dates);
+            updated = true;
+        }
+
+        if let Some(excluded_dates) = &self.excluded_dates {
+            calendar_event.excluded_dates.clone_from(excluded_dates);
+            updated = true;
+        }
+
+        if let Some(occurrence_overrides) = &self.occurrence_overrides {
+            calendar_event
+                .occurrence_overrides
+                .clone_from(occurrence_overrides);
+            updated = true;
+        }
+
+        if let Some(capacity) = &self.capacity {
+            calendar_event.capacity = *capacity;
+            updated = true;
+        }
+
+        if let Some(status) = &self.status {
+            calendar_event.status = *status;
+            updated = true;
+        }
+
         trace!(update = ?self, ?updated, ?calendar_event, "CalendarEvent updated");
 
         Ok(updated)
@@ -0,0 +1,359 @@
+use chrono::{DateTime, Datelike, Days, Months, NaiveDate, NaiveDateTime, Utc, Weekday};
+use std::{fmt, str::FromStr};
+use tracing::debug;
+
+/// How often the recurrence repeats
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+impl Frequency {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+            Frequency::Yearly => "YEARLY",
+        }
+    }
+}
+
+/// A recurrence rule as defined in
+/// [RFC 5545 Section 3.3.10](https://www.rfc-editor.org/rfc/rfc5545#section-3.3.10)
+/// and used by [RFC 8984](https://www.rfc-editor.org/rfc/rfc8984.html#name-recurrencerules).
+///
+/// Only the date-level subset is supported: `FREQ` (daily to yearly),
+/// `INTERVAL`, `COUNT`, `UNTIL` and plain weekdays in `BYDAY`. Events carry
+/// the `RRULE` value string, e.g. `FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH`, as it
+/// is and only parse it when expanding, so other clients may use parts of
+/// the RFC we don’t support without breaking the whole event.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecurrenceRule {
+    frequency: Frequency,
+    interval: u32,
+    count: Option<u32>,
+    until: Option<NaiveDate>,
+    by_day: Vec<Weekday>,
+}
+
+impl RecurrenceRule {
+    pub fn new(frequency: Frequency) -> Self {
+        RecurrenceRule {
+            frequency,
+            interval: 1,
+            count: None,
+            until: None,
+            by_day: vec![],
+        }
+    }
+
+    /// The rule an event carries, None if it uses parts we don’t support
+    ///
+    /// Such an event doesn’t repeat here, but is kept as it is.
+    pub fn from_event(rule: &str) -> Option<Self> {
+        match rule.parse() {
+            Ok(rule) => Some(rule),
+            Err(error) => {
+                debug!(rule, %error, "Unsupported recurrence rule, not repeating");
+                None
+            }
+        }
+    }
+
+    pub fn frequency(&self) -> Frequency {
+        self.frequency
+    }
+
+    pub fn interval(&self) -> u32 {
+        self.interval
+    }
+
+    /// How many occurrences are left, including the current one
+    pub fn count(&self) -> Option<u32> {
+        self.count
+    }
+
+    pub fn until(&self) -> Option<NaiveDate> {
+        self.until
+    }
+
+    pub fn by_day(&self) -> &[Weekday] {
+        &self.by_day
+    }
+
+    /// The date of the occurrence following the one on `current`
+    ///
+    /// None if the rule is exhausted. Monthly and yearly rules skip the months
+    /// lacking the day of `current`, as RFC 5545 has it, so they stay on the
+    /// day they started on.
+    pub fn next_after(&self, current: NaiveDate) -> Option<NaiveDate> {
+        if self.count.map(|c| c <= 1).unwrap_or_default() {
+            return None;
+        }
+        let interval = self.interval.max(1);
+        let next = match self.frequency {
+            Frequency::Daily => current.checked_add_days(Days::new(interval.into())),
+            Frequency::Weekly if !self.by_day.is_empty() => self.next_weekday(current, interval),
+            Frequency::Weekly => current.checked_add_days(Days::new(7 * u64::from(interval))),
+            Frequency::Monthly => next_same_day(current, interval),
+            Frequency::Yearly => next_same_day(current, 12 * interval),
+        }?;
+        if self.until.map(|u| next > u).unwrap_or_default() {
+            return None;
+        }
+        Some(next)
+    }
+
+    /// The rule to carry on to the next occurrence
+    ///
+    /// Counts down `COUNT`, None if no further occurrences follow after that.
+    pub fn remaining(&self) -> Option<RecurrenceRule> {
+        let mut rule = self.clone();
+        if let Some(count) = self.count {
+            if count <= 1 {
+                return None;
+            }
+            rule.count = Some(count - 1);
+        }
+        Some(rule)
+    }
+
+    /// The `RRULE` value with `UNTIL` given as an instant
+    ///
+    /// RFC 5545 wants `UNTIL` to be a UTC date-time when `DTSTART` is one.
+    pub fn to_string_until(&self, until: DateTime<Utc>) -> String {
+        let mut rule = String::new();
+        let _ = self.write_parts(&mut rule, Some(until.format("%Y%m%dT%H%M%SZ").to_string()));
+        rule
+    }
+
+    fn write_parts(&self, f: &mut impl fmt::Write, until: Option<String>) -> fmt::Result {
+        write!(f, "FREQ={}", self.frequency.as_str())?;
+        if self.interval != 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if let Some(count) = self.count {
+            write!(f, ";COUNT={count}")?;
+        }
+        if let Some(until) = until {
+            write!(f, ";UNTIL={until}")?;
+        }
+        if !self.by_day.is_empty() {
+            let days = self
+                .by_day
+                .iter()
+                .map(weekday_code)
+                .collect::<Vec<_>>()
+                .join(",");
+            write!(f, ";BYDAY={days}")?;
+        }
+        Ok(())
+    }
+
+    fn next_weekday(&self, current: NaiveDate, interval: u32) -> Option<NaiveDate> {
+        // weeks start on monday, as per the RFC default of `WKST`
+        let week_start = |d: NaiveDate| d - Days::new(d.weekday().num_days_from_monday().into());
+        let first_week = week_start(current);
+        let mut day = current;
+        for _ in 0..(7 * (interval + 1)) {
+            day = day.succ_opt()?;
+            let weeks = (week_start(day) - first_week).num_weeks() as u32;
+            if weeks % interval == 0 && self.by_day.contains(&day.weekday()) {
+                return Some(day);
+            }
+        }
+        None
+    }
+}
+
+/// The first date `months` apart from `current`, or a multiple of that, with
+/// the same day of the month
+fn next_same_day(current: NaiveDate, months: u32) -> Option<NaiveDate> {
+    // enough to get from one 29th of February to the next, even across a
+    // century that isn’t a leap year
+    (1..=96).find_map(|n| {
+        let next = current.checked_add_months(Months::new(months.checked_mul(n)?))?;
+        (next.day() == current.day()).then_some(next)
+    })
+}
+
+fn weekday_code(day: &Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+fn parse_weekday(code: &str) -> Result<Weekday, String> {
+    Ok(match code {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return Err(format!("Unsupported BYDAY value: {code}")),
+    })
+}
+
+fn parse_until(value: &str) -> Result<NaiveDate, String> {
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y%m%d") {
+        return Ok(date);
+    }
+    NaiveDateTime::parse_from_str(value.trim_end_matches('Z'), "%Y%m%dT%H%M%S")
+        .map(|dt| dt.date())
+        .map_err(|e| format!("Invalid UNTIL value {value}: {e}"))
+}
+
+impl FromStr for RecurrenceRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let s = s.strip_prefix("RRULE:").unwrap_or(s);
+        let mut frequency = None;
+        let mut rule = RecurrenceRule::new(Frequency::Daily);
+        for part in s.split(';').filter(|p| !p.is_empty()) {
+            let Some((key, value)) = part.split_once('=') else {
+                return Err(format!("Invalid recurrence rule part: {part}"));
+            };
+            match key.to_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.to_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => return Err(format!("Unsupported frequency: {value}")),
+                    })
+                }
+                "INTERVAL" => {
+                    rule.interval = value
+                        .parse()
+                        .map_err(|e| format!("Invalid INTERVAL {value}: {e}"))?;
+                    if rule.interval == 0 {
+                        return Err("INTERVAL must be a positive number".to_string());
+                    }
+                }
+                "COUNT" => {
+                    rule.count = Some(
+                        value
+                            .parse()
+                            .map_err(|e| format!("Invalid COUNT {value}: {e}"))?,
+                    )
+                }
+                "UNTIL" => rule.until = Some(parse_until(value)?),
+                "BYDAY" => {
+                    rule.by_day = value
+                        .split(',')
+                        .map(|d| parse_weekday(&d.to_uppercase()))
+                        .collect::<Result<_, _>>()?
+                }
+                "WKST" if value.eq_ignore_ascii_case("MO") => {}
+                _ => return Err(format!("Unsupported recurrence rule part: {part}")),
+            }
+        }
+        rule.frequency = frequency.ok_or_else(|| "FREQ is required".to_string())?;
+        if rule.count.is_some() && rule.until.is_some() {
+            return Err("COUNT and UNTIL can’t both be set".to_string());
+        }
+        if !rule.by_day.is_empty() && rule.frequency != Frequency::Weekly {
+            return Err("BYDAY is only supported for weekly recurrence".to_string());
+        }
+        Ok(rule)
+    }
+}
+
+impl fmt::Display for RecurrenceRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let until = self.until.map(|u| u.format("%Y%m%d").to_string());
+        self.write_parts(f, until)
+    }
+}
+
+impl TryFrom<String> for RecurrenceRule {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<RecurrenceRule> for String {
+    fn from(value: RecurrenceRule) -> Self {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn parse_roundtrip() -> Result<(), String> {
+        let rule: RecurrenceRule = "RRULE:FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH".parse()?;
+        assert_eq!(rule.frequency(), Frequency::Weekly);
+        assert_eq!(rule.interval(), 2);
+        assert_eq!(rule.by_day(), &[Weekday::Mon, Weekday::Thu]);
+        assert_eq!(rule.to_string(), "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH");
+
+        assert!("INTERVAL=2".parse::<RecurrenceRule>().is_err());
+        assert!("FREQ=HOURLY".parse::<RecurrenceRule>().is_err());
+        assert!("FREQ=DAILY;BYDAY=MO".parse::<RecurrenceRule>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn next_occurrences() -> Result<(), String> {
+        // a friday
+        let due = date(2024, 5, 3);
+
+        let weekly: RecurrenceRule = "FREQ=WEEKLY".parse()?;
+        assert_eq!(weekly.next_after(due), Some(date(2024, 5, 10)));
+
+        let mo_th: RecurrenceRule = "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH".parse()?;
+        assert_eq!(mo_th.next_after(due), Some(date(2024, 5, 13)));
+        assert_eq!(mo_th.next_after(date(2024, 5, 13)), Some(date(2024, 5, 16)));
+
+        let monthly: RecurrenceRule = "FREQ=MONTHLY".parse()?;
+        assert_eq!(
+            monthly.next_after(date(2024, 1, 31)),
+            Some(date(2024, 3, 31))
+        );
+        assert_eq!(
+            monthly.next_after(date(2024, 3, 31)),
+            Some(date(2024, 5, 31))
+        );
+
+        let leap_day: RecurrenceRule = "FREQ=YEARLY".parse()?;
+        assert_eq!(
+            leap_day.next_after(date(2096, 2, 29)),
+            Some(date(2104, 2, 29))
+        );
+
+        let until: RecurrenceRule = "FREQ=DAILY;UNTIL=20240504".parse()?;
+        assert_eq!(until.next_after(due), Some(date(2024, 5, 4)));
+        assert_eq!(until.next_after(date(2024, 5, 4)), None);
+
+        let count: RecurrenceRule = "FREQ=DAILY;COUNT=2".parse()?;
+        let remaining = count.remaining().expect("one more to go");
+        assert_eq!(remaining.count(), Some(1));
+        assert_eq!(remaining.next_after(due), None);
+        assert_eq!(remaining.remaining(), None);
+        Ok(())
+    }
+}
@@ -35,10 +35,17 @@ impl FromStr for RsvpStatus {
     }
 }
 
+/// How many guests one can bring along at most
+pub const MAX_GUESTS: u32 = 10;
+
 /// The RSVP Event
 #[derive(Clone, Debug, Deserialize, Serialize, EventContent, Builder)]
 #[ruma_event(type = "global.acter.dev.rsvp", kind = MessageLike)]
-#[builder(name = "RsvpBuilder", derive(Debug))]
+#[builder(
+    name = "RsvpBuilder",
+    build_fn(validate = "Self::validate"),
+    derive(Debug)
+)]
 pub struct RsvpEventContent {
     #[builder(setter(into))]
     #[serde(rename = "m.relates_to")]
@@ -46,4 +53,34 @@ pub struct RsvpEventContent {
 
     /// The status responded by this user
     pub status: RsvpStatus,
+
+    /// How many people this user brings along, up to [`MAX_GUESTS`]
+    #[builder(setter(into), default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub guests: Option<u32>,
+
+    /// A short remark for the organisers, e.g. "arriving late"
+    #[builder(setter(into), default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+impl RsvpEventContent {
+    /// How many people come with this response, including its sender
+    ///
+    /// Guests beyond [`MAX_GUESTS`] sent by other clients aren’t counted.
+    pub fn party_size(&self) -> u32 {
+        1 + self.guests.unwrap_or_default().min(MAX_GUESTS)
+    }
+}
+
+impl RsvpBuilder {
+    fn validate(&self) -> Result<(), String> {
+        if let Some(Some(guests)) = &self.guests {
+            if *guests > MAX_GUESTS {
+                return Err(format!("One can bring at most {MAX_GUESTS} guests"));
+            }
+        }
+        Ok(())
+    }
 }
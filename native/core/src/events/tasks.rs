@@ -2,7 +2,10 @@ use chrono_tz::Tz;
 use core::result::Result as CoreResult;
 use derive_builder::Builder;
 use derive_getters::Getters;
-use matrix_sdk_base::ruma::events::{macros::EventContent, room::message::TextMessageEventContent};
+use matrix_sdk_base::ruma::{
+    events::{macros::EventContent, room::message::TextMessageEventContent},
+    OwnedEventId, OwnedUserId,
+};
 use serde::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
 use tracing::trace;
@@ -11,12 +14,15 @@ use tracing::trace;
 /// modeled after [JMAP Tasks](https://jmap.io/spec-tasks.html), extensions to
 /// [ietf rfc8984](https://www.rfc-editor.org/rfc/rfc8984.html#name-task).
 ///
-use super::{BelongsTo, Date, Display, Update, UtcDateTime};
+use super::{BelongsTo, Date, Display, RecurrenceRule, Update, UtcDateTime};
 use crate::{util::deserialize_some, Result as ActerResult};
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
+/// Lists a space manages on its own, there is at most one of each per space
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
 pub enum SpecialTaskListRole {
+    /// Where tasks end up that were sent without a list
     Inbox,
+    /// Where deleted tasks are kept until they are restored or purged
     Trash,
 }
 
@@ -43,6 +49,50 @@ impl Priority {
     fn is_undefinied(&self) -> bool {
         matches!(self, Priority::Undefined)
     }
+
+    /// The priority of rank 1 (highest) to 9 (lowest), undefined otherwise
+    pub fn from_rank(rank: u8) -> Self {
+        match rank {
+            1 => Priority::Highest,
+            2 => Priority::SecondHighest,
+            3 => Priority::Three,
+            4 => Priority::Four,
+            5 => Priority::Five,
+            6 => Priority::Six,
+            7 => Priority::Seven,
+            8 => Priority::SecondLowest,
+            9 => Priority::Lowest,
+            _ => Priority::Undefined,
+        }
+    }
+}
+
+/// A workflow state of a TaskList, e.g. a column on a board
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct TaskState {
+    /// Identifies the state within the list, referenced by the tasks
+    pub key: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display: Option<Display>,
+}
+
+impl TaskState {
+    pub fn new(key: String, name: String, display: Option<Display>) -> Self {
+        TaskState { key, name, display }
+    }
+
+    pub fn key(&self) -> String {
+        self.key.clone()
+    }
+
+    pub fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    pub fn display(&self) -> Option<Display> {
+        self.display.clone()
+    }
 }
 
 /// The TaskList Event
@@ -50,7 +100,11 @@ impl Priority {
 /// modeled after [JMAP TaskList](https://jmap.io/spec-tasks.html#tasklists)
 #[derive(Clone, Debug, Deserialize, Serialize, EventContent, Builder, Getters)]
 #[ruma_event(type = "global.acter.dev.tasklist", kind = MessageLike)]
-#[builder(name = "TaskListBuilder", derive(Debug))]
+#[builder(
+    name = "TaskListBuilder",
+    build_fn(validate = "Self::validate"),
+    derive(Debug)
+)]
 pub struct TaskListEventContent {
     pub name: String,
 
@@ -82,6 +136,28 @@ pub struct TaskListEventContent {
     #[builder(setter(into), default)]
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub categories: Vec<String>,
+
+    /// The workflow states tasks of this list can be in, in order
+    #[builder(setter(into), default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub states: Vec<TaskState>,
+}
+
+impl TaskListBuilder {
+    /// Add a workflow state after the existing ones
+    pub fn add_state(&mut self, state: TaskState) -> &mut Self {
+        self.states.get_or_insert_with(Vec::new).push(state);
+        self
+    }
+
+    fn validate(&self) -> CoreResult<(), String> {
+        if let Some(states) = &self.states {
+            if has_duplicate_states(states) {
+                return Err("Workflow states need unique keys".to_string());
+            }
+        }
+        Ok(())
+    }
 }
 
 /// The TaskList Event
@@ -89,7 +165,11 @@ pub struct TaskListEventContent {
 /// modeled after [JMAP TaskList](https://jmap.io/spec-tasks.html#tasklists)
 #[derive(Clone, Debug, Deserialize, Serialize, EventContent, Builder)]
 #[ruma_event(type = "global.acter.dev.tasklist.update", kind = MessageLike)]
-#[builder(name = "TaskListUpdateBuilder", derive(Debug))]
+#[builder(
+    name = "TaskListUpdateBuilder",
+    build_fn(validate = "Self::validate"),
+    derive(Debug)
+)]
 pub struct TaskListUpdateEventContent {
     #[builder(setter(into))]
     #[serde(rename = "m.relates_to")]
@@ -159,6 +239,35 @@ pub struct TaskListUpdateEventContent {
         deserialize_with = "deserialize_some"
     )]
     pub categories: Option<Vec<String>>,
+
+    /// The workflow states tasks of this list can be in, in order
+    #[builder(setter(into), default)]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_some"
+    )]
+    pub states: Option<Vec<TaskState>>,
+}
+
+impl TaskListUpdateBuilder {
+    /// Add a workflow state to the states replacing the current ones
+    pub fn add_state(&mut self, state: TaskState) -> &mut Self {
+        self.states
+            .get_or_insert_with(|| Some(Vec::new()))
+            .get_or_insert_with(Vec::new)
+            .push(state);
+        self
+    }
+
+    fn validate(&self) -> CoreResult<(), String> {
+        if let Some(Some(states)) = &self.states {
+            if has_duplicate_states(states) {
+                return Err("Workflow states need unique keys".to_string());
+            }
+        }
+        Ok(())
+    }
 }
 
 impl TaskListUpdateEventContent {
@@ -196,6 +305,10 @@ impl TaskListUpdateEventContent {
             task_list.categories.clone_from(categories);
             updated = true;
         }
+        if let Some(states) = &self.states {
+            task_list.states.clone_from(states);
+            updated = true;
+        }
 
         trace!(update = ?self, ?updated, ?task_list, "TaskList updated");
 
@@ -203,11 +316,114 @@ impl TaskListUpdateEventContent {
     }
 }
 
+/// A single item of the checklist of a Task
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ChecklistItem {
+    /// Identifies the item within the checklist
+    pub id: String,
+    pub title: String,
+    #[serde(default)]
+    pub done: bool,
+}
+
+impl ChecklistItem {
+    pub fn new(id: String, title: String) -> Self {
+        ChecklistItem {
+            id,
+            title,
+            done: false,
+        }
+    }
+
+    pub fn id_str(&self) -> String {
+        self.id.clone()
+    }
+
+    pub fn title(&self) -> String {
+        self.title.clone()
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+}
+
+/// A change to a single item of the checklist of a Task
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum ChecklistChange {
+    /// Add the item at the given position, at the end if none is given
+    Add {
+        item: ChecklistItem,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        position: Option<u32>,
+    },
+    /// Remove the item
+    Remove { id: String },
+    /// Mark the item as done or not done
+    SetDone { id: String, done: bool },
+    /// Change the title of the item
+    Rename { id: String, title: String },
+    /// Move the item to the given position
+    Move { id: String, position: u32 },
+}
+
+impl ChecklistChange {
+    /// Apply this change to the checklist, returns whether anything changed
+    pub fn apply(&self, checklist: &mut Vec<ChecklistItem>) -> bool {
+        match self {
+            ChecklistChange::Add { item, position } => {
+                if checklist.iter().any(|i| i.id == item.id) {
+                    return false;
+                }
+                let position = position
+                    .map(|p| (p as usize).min(checklist.len()))
+                    .unwrap_or(checklist.len());
+                checklist.insert(position, item.clone());
+                true
+            }
+            ChecklistChange::Remove { id } => {
+                let before = checklist.len();
+                checklist.retain(|i| &i.id != id);
+                checklist.len() != before
+            }
+            ChecklistChange::SetDone { id, done } => {
+                match checklist.iter_mut().find(|i| &i.id == id) {
+                    Some(item) if item.done != *done => {
+                        item.done = *done;
+                        true
+                    }
+                    _ => false,
+                }
+            }
+            ChecklistChange::Rename { id, title } => {
+                match checklist.iter_mut().find(|i| &i.id == id) {
+                    Some(item) if &item.title != title => {
+                        item.title.clone_from(title);
+                        true
+                    }
+                    _ => false,
+                }
+            }
+            ChecklistChange::Move { id, position } => {
+                let Some(idx) = checklist.iter().position(|i| &i.id == id) else {
+                    return false;
+                };
+                let item = checklist.remove(idx);
+                let position = (*position as usize).min(checklist.len());
+                checklist.insert(position, item);
+                idx != position
+            }
+        }
+    }
+}
+
 /// The Task Event
 ///
 /// modeled after [JMAP Task](https://jmap.io/spec-tasks.html#tasks)
 /// see also the [IETF Task](https://www.rfc-editor.org/rfc/rfc8984.html#name-task)
-/// but all timezones have been dumbed down to UTC-only.
+/// The due date is resolved in the time zone of the task, falling back to the
+/// one of its TaskList and UTC after that.
 #[derive(Clone, Debug, Deserialize, Serialize, EventContent, Builder, Getters)]
 #[ruma_event(type = "global.acter.dev.task", kind = MessageLike)]
 #[builder(
@@ -224,6 +440,16 @@ pub struct TaskEventContent {
     #[serde(rename = "m.relates_to")]
     pub task_list_id: BelongsTo,
 
+    /// If this task has been moved to the trash, the list to restore it to
+    #[builder(setter(into), default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trashed_from: Option<BelongsTo>,
+
+    /// If this is a subtask, the task it is part of
+    #[builder(setter(into), default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_task_id: Option<BelongsTo>,
+
     /// Further information describing the task
     #[builder(setter(into), default)]
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -240,6 +466,24 @@ pub struct TaskEventContent {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub utc_due_time_of_day: Option<i32>,
 
+    /// Any particular time this task is due as seconds since midnight in its
+    /// time zone, takes precedence over `utc_due_time_of_day`
+    #[builder(setter(into), default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_time_of_day: Option<i32>,
+
+    /// The time zone the due date is given in, if it differs from the one of
+    /// the TaskList
+    #[builder(setter(into), default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_zone: Option<Tz>,
+
+    /// Repeat this task, the next occurrence is due as defined by this
+    /// `RRULE` value, see [`RecurrenceRule`]
+    #[builder(setter(into), default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recurrence: Option<String>,
+
     /// When was this task started?
     #[builder(setter(into), default)]
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -250,11 +494,21 @@ pub struct TaskEventContent {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub progress_percent: Option<u8>,
 
+    /// How long this task is expected to take, in seconds
+    #[builder(setter(into), default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimate_seconds: Option<u32>,
+
     /// Sort order within the TaskList
     #[builder(default)]
     #[serde(default)]
     pub sort_order: u32,
 
+    /// The key of the workflow state of the TaskList this task is in
+    #[builder(setter(into), default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>,
+
     /// the priority of the Task
     #[builder(default)]
     #[serde(default, skip_serializing_if = "Priority::is_undefinied")]
@@ -273,27 +527,130 @@ pub struct TaskEventContent {
     #[builder(setter(into), default)]
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub categories: Vec<String>,
+
+    /// The tasks that need to be done before this task can start
+    #[builder(setter(into), default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub blocked_by: Vec<OwnedEventId>,
+
+    /// Small steps to tick off for this task, in order
+    #[builder(setter(into), default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub checklist: Vec<ChecklistItem>,
+
+    /// Derive the progress from the checked items of the checklist
+    #[builder(default)]
+    #[serde(default)]
+    pub progress_from_checklist: bool,
+
+    /// Identifies the task this one was imported from, e.g. the UID of a VTODO
+    #[builder(setter(into), default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub external_uid: Option<String>,
+}
+
+impl TaskEventContent {
+    /// The rule to repeat this task by, None if it has none we support
+    pub fn recurrence_rule(&self) -> Option<RecurrenceRule> {
+        RecurrenceRule::from_event(self.recurrence.as_deref()?)
+    }
+
+    /// How many of the checklist items are done in percent, None if there are none
+    pub fn checklist_progress(&self) -> Option<u8> {
+        if self.checklist.is_empty() {
+            return None;
+        }
+        let done = self.checklist.iter().filter(|i| i.done).count() * 100;
+        Some((done / self.checklist.len()) as u8)
+    }
+
+    /// The progress of this task, derived from the checklist if requested
+    pub fn effective_progress(&self) -> Option<u8> {
+        if self.progress_from_checklist {
+            self.checklist_progress()
+        } else {
+            self.progress_percent
+        }
+    }
 }
 
 impl TaskBuilder {
+    /// Add an item to the end of the checklist
+    pub fn checklist_item(&mut self, item: ChecklistItem) -> &mut Self {
+        self.checklist.get_or_insert_with(Vec::new).push(item);
+        self
+    }
+
+    /// Whether the list the task is sent to has been set already
+    pub fn has_task_list_id(&self) -> bool {
+        self.task_list_id.is_some()
+    }
+
     fn validate(&self) -> CoreResult<(), String> {
         if let Some(Some(percent)) = &self.progress_percent {
             if *percent > 100 {
                 return Err("Progress percent can’t be higher than 100".to_string());
             }
         }
+        if let Some(blocked_by) = &self.blocked_by {
+            if has_duplicates(blocked_by) {
+                return Err("A task can’t be blocked by the same task twice".to_string());
+            }
+        }
+        if let Some(checklist) = &self.checklist {
+            if has_duplicate_items(checklist) {
+                return Err("Checklist items need unique ids".to_string());
+            }
+        }
+        if let Some(Some(seconds)) = &self.due_time_of_day {
+            if !(0..SECONDS_PER_DAY).contains(seconds) {
+                return Err("The due time has to be within the day".to_string());
+            }
+        }
+        if let Some(Some(_)) = &self.recurrence {
+            if !matches!(&self.due_date, Some(Some(_))) {
+                return Err("A recurring task needs a due date".to_string());
+            }
+        }
         Ok(())
     }
 }
 
+const SECONDS_PER_DAY: i32 = 24 * 60 * 60;
+
+fn has_duplicates(ids: &[OwnedEventId]) -> bool {
+    ids.iter()
+        .enumerate()
+        .any(|(idx, id)| ids[idx + 1..].contains(id))
+}
+
+fn has_duplicate_items(items: &[ChecklistItem]) -> bool {
+    items
+        .iter()
+        .enumerate()
+        .any(|(idx, item)| items[idx + 1..].iter().any(|i| i.id == item.id))
+}
+
+fn has_duplicate_states(states: &[TaskState]) -> bool {
+    states
+        .iter()
+        .enumerate()
+        .any(|(idx, state)| states[idx + 1..].iter().any(|s| s.key == state.key))
+}
+
 /// The Task Update Event
 ///
 /// modeled after [JMAP Task](https://jmap.io/spec-tasks.html#tasks)
 /// see also the [IETF Task](https://www.rfc-editor.org/rfc/rfc8984.html#name-task)
-/// but all timezones have been dumbed down to UTC-only.
+/// A changed due date is resolved like the one of the [`TaskEventContent`],
+/// in the time zone of the task, of its TaskList or UTC.
 #[derive(Clone, Debug, Deserialize, Serialize, EventContent, Builder)]
 #[ruma_event(type = "global.acter.dev.task.update", kind = MessageLike)]
-#[builder(name = "TaskUpdateBuilder", derive(Debug))]
+#[builder(
+    name = "TaskUpdateBuilder",
+    build_fn(validate = "Self::validate"),
+    derive(Debug)
+)]
 pub struct TaskUpdateEventContent {
     #[builder(setter(into))]
     #[serde(rename = "m.relates_to")]
@@ -308,7 +665,24 @@ pub struct TaskUpdateEventContent {
     )]
     pub title: Option<String>,
 
-    /// Every tasks belongs to a tasklist
+    /// Every tasks belongs to a tasklist, moves it to another one in the same space
+    #[builder(default)]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_some"
+    )]
+    pub task_list_id: Option<BelongsTo>,
+
+    /// If this task has been moved to the trash, the list to restore it to
+    #[builder(default)]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_some"
+    )]
+    pub trashed_from: Option<Option<BelongsTo>>,
+
     /// Further information describing the task
     #[builder(default)]
     #[serde(
@@ -336,6 +710,33 @@ pub struct TaskUpdateEventContent {
     )]
     pub utc_due_time_of_day: Option<Option<i32>>,
 
+    /// Specific time on the day in its time zone this task is due
+    #[builder(default)]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_some"
+    )]
+    pub due_time_of_day: Option<Option<i32>>,
+
+    /// The time zone the due date is given in
+    #[builder(default)]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_some"
+    )]
+    pub time_zone: Option<Option<Tz>>,
+
+    /// Repeat this task, the next occurrence is due as defined by this rule
+    #[builder(default)]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_some"
+    )]
+    pub recurrence: Option<Option<String>>,
+
     /// When was this task started?
     #[builder(default)]
     #[serde(
@@ -354,6 +755,15 @@ pub struct TaskUpdateEventContent {
     )]
     pub progress_percent: Option<Option<u8>>,
 
+    /// How long this task is expected to take, in seconds
+    #[builder(default)]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_some"
+    )]
+    pub estimate_seconds: Option<Option<u32>>,
+
     /// Sort order within the TaskList
     #[builder(default)]
     #[serde(
@@ -363,6 +773,15 @@ pub struct TaskUpdateEventContent {
     )]
     pub sort_order: Option<u32>,
 
+    /// The key of the workflow state of the TaskList this task is in
+    #[builder(default)]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_some"
+    )]
+    pub state: Option<Option<String>>,
+
     /// the priority of the Task
     #[builder(default)]
     #[serde(
@@ -397,6 +816,59 @@ pub struct TaskUpdateEventContent {
         deserialize_with = "deserialize_some"
     )]
     pub categories: Option<Vec<String>>,
+
+    /// The tasks that need to be done before this task can start
+    #[builder(default)]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_some"
+    )]
+    pub blocked_by: Option<Vec<OwnedEventId>>,
+
+    /// Changes to single items of the checklist, applied in order
+    #[builder(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub checklist_changes: Vec<ChecklistChange>,
+
+    /// Derive the progress from the checked items of the checklist
+    #[builder(default)]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_some"
+    )]
+    pub progress_from_checklist: Option<bool>,
+}
+
+impl TaskUpdateBuilder {
+    /// Add a change to a single item of the checklist
+    pub fn checklist_change(&mut self, change: ChecklistChange) -> &mut Self {
+        self.checklist_changes
+            .get_or_insert_with(Vec::new)
+            .push(change);
+        self
+    }
+
+    fn validate(&self) -> CoreResult<(), String> {
+        if let Some(Some(seconds)) = &self.due_time_of_day {
+            if !(0..SECONDS_PER_DAY).contains(seconds) {
+                return Err("The due time has to be within the day".to_string());
+            }
+        }
+        let Some(Some(blocked_by)) = &self.blocked_by else {
+            return Ok(());
+        };
+        if let Some(task) = &self.task {
+            if blocked_by.contains(&task.event_id) {
+                return Err("A task can’t be blocked by itself".to_string());
+            }
+        }
+        if has_duplicates(blocked_by) {
+            return Err("A task can’t be blocked by the same task twice".to_string());
+        }
+        Ok(())
+    }
 }
 
 impl TaskUpdateEventContent {
@@ -406,6 +878,14 @@ impl TaskUpdateEventContent {
             task.title.clone_from(title);
             updated = true;
         }
+        if let Some(task_list_id) = &self.task_list_id {
+            task.task_list_id = task_list_id.clone();
+            updated = true;
+        }
+        if let Some(trashed_from) = &self.trashed_from {
+            task.trashed_from.clone_from(trashed_from);
+            updated = true;
+        }
         if let Some(description) = &self.description {
             task.description.clone_from(description);
             updated = true;
@@ -418,6 +898,18 @@ impl TaskUpdateEventContent {
             task.utc_due_time_of_day = *utc_due_time_of_day;
             updated = true;
         }
+        if let Some(due_time_of_day) = &self.due_time_of_day {
+            task.due_time_of_day = *due_time_of_day;
+            updated = true;
+        }
+        if let Some(time_zone) = &self.time_zone {
+            task.time_zone = *time_zone;
+            updated = true;
+        }
+        if let Some(recurrence) = &self.recurrence {
+            task.recurrence.clone_from(recurrence);
+            updated = true;
+        }
         if let Some(utc_start) = &self.utc_start {
             task.utc_start = *utc_start;
             updated = true;
@@ -426,10 +918,18 @@ impl TaskUpdateEventContent {
             task.progress_percent = *progress_percent;
             updated = true;
         }
+        if let Some(estimate_seconds) = &self.estimate_seconds {
+            task.estimate_seconds = *estimate_seconds;
+            updated = true;
+        }
         if let Some(sort_order) = &self.sort_order {
             task.sort_order = *sort_order;
             updated = true;
         }
+        if let Some(state) = &self.state {
+            task.state.clone_from(state);
+            updated = true;
+        }
         if let Some(priority) = &self.priority {
             task.priority = priority.clone();
             updated = true;
@@ -446,6 +946,19 @@ impl TaskUpdateEventContent {
             task.categories.clone_from(categories);
             updated = true;
         }
+        if let Some(blocked_by) = &self.blocked_by {
+            task.blocked_by.clone_from(blocked_by);
+            updated = true;
+        }
+        for change in &self.checklist_changes {
+            if change.apply(&mut task.checklist) {
+                updated = true;
+            }
+        }
+        if let Some(progress_from_checklist) = &self.progress_from_checklist {
+            task.progress_from_checklist = *progress_from_checklist;
+            updated = true;
+        }
 
         trace!(update = ?self, ?updated, ?task, "Task updated");
 
@@ -472,3 +985,126 @@ pub struct TaskSelfUnassignEventContent {
     #[serde(rename = "m.relates_to")]
     pub task: BelongsTo,
 }
+
+/// TaskAssignment Event
+///
+/// Assigns or unassigns another member, sending it requires the power level
+/// for this event type.
+#[derive(Clone, Debug, Deserialize, Serialize, EventContent, Builder, Getters)]
+#[ruma_event(type = "global.acter.dev.task.assignment", kind = MessageLike)]
+#[builder(name = "TaskAssignmentBuilder", derive(Debug))]
+pub struct TaskAssignmentEventContent {
+    #[builder(setter(into))]
+    #[serde(rename = "m.relates_to")]
+    pub task: BelongsTo,
+
+    /// The member to assign or unassign
+    pub assignee: OwnedUserId,
+
+    /// Whether the member is now assigned or unassigned
+    pub assigned: bool,
+}
+
+/// TaskTimeLog Event
+///
+/// Time the sender spent working on a task.
+#[derive(Clone, Debug, Deserialize, Serialize, EventContent, Builder, Getters)]
+#[ruma_event(type = "global.acter.dev.task.time_log", kind = MessageLike)]
+#[builder(
+    name = "TaskTimeLogBuilder",
+    build_fn(validate = "Self::validate"),
+    derive(Debug)
+)]
+pub struct TaskTimeLogEventContent {
+    #[builder(setter(into))]
+    #[serde(rename = "m.relates_to")]
+    pub task: BelongsTo,
+
+    /// How long was worked on the task, in seconds
+    pub seconds: u32,
+
+    /// What has been done in that time
+    #[builder(setter(into), default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+impl TaskTimeLogBuilder {
+    fn validate(&self) -> CoreResult<(), String> {
+        if self.seconds == Some(0) {
+            return Err("The time spent has to be more than nothing".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use matrix_sdk_base::ruma::owned_event_id;
+
+    #[test]
+    fn checklist_changes_apply_in_order() -> ActerResult<()> {
+        let mut task = TaskBuilder::default()
+            .title("Prepare the meetup".to_owned())
+            .task_list_id(owned_event_id!("$tasklist"))
+            .checklist(vec![
+                ChecklistItem::new("tape".to_owned(), "buy tape".to_owned()),
+                ChecklistItem::new("flyers".to_owned(), "print flyers".to_owned()),
+            ])
+            .progress_from_checklist(true)
+            .build()
+            .unwrap();
+        assert_eq!(task.effective_progress(), Some(0));
+
+        let json_raw = r#"{
+            "m.relates_to": {"rel_type": "global.acter.dev.update", "event_id": "$task"},
+            "checklist_changes": [
+                {"op": "add", "item": {"id": "room", "title": "book room"}, "position": 0},
+                {"op": "set_done", "id": "tape", "done": true},
+                {"op": "move", "id": "flyers", "position": 0},
+                {"op": "remove", "id": "unknown"}
+            ]
+        }"#;
+        let update = serde_json::from_str::<TaskUpdateEventContent>(json_raw)?;
+        assert!(update.apply(&mut task)?);
+
+        let ids: Vec<_> = task.checklist.iter().map(|i| i.id.as_str()).collect();
+        assert_eq!(ids, vec!["flyers", "room", "tape"]);
+        assert_eq!(task.checklist_progress(), Some(33));
+        assert_eq!(task.effective_progress(), Some(33));
+        assert_eq!(task.progress_percent, None);
+        Ok(())
+    }
+
+    #[test]
+    fn state_keys_are_unique() {
+        let doing = || TaskState::new("doing".to_owned(), "Doing".to_owned(), None);
+        assert!(TaskListBuilder::default()
+            .name("Board".to_owned())
+            .add_state(doing())
+            .add_state(doing())
+            .build()
+            .is_err());
+        assert!(TaskListUpdateBuilder::default()
+            .task_list(owned_event_id!("$tasklist"))
+            .add_state(doing())
+            .add_state(doing())
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn due_time_within_the_day() {
+        let update = |seconds| {
+            TaskUpdateBuilder::default()
+                .task(owned_event_id!("$task"))
+                .due_time_of_day(Some(Some(seconds)))
+                .build()
+        };
+        assert!(update(0).is_ok());
+        assert!(update(SECONDS_PER_DAY - 1).is_ok());
+        assert!(update(SECONDS_PER_DAY).is_err());
+        assert!(update(-1).is_err());
+    }
+}
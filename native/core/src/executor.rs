@@ -204,31 +204,11 @@ mod tests {
     use super::*;
     use crate::{
         events::{comments::CommentEventContent, BelongsTo},
-        models::{Comment, TestModelBuilder},
-    };
-    use matrix_sdk::Client;
-    use matrix_sdk_base::{
-        ruma::{
-            api::MatrixVersion, event_id, events::room::message::TextMessageEventContent, user_id,
-        },
-        store::{MemoryStore, StoreConfig},
+        models::{fresh_executor, Comment, TestModelBuilder},
     };
+    use matrix_sdk_base::ruma::{event_id, events::room::message::TextMessageEventContent};
     use serde_json::{from_value, json};
 
-    async fn fresh_executor() -> Result<Executor> {
-        let config = StoreConfig::new("tests".to_owned()).state_store(MemoryStore::new());
-        let client = Client::builder()
-            .homeserver_url("http://localhost")
-            .server_versions([MatrixVersion::V1_5])
-            .store_config(config)
-            .build()
-            .await
-            .unwrap();
-
-        let store = Store::new_with_auth(client, user_id!("@test:example.org").to_owned()).await?;
-        Executor::new(store).await
-    }
-
     #[tokio::test]
     async fn smoke_test() -> Result<()> {
         let _ = env_logger::try_init();
@@ -12,12 +12,14 @@ pub mod events;
 pub mod executor;
 pub mod models;
 pub mod push;
+pub mod scheduler;
 pub mod share_link;
 pub mod spaces;
 pub mod statics;
 pub mod store;
 pub mod super_invites;
 pub mod support;
+pub mod task_import;
 
 pub use error::{Error, Result};
 pub use support::{CustomAuthSession, RestoreToken};
@@ -15,7 +15,10 @@ mod test;
 
 use async_recursion::async_recursion;
 pub use attachments::{Attachment, AttachmentUpdate, AttachmentsManager, AttachmentsStats};
-pub use calendar::{CalendarEvent, CalendarEventUpdate};
+pub use calendar::{
+    ical_calendar, ical_feed, CalendarEvent, CalendarEventOccurrence, CalendarEventUpdate,
+    CalendarImport,
+};
 pub use comments::{Comment, CommentUpdate, CommentsManager, CommentsStats};
 pub use common::*;
 pub use core::fmt::Debug;
@@ -33,17 +36,21 @@ pub use news::{NewsEntry, NewsEntryUpdate};
 pub use pins::{Pin, PinUpdate};
 pub use reactions::{Reaction, ReactionManager, ReactionStats};
 pub use read_receipts::{ReadReceipt, ReadReceiptStats, ReadReceiptsManager};
-pub use rsvp::{Rsvp, RsvpManager, RsvpStats};
+pub use rsvp::{Rsvp, RsvpAttendance, RsvpManager, RsvpSeat, RsvpStats};
 use serde::{Deserialize, Serialize};
 pub use stories::{Story, StoryUpdate};
 pub use tag::Tag;
 pub use tasks::{
-    Task, TaskList, TaskListUpdate, TaskSelfAssign, TaskSelfUnassign, TaskStats, TaskUpdate,
+    BurndownDay, Task, TaskAssignment, TaskFieldChange, TaskHistoryEntry, TaskHistoryKind,
+    TaskList, TaskListDuplicateOptions, TaskListUpdate, TaskQuery, TaskQueryBuilder,
+    TaskSelfAssign, TaskSelfUnassign, TaskSort, TaskSortKey, TaskStats, TaskTimeLog, TaskUpdate,
 };
 use tracing::{error, info, trace, warn};
 
+#[cfg(test)]
+pub use test::fresh_executor;
 #[cfg(any(test, feature = "testing"))]
-pub use test::{TestModel, TestModelBuilder, TestModelBuilderError};
+pub use test::{TestModel, TestModelBuilder, TestModelBuilderError, TestRsvps};
 
 pub use crate::store::Store;
 use crate::{
@@ -57,8 +64,9 @@ use crate::{
         rsvp::RsvpEventContent,
         stories::{StoryEventContent, StoryUpdateEventContent},
         tasks::{
-            TaskEventContent, TaskListEventContent, TaskListUpdateEventContent,
-            TaskSelfAssignEventContent, TaskSelfUnassignEventContent, TaskUpdateEventContent,
+            TaskAssignmentEventContent, TaskEventContent, TaskListEventContent,
+            TaskListUpdateEventContent, TaskSelfAssignEventContent, TaskSelfUnassignEventContent,
+            TaskTimeLogEventContent, TaskUpdateEventContent,
         },
         AnyActerEvent,
     },
@@ -334,6 +342,8 @@ pub enum AnyActerModel {
     TaskUpdate(TaskUpdate),
     TaskSelfAssign(TaskSelfAssign),
     TaskSelfUnassign(TaskSelfUnassign),
+    TaskAssignment(TaskAssignment),
+    TaskTimeLog(TaskTimeLog),
 
     // -- Pins
     Pin(Pin),
@@ -373,6 +383,8 @@ impl AnyActerModel {
             AnyActerModel::TaskUpdate(_) => TaskUpdateEventContent::TYPE,
             AnyActerModel::TaskSelfAssign(_) => TaskSelfAssignEventContent::TYPE,
             AnyActerModel::TaskSelfUnassign(_) => TaskSelfUnassignEventContent::TYPE,
+            AnyActerModel::TaskAssignment(_) => TaskAssignmentEventContent::TYPE,
+            AnyActerModel::TaskTimeLog(_) => TaskTimeLogEventContent::TYPE,
             AnyActerModel::Pin(_) => PinEventContent::TYPE,
             AnyActerModel::PinUpdate(_) => PinUpdateEventContent::TYPE,
             AnyActerModel::NewsEntry(_) => NewsEntryEventContent::TYPE,
@@ -637,6 +649,34 @@ impl TryFrom<AnyActerEvent> for AnyActerModel {
                     reason: r.unsigned.redacted_because,
                 }),
             },
+            AnyActerEvent::TaskAssignment(e) => match e {
+                MessageLikeEvent::Original(m) => Ok(AnyActerModel::TaskAssignment(m.into())),
+                MessageLikeEvent::Redacted(r) => Err(Self::Error::ModelRedacted {
+                    model_type: TaskAssignmentEventContent::TYPE.to_owned(),
+                    meta: EventMeta {
+                        room_id: r.room_id,
+                        event_id: r.event_id,
+                        sender: r.sender,
+                        origin_server_ts: r.origin_server_ts,
+                        redacted: None,
+                    },
+                    reason: r.unsigned.redacted_because,
+                }),
+            },
+            AnyActerEvent::TaskTimeLog(e) => match e {
+                MessageLikeEvent::Original(m) => Ok(AnyActerModel::TaskTimeLog(m.into())),
+                MessageLikeEvent::Redacted(r) => Err(Self::Error::ModelRedacted {
+                    model_type: TaskTimeLogEventContent::TYPE.to_owned(),
+                    meta: EventMeta {
+                        room_id: r.room_id,
+                        event_id: r.event_id,
+                        sender: r.sender,
+                        origin_server_ts: r.origin_server_ts,
+                        redacted: None,
+                    },
+                    reason: r.unsigned.redacted_because,
+                }),
+            },
             AnyActerEvent::Comment(e) => match e {
                 MessageLikeEvent::Original(m) => Ok(AnyActerModel::Comment(m.into())),
                 MessageLikeEvent::Redacted(r) => Err(Self::Error::ModelRedacted {
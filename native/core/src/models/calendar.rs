@@ -1,6 +1,12 @@
 mod event;
+mod ical;
+mod ical_import;
+mod occurrences;
 
 pub use event::{CalendarEvent, CalendarEventUpdate};
+pub use ical::{ical_calendar, ical_feed};
+pub use ical_import::CalendarImport;
+pub use occurrences::CalendarEventOccurrence;
 
 use crate::statics::KEYS;
 
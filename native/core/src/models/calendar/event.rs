@@ -1,12 +1,14 @@
-use icalendar::{Component, Event as iCalEvent, EventLike};
+use chrono::DateTime;
+use chrono_tz::Tz;
 use matrix_sdk_base::ruma::{events::OriginalMessageLikeEvent, EventId, RoomId, UserId};
 use serde::{Deserialize, Serialize};
 use std::ops::Deref;
+use tracing::debug;
 
 use super::{
     super::{
-        default_model_execute, ActerModel, AnyActerModel, Capability, EventMeta, Store,
-        TextMessageContent,
+        default_model_execute, ActerModel, AnyActerModel, Capability, EventMeta, RsvpManager,
+        Store, TextMessageContent,
     },
     CALENDAR_KEY,
 };
@@ -14,6 +16,7 @@ use crate::{
     events::{
         calendar::{
             CalendarEventEventContent, CalendarEventUpdateBuilder, CalendarEventUpdateEventContent,
+            EventStatus,
         },
         UtcDateTime,
     },
@@ -24,6 +27,9 @@ use crate::{
 pub struct CalendarEvent {
     inner: CalendarEventEventContent,
     meta: EventMeta,
+    /// How many updates have been applied
+    #[serde(default)]
+    sequence: u32,
 }
 impl Deref for CalendarEvent {
     type Target = CalendarEventEventContent;
@@ -67,22 +73,56 @@ impl CalendarEvent {
         self.inner.show_without_time
     }
 
-    pub fn as_ical_event(&self) -> iCalEvent {
-        let mut cal_e_builder = iCalEvent::new();
-
-        cal_e_builder
-            .summary(&self.inner.title)
-            .starts(self.inner.utc_start)
-            .ends(self.inner.utc_end)
-            .class(icalendar::Class::Private);
-        if let Some(msg) = &self.inner.description {
-            if let Some(formatted) = &msg.formatted {
-                return cal_e_builder.description(&formatted.body).done();
-            } else {
-                return cal_e_builder.description(&msg.body).done();
-            }
-        }
-        cal_e_builder.done()
+    /// The time zone the event was planned in, UTC if we don’t know its name
+    pub fn time_zone(&self) -> Option<Tz> {
+        let name = self.inner.time_zone.as_deref()?;
+        Some(name.parse().unwrap_or_else(|_| {
+            debug!(name, "Unknown time zone, using UTC");
+            Tz::UTC
+        }))
+    }
+
+    /// When the event starts, in the given zone or else in the one it was
+    /// planned in
+    pub fn start_in(&self, tz: Option<Tz>) -> DateTime<Tz> {
+        self.inner.utc_start.with_timezone(&self.display_zone(tz))
+    }
+
+    /// When the event ends, in the given zone or else in the one it was
+    /// planned in
+    pub fn end_in(&self, tz: Option<Tz>) -> DateTime<Tz> {
+        self.inner.utc_end.with_timezone(&self.display_zone(tz))
+    }
+
+    fn display_zone(&self, tz: Option<Tz>) -> Tz {
+        tz.or(self.time_zone()).unwrap_or(Tz::UTC)
+    }
+
+    pub fn status(&self) -> EventStatus {
+        self.inner.status
+    }
+
+    /// Cancelled events stay around, so those who planned to attend know
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.status == EventStatus::Cancelled
+    }
+
+    /// How many people can attend, `None` if there is no limit
+    pub fn capacity(&self) -> Option<u32> {
+        self.inner.capacity
+    }
+
+    /// The revision of this event, counting the updates applied to it
+    pub fn sequence(&self) -> u32 {
+        self.sequence
+    }
+
+    /// The UID of this event in iCalendar, shared by all its occurrences
+    ///
+    /// Always our own, even for imported events: their UID from the other
+    /// calendar only serves to find them when importing again.
+    pub fn ical_uid(&self) -> String {
+        self.meta.event_id.to_string()
     }
 }
 
@@ -110,7 +150,16 @@ impl ActerModel for CalendarEvent {
     }
 
     async fn execute(self, store: &Store) -> Result<Vec<String>> {
-        default_model_execute(store, self.into()).await
+        let event_id = self.meta.event_id.clone();
+        let limited = self.inner.capacity.is_some();
+        let mut updates = default_model_execute(store, self.into()).await?;
+        if limited {
+            // all seats are free
+            let mut manager = RsvpManager::from_store_and_event_id(store, &event_id).await;
+            manager.update_attendance().await?;
+            updates.push(manager.save().await?);
+        }
+        Ok(updates)
     }
 
     fn belongs_to(&self) -> Option<Vec<String>> {
@@ -125,7 +174,11 @@ impl ActerModel for CalendarEvent {
         // FIXME: redacting a CalendarEventUpdate would mean reverting to the previous
         //        state. That is currently not that easy...
 
-        update.apply(&mut self.inner)
+        let updated = update.apply(&mut self.inner)?;
+        if updated {
+            self.sequence += 1;
+        }
+        Ok(updated)
     }
 }
 
@@ -148,6 +201,7 @@ impl From<OriginalMessageLikeEvent<CalendarEventEventContent>> for CalendarEvent
                 origin_server_ts,
                 redacted: None,
             },
+            sequence: 0,
         }
     }
 }
@@ -171,7 +225,16 @@ impl ActerModel for CalendarEventUpdate {
     }
 
     async fn execute(self, store: &Store) -> Result<Vec<String>> {
-        default_model_execute(store, self.into()).await
+        let capacity_changed = self.inner.capacity.is_some();
+        let calendar_event = self.inner.calendar_event.event_id.clone();
+        let mut updates = default_model_execute(store, self.into()).await?;
+        if capacity_changed {
+            // people may have moved between the seats and the waitlist
+            let mut manager = RsvpManager::from_store_and_event_id(store, &calendar_event).await;
+            manager.update_attendance().await?;
+            updates.push(manager.save().await?);
+        }
+        Ok(updates)
     }
 
     fn belongs_to(&self) -> Option<Vec<String>> {
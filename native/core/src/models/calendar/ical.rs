@@ -0,0 +1,438 @@
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Offset, TimeZone};
+use chrono_tz::{OffsetComponents, Tz};
+use icalendar::{
+    Calendar, CalendarDateTime, Component, Event as iCalEvent, EventLike,
+    EventStatus as iCalStatus, Property,
+};
+use matrix_sdk_base::ruma::UserId;
+use std::collections::BTreeMap;
+
+use super::{
+    super::{ActerModel, RsvpManager, Store},
+    occurrences::local_to_utc,
+    CalendarEvent,
+};
+use crate::{
+    events::{calendar::EventStatus, rsvp::RsvpStatus, RecurrenceRule, UtcDateTime},
+    Result,
+};
+
+impl CalendarEvent {
+    pub fn as_ical_event(&self) -> iCalEvent {
+        let mut cal_e_builder = iCalEvent::new();
+
+        cal_e_builder
+            .uid(&self.ical_uid())
+            .add_property("SEQUENCE", &self.sequence().to_string())
+            .summary(&self.title)
+            .starts(self.ical_date_time(self.utc_start))
+            .ends(self.ical_date_time(self.utc_end))
+            .status(self.ical_status())
+            .class(icalendar::Class::Private);
+        if let Some(rule) = &self.recurrence {
+            // rules we don’t support go out as we got them
+            let rule = match self.recurrence_rule() {
+                Some(rule) => self.ical_rule(&rule),
+                None => rule.clone(),
+            };
+            cal_e_builder.add_property("RRULE", &rule);
+        }
+        if !self.recurrence_dates.is_empty() {
+            cal_e_builder.append_property(self.ical_dates("RDATE", &self.recurrence_dates));
+        }
+        // cancelled occurrences are just not happening
+        let excluded: Vec<UtcDateTime> = self
+            .excluded_dates
+            .iter()
+            .cloned()
+            .chain(
+                self.occurrence_overrides
+                    .iter()
+                    .filter(|o| o.cancelled)
+                    .map(|o| o.recurrence_id),
+            )
+            .collect();
+        if !excluded.is_empty() {
+            cal_e_builder.append_property(self.ical_dates("EXDATE", &excluded));
+        }
+        if let Some(msg) = &self.description {
+            if let Some(formatted) = &msg.formatted {
+                return cal_e_builder.description(&formatted.body).done();
+            } else {
+                return cal_e_builder.description(&msg.body).done();
+            }
+        }
+        cal_e_builder.done()
+    }
+
+    /// The event and a further one for every occurrence that has been moved
+    /// or renamed, referring to it by `RECURRENCE-ID`
+    pub fn as_ical_events(&self) -> Vec<iCalEvent> {
+        let duration = self.utc_end - self.utc_start;
+        let mut events = vec![self.as_ical_event()];
+        for change in self.occurrence_overrides.iter().filter(|o| !o.cancelled) {
+            let utc_start = change.utc_start.unwrap_or(change.recurrence_id);
+            let utc_end = change.utc_end.unwrap_or(utc_start + duration);
+            events.push(
+                iCalEvent::new()
+                    .uid(&self.ical_uid())
+                    .add_property("SEQUENCE", &self.sequence().to_string())
+                    .append_property(self.ical_dates("RECURRENCE-ID", &[change.recurrence_id]))
+                    .summary(change.title.as_ref().unwrap_or(&self.title))
+                    .starts(self.ical_date_time(utc_start))
+                    .ends(self.ical_date_time(utc_end))
+                    .status(self.ical_status())
+                    .class(icalendar::Class::Private)
+                    .done(),
+            );
+        }
+        events
+    }
+
+    /// iCalendar knows no postponed events, their time is just not final
+    fn ical_status(&self) -> iCalStatus {
+        match self.status {
+            EventStatus::Confirmed => iCalStatus::Confirmed,
+            EventStatus::Tentative | EventStatus::Postponed => iCalStatus::Tentative,
+            EventStatus::Cancelled => iCalStatus::Cancelled,
+        }
+    }
+
+    /// The time in the zone of the event, with its `TZID`, or in UTC
+    fn ical_date_time(&self, date: UtcDateTime) -> CalendarDateTime {
+        match self.time_zone() {
+            Some(tz) => CalendarDateTime::WithTimezone {
+                date_time: date.with_timezone(&tz).naive_local(),
+                tzid: tz.name().to_owned(),
+            },
+            None => CalendarDateTime::Utc(date),
+        }
+    }
+
+    /// The rule with `UNTIL` at the end of its day in the time zone of the
+    /// event, given in UTC like RFC 5545 asks for next to a `DTSTART` with time
+    fn ical_rule(&self, rule: &RecurrenceRule) -> String {
+        let tz = self.time_zone().unwrap_or(Tz::UTC);
+        match rule
+            .until()
+            .and_then(|until| local_to_utc(tz, until.and_hms_opt(23, 59, 59)?))
+        {
+            Some(until) => rule.to_string_until(until),
+            None => rule.to_string(),
+        }
+    }
+
+    fn ical_dates(&self, key: &str, dates: &[UtcDateTime]) -> Property {
+        let value = dates
+            .iter()
+            .map(|date| match self.time_zone() {
+                Some(tz) => date.with_timezone(&tz).format("%Y%m%dT%H%M%S").to_string(),
+                None => date.format("%Y%m%dT%H%M%SZ").to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        let mut property = Property::new(key, &value);
+        if let Some(tz) = self.time_zone() {
+            property.add_parameter("TZID", tz.name());
+        }
+        property.done()
+    }
+}
+
+/// The iCalendar data for the events, with a `VTIMEZONE` for every time zone
+/// they have been planned in
+pub fn ical_calendar(events: &[CalendarEvent]) -> String {
+    let ical_events = events
+        .iter()
+        .flat_map(CalendarEvent::as_ical_events)
+        .collect();
+    with_time_zones(events, ical_events)
+}
+
+/// All events as one iCalendar feed to subscribe to, with the RSVP of `me`
+/// as `PARTSTAT` of an `ATTENDEE`
+///
+/// Every update of an event increases its `SEQUENCE`, so clients pick up the
+/// changes when fetching the feed again.
+pub async fn ical_feed(store: &Store, events: &[CalendarEvent], me: &UserId) -> Result<String> {
+    let mut ical_events = vec![];
+    for event in events {
+        let rsvp = RsvpManager::from_store_and_event_id(store, event.event_id())
+            .await
+            .rsvp_entries()
+            .await?
+            .remove(me)
+            .map(|r| r.status.clone());
+        let attendee = Property::new("ATTENDEE", &me.matrix_uri(false).to_string())
+            .add_parameter("PARTSTAT", partstat(rsvp.as_ref()))
+            .done();
+        for mut ical_event in event.as_ical_events() {
+            ical_event.append_property(attendee.clone());
+            ical_events.push(ical_event);
+        }
+    }
+    Ok(with_time_zones(events, ical_events))
+}
+
+fn partstat(rsvp: Option<&RsvpStatus>) -> &'static str {
+    match rsvp {
+        Some(RsvpStatus::Yes) => "ACCEPTED",
+        Some(RsvpStatus::Maybe) => "TENTATIVE",
+        Some(RsvpStatus::No) => "DECLINED",
+        None => "NEEDS-ACTION",
+    }
+}
+
+fn with_time_zones(events: &[CalendarEvent], ical_events: Vec<iCalEvent>) -> String {
+    // the zone rules from the year before the earliest event on
+    let mut zones: BTreeMap<&'static str, (Tz, i32)> = BTreeMap::new();
+    for event in events {
+        if let Some(tz) = event.time_zone() {
+            let year = event.utc_start.with_timezone(&tz).year() - 1;
+            let entry = zones.entry(tz.name()).or_insert((tz, year));
+            entry.1 = entry.1.min(year);
+        }
+    }
+    let calendar: Calendar = ical_events.into_iter().collect();
+    let mut ical = calendar.to_string();
+    let vtimezones: String = zones
+        .values()
+        .map(|(tz, year)| vtimezone(*tz, *year))
+        .collect();
+    let pos = ical
+        .find("BEGIN:VEVENT")
+        .or_else(|| ical.find("END:VCALENDAR"))
+        .unwrap_or(ical.len());
+    ical.insert_str(pos, &vtimezones);
+    ical
+}
+
+/// A change of the UTC offset of a zone
+struct Transition {
+    /// the wall clock time right before the change
+    local: NaiveDateTime,
+    from: i32,
+    to: i32,
+    daylight: bool,
+    name: String,
+}
+
+/// A `VTIMEZONE` with the rules of the zone as they are in `year`
+///
+/// Earlier changes to the rules of the zone are not included, clients are
+/// expected to know the zone by its `TZID` anyways.
+fn vtimezone(tz: Tz, year: i32) -> String {
+    let mut lines = vec!["BEGIN:VTIMEZONE".to_owned(), format!("TZID:{}", tz.name())];
+    let transitions = transitions_in(tz, year);
+    if transitions.is_empty() {
+        let offset = NaiveDate::from_ymd_opt(year, 1, 1)
+            .and_then(|d| d.and_hms_opt(0, 0, 0))
+            .map(|d| tz.offset_from_utc_datetime(&d))
+            .unwrap_or_else(|| tz.offset_from_utc_datetime(&NaiveDateTime::default()));
+        let utc_offset = format_offset(offset.fix().local_minus_utc());
+        lines.extend([
+            "BEGIN:STANDARD".to_owned(),
+            format!("TZNAME:{offset}"),
+            format!("TZOFFSETFROM:{utc_offset}"),
+            format!("TZOFFSETTO:{utc_offset}"),
+            "DTSTART:19700101T000000".to_owned(),
+            "END:STANDARD".to_owned(),
+        ]);
+    }
+    for transition in transitions {
+        let kind = if transition.daylight {
+            "DAYLIGHT"
+        } else {
+            "STANDARD"
+        };
+        lines.extend([
+            format!("BEGIN:{kind}"),
+            format!("TZNAME:{}", transition.name),
+            format!("TZOFFSETFROM:{}", format_offset(transition.from)),
+            format!("TZOFFSETTO:{}", format_offset(transition.to)),
+            format!("DTSTART:{}", transition.local.format("%Y%m%dT%H%M%S")),
+            format!("RRULE:FREQ=YEARLY;{}", yearly_rule(transition.local.date())),
+            format!("END:{kind}"),
+        ]);
+    }
+    lines.push("END:VTIMEZONE".to_owned());
+    lines.iter().map(|line| format!("{line}\r\n")).collect()
+}
+
+/// The offset changes of the zone in the given year, to the quarter hour
+fn transitions_in(tz: Tz, year: i32) -> Vec<Transition> {
+    let step = Duration::minutes(15);
+    let offset_at = |utc: NaiveDateTime| tz.offset_from_utc_datetime(&utc);
+    let mut transitions = vec![];
+    let Some(mut day) = NaiveDate::from_ymd_opt(year, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0))
+    else {
+        return transitions;
+    };
+    while day.year() == year {
+        let next_day = day + Duration::days(1);
+        let before = offset_at(day).fix();
+        if before != offset_at(next_day).fix() {
+            let mut last = day;
+            while offset_at(last + step).fix() == before {
+                last += step;
+            }
+            let after = offset_at(last + step);
+            transitions.push(Transition {
+                local: last + step + Duration::seconds(before.local_minus_utc().into()),
+                from: before.local_minus_utc(),
+                to: after.fix().local_minus_utc(),
+                daylight: after.dst_offset() != Duration::zero(),
+                name: after.to_string(),
+            });
+        }
+        day = next_day;
+    }
+    transitions
+}
+
+/// `BYMONTH` and `BYDAY` for the weekday of the month the date falls on,
+/// counting from the end for the last one
+fn yearly_rule(date: NaiveDate) -> String {
+    let days_in_month = date
+        .with_day(1)
+        .and_then(|first| first.checked_add_months(chrono::Months::new(1)))
+        .and_then(|next| next.pred_opt())
+        .map(|last| last.day())
+        .unwrap_or(28);
+    let nth = if date.day() + 7 > days_in_month {
+        "-1".to_owned()
+    } else {
+        ((date.day() - 1) / 7 + 1).to_string()
+    };
+    let weekday = date.weekday().to_string()[..2].to_uppercase();
+    format!("BYMONTH={};BYDAY={nth}{weekday}", date.month())
+}
+
+fn format_offset(seconds: i32) -> String {
+    let sign = if seconds < 0 { '-' } else { '+' };
+    let minutes = seconds.abs() / 60;
+    format!("{sign}{:02}{:02}", minutes / 60, minutes % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{fresh_executor, AnyActerModel, TestModelBuilder, TestRsvps};
+    use matrix_sdk_base::ruma::user_id;
+
+    #[tokio::test]
+    async fn feed_with_sequence_and_rsvp() -> Result<()> {
+        let executor = fresh_executor().await?;
+        let meta = TestModelBuilder::fake_meta();
+        let event_id = meta.event_id.clone();
+        executor
+            .handle(TestModelBuilder::calendar_event(
+                meta,
+                serde_json::json!({
+                    "title": "Board meeting",
+                    "utc_start": "2024-05-06T09:00:00Z",
+                    "utc_end": "2024-05-06T10:00:00Z",
+                }),
+            ))
+            .await?;
+        for title in ["Board meeting, moved", "Board meeting"] {
+            executor
+                .handle(TestModelBuilder::calendar_event_update(
+                    &event_id,
+                    serde_json::json!({ "title": title }),
+                ))
+                .await?;
+        }
+        executor
+            .handle(TestRsvps::new(&event_id).rsvp("@test:example.org", "maybe"))
+            .await?;
+
+        let AnyActerModel::CalendarEvent(event) = executor.store().get(event_id.as_str()).await?
+        else {
+            panic!("not a calendar event");
+        };
+        assert_eq!(event.sequence(), 2);
+        let events = [event];
+        let feed = ical_feed(executor.store(), &events, user_id!("@test:example.org")).await?;
+        assert!(feed.contains(&format!("UID:{event_id}")));
+        assert!(feed.contains("SEQUENCE:2"));
+        assert!(feed.contains("ATTENDEE;PARTSTAT=TENTATIVE:matrix:u/test:example.org"));
+
+        let feed = ical_feed(executor.store(), &events, user_id!("@other:example.org")).await?;
+        assert!(feed.contains("ATTENDEE;PARTSTAT=NEEDS-ACTION:matrix:u/other:example.org"));
+        Ok(())
+    }
+
+    #[test]
+    fn exports_status() {
+        for (status, ical) in [
+            ("cancelled", "STATUS:CANCELLED"),
+            ("postponed", "STATUS:TENTATIVE"),
+            ("confirmed", "STATUS:CONFIRMED"),
+        ] {
+            let event: CalendarEvent = serde_json::from_value(serde_json::json!({
+                "inner": {
+                    "title": "Summer fest",
+                    "utc_start": "2024-07-06T10:00:00Z",
+                    "utc_end": "2024-07-06T22:00:00Z",
+                    "status": status,
+                },
+                "meta": TestModelBuilder::fake_meta(),
+            }))
+            .unwrap();
+            assert!(ical_calendar(&[event]).contains(ical));
+        }
+    }
+
+    #[test]
+    fn exports_time_zone() {
+        let event: CalendarEvent = serde_json::from_value(serde_json::json!({
+            "inner": {
+                "title": "Choir practice",
+                "utc_start": "2024-03-21T18:00:00Z",
+                "utc_end": "2024-03-21T20:00:00Z",
+                "time_zone": "Europe/Berlin",
+                "recurrence": "FREQ=WEEKLY",
+                "excluded_dates": ["2024-04-04T17:00:00Z"],
+            },
+            "meta": TestModelBuilder::fake_meta(),
+        }))
+        .unwrap();
+        let ical = ical_calendar(&[event]);
+
+        assert!(ical.contains("DTSTART;TZID=Europe/Berlin:20240321T190000"));
+        assert!(ical.contains("EXDATE;TZID=Europe/Berlin:20240404T190000"));
+        assert!(ical.contains("RRULE:FREQ=WEEKLY"));
+        let vtimezone =
+            &ical[ical.find("BEGIN:VTIMEZONE").unwrap()..ical.find("BEGIN:VEVENT").unwrap()];
+        assert!(vtimezone.contains("TZID:Europe/Berlin"));
+        assert!(vtimezone.contains(
+            "BEGIN:DAYLIGHT\r\nTZNAME:CEST\r\nTZOFFSETFROM:+0100\r\nTZOFFSETTO:+0200\r\nDTSTART:20230326T020000\r\nRRULE:FREQ=YEARLY;BYMONTH=3;BYDAY=-1SU\r\n"
+        ));
+        assert!(vtimezone
+            .contains("DTSTART:20231029T030000\r\nRRULE:FREQ=YEARLY;BYMONTH=10;BYDAY=-1SU"));
+    }
+
+    #[test]
+    fn fixed_zone() {
+        let lines = vtimezone(Tz::Asia__Kolkata, 2024);
+        assert!(lines.contains("TZOFFSETTO:+0530\r\nDTSTART:19700101T000000"));
+    }
+
+    #[test]
+    fn exports_until_as_date_time() {
+        let event: CalendarEvent = serde_json::from_value(serde_json::json!({
+            "inner": {
+                "title": "Choir practice",
+                "utc_start": "2024-03-21T18:00:00Z",
+                "utc_end": "2024-03-21T20:00:00Z",
+                "time_zone": "Europe/Berlin",
+                "recurrence": "FREQ=WEEKLY;UNTIL=20240411",
+            },
+            "meta": TestModelBuilder::fake_meta(),
+        }))
+        .unwrap();
+        // the end of the 11th in Berlin, which is on summer time by then
+        assert!(ical_calendar(&[event]).contains("RRULE:FREQ=WEEKLY;UNTIL=20240411T215959Z"));
+    }
+}
@@ -0,0 +1,666 @@
+use chrono::{Duration, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use icalendar::{
+    Calendar, CalendarDateTime, Component, DatePerhapsTime, Event as iCalEvent, EventLike,
+    EventStatus as iCalStatus,
+};
+use matrix_sdk_base::ruma::events::room::message::TextMessageEventContent;
+use serde::Serialize;
+use std::collections::HashMap;
+
+use super::{super::tasks::ical_categories, occurrences::local_to_utc, CalendarEvent};
+use crate::{
+    events::{
+        calendar::{
+            CalendarEventBuilder, CalendarEventEventContent, CalendarEventUpdateBuilder,
+            EventLocation, EventStatus, OccurrenceOverride,
+        },
+        RecurrenceRule, UtcDateTime,
+    },
+    Error, Result,
+};
+
+/// The calendar events of an iCalendar file
+#[derive(Clone, Debug, Default)]
+pub struct CalendarImport {
+    /// One for every VEVENT, with its changed occurrences as overrides
+    pub events: Vec<CalendarEventEventContent>,
+    /// Why VEVENTs couldn’t be read
+    pub skipped: Vec<String>,
+}
+
+impl CalendarImport {
+    pub fn from_ical(ical: &str) -> Result<Self> {
+        let calendar: Calendar = ical.parse().map_err(Error::Custom)?;
+        let vevents: Vec<&iCalEvent> = calendar
+            .components
+            .iter()
+            .filter_map(|c| c.as_event())
+            .collect();
+        let zones = TimeZones::from_ical(ical);
+        let mut import = CalendarImport::default();
+        // the series first, their changed occurrences refer to them
+        for vevent in vevents.iter().filter(|e| !is_changed_occurrence(e)) {
+            match content_from_vevent(vevent, &zones) {
+                Ok(content) => import.events.push(content),
+                Err(reason) => import.skipped.push(reason),
+            }
+        }
+        for vevent in vevents.iter().filter(|e| is_changed_occurrence(e)) {
+            if let Some(tzid) = unknown_time_zone(vevent, &zones) {
+                import
+                    .skipped
+                    .push(format!("{}: unknown time zone {tzid}", label(vevent)));
+                continue;
+            }
+            let series = vevent.get_uid().and_then(|uid| {
+                import
+                    .events
+                    .iter_mut()
+                    .find(|e| e.external_uid.as_deref() == Some(uid))
+            });
+            let Some(series) = series else {
+                import.skipped.push(format!(
+                    "{}: changed occurrence of an unknown event",
+                    label(vevent)
+                ));
+                continue;
+            };
+            match occurrence_override(vevent, series, &zones) {
+                Some(change) => series.occurrence_overrides.push(change),
+                None => import
+                    .skipped
+                    .push(format!("{}: invalid RECURRENCE-ID", label(vevent))),
+            }
+        }
+        Ok(import)
+    }
+}
+
+impl CalendarEvent {
+    /// The update to bring this event in line with the imported one, None if
+    /// nothing changed
+    pub fn update_from_import(
+        &self,
+        imported: &CalendarEventEventContent,
+    ) -> Option<CalendarEventUpdateBuilder> {
+        let mut builder = self.updater();
+        let mut changed = false;
+        if self.title != imported.title {
+            builder.title(Some(imported.title.clone()));
+            changed = true;
+        }
+        if !same(&self.description, &imported.description) {
+            builder.description(Some(imported.description.clone()));
+            changed = true;
+        }
+        if self.utc_start != imported.utc_start {
+            builder.utc_start(Some(imported.utc_start));
+            changed = true;
+        }
+        if self.utc_end != imported.utc_end {
+            builder.utc_end(Some(imported.utc_end));
+            changed = true;
+        }
+        if self.show_without_time != imported.show_without_time {
+            builder.show_without_time(Some(imported.show_without_time));
+            changed = true;
+        }
+        if self.time_zone != imported.time_zone {
+            builder.time_zone(Some(imported.time_zone.clone()));
+            changed = true;
+        }
+        if !same(&self.locations, &imported.locations) {
+            builder.locations(Some(imported.locations.clone()));
+            changed = true;
+        }
+        if self.categories != imported.categories {
+            builder.categories(Some(imported.categories.clone()));
+            changed = true;
+        }
+        if self.recurrence != imported.recurrence {
+            builder.recurrence(Some(imported.recurrence.clone()));
+            changed = true;
+        }
+        if self.recurrence_dates != imported.recurrence_dates {
+            builder.recurrence_dates(Some(imported.recurrence_dates.clone()));
+            changed = true;
+        }
+        if self.excluded_dates != imported.excluded_dates {
+            builder.excluded_dates(Some(imported.excluded_dates.clone()));
+            changed = true;
+        }
+        if self.occurrence_overrides != imported.occurrence_overrides {
+            builder.occurrence_overrides(Some(imported.occurrence_overrides.clone()));
+            changed = true;
+        }
+        // postponed events are exported as tentative, that’s no news
+        let still_postponed =
+            self.status == EventStatus::Postponed && imported.status == EventStatus::Tentative;
+        if self.status != imported.status && !still_postponed {
+            builder.status(Some(imported.status));
+            changed = true;
+        }
+        changed.then_some(builder)
+    }
+}
+
+/// Compares types that don’t implement `PartialEq` by their serialization
+fn same<T: Serialize>(a: &T, b: &T) -> bool {
+    serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+}
+
+fn is_changed_occurrence(vevent: &iCalEvent) -> bool {
+    vevent.property_value("RECURRENCE-ID").is_some()
+}
+
+/// How to refer to a VEVENT in the skipped list
+fn label(vevent: &iCalEvent) -> String {
+    vevent
+        .get_summary()
+        .or(vevent.get_uid())
+        .unwrap_or("Untitled event")
+        .to_owned()
+}
+
+fn content_from_vevent(
+    vevent: &iCalEvent,
+    zones: &TimeZones,
+) -> std::result::Result<CalendarEventEventContent, String> {
+    if let Some(tzid) = unknown_time_zone(vevent, zones) {
+        return Err(format!("{}: unknown time zone {tzid}", label(vevent)));
+    }
+    let Some((utc_start, time_zone, all_day)) = vevent
+        .get_start()
+        .and_then(|start| utc_from_ical(start, zones))
+    else {
+        return Err(format!("{}: no valid start", label(vevent)));
+    };
+    // without an end, events last for the day or are just a point in time
+    let utc_end = vevent
+        .get_end()
+        .and_then(|end| utc_from_ical(end, zones))
+        .map(|(end, ..)| end)
+        .unwrap_or_else(|| {
+            if all_day {
+                utc_start + Duration::days(1)
+            } else {
+                utc_start
+            }
+        });
+    let recurrence = vevent
+        .property_value("RRULE")
+        .map(|rule| rule.parse::<RecurrenceRule>().map(|r| r.to_string()))
+        .transpose()
+        .map_err(|e| format!("{}: {e}", label(vevent)))?;
+
+    CalendarEventBuilder::default()
+        .title(vevent.get_summary().unwrap_or_default().to_owned())
+        .description(
+            vevent
+                .get_description()
+                .map(|d| TextMessageEventContent::plain(d.to_owned())),
+        )
+        .utc_start(utc_start)
+        .utc_end(utc_end)
+        .show_without_time(all_day)
+        .time_zone(time_zone.map(|tz| tz.name().to_owned()))
+        .locations(locations_from_vevent(vevent))
+        .categories(ical_categories(vevent))
+        .recurrence(recurrence)
+        .recurrence_dates(ical_dates(vevent, "RDATE", zones))
+        .excluded_dates(ical_dates(vevent, "EXDATE", zones))
+        .external_uid(vevent.get_uid().map(ToOwned::to_owned))
+        .status(match vevent.get_status() {
+            Some(iCalStatus::Tentative) => EventStatus::Tentative,
+            Some(iCalStatus::Cancelled) => EventStatus::Cancelled,
+            Some(iCalStatus::Confirmed) | None => EventStatus::Confirmed,
+        })
+        .build()
+        .map_err(|e| format!("{}: {e}", label(vevent)))
+}
+
+fn occurrence_override(
+    vevent: &iCalEvent,
+    series: &CalendarEventEventContent,
+    zones: &TimeZones,
+) -> Option<OccurrenceOverride> {
+    let recurrence_id = ical_dates(vevent, "RECURRENCE-ID", zones)
+        .into_iter()
+        .next()?;
+    let cancelled = vevent
+        .property_value("STATUS")
+        .map(|s| s.eq_ignore_ascii_case("CANCELLED"))
+        .unwrap_or_default();
+    let utc_start = vevent
+        .get_start()
+        .and_then(|start| utc_from_ical(start, zones))
+        .map(|(start, ..)| start)
+        .filter(|start| *start != recurrence_id);
+    let utc_end = vevent
+        .get_end()
+        .and_then(|end| utc_from_ical(end, zones))
+        .map(|(end, ..)| end)
+        .filter(|end| *end != recurrence_id + (series.utc_end - series.utc_start));
+    Some(OccurrenceOverride {
+        recurrence_id,
+        utc_start,
+        utc_end,
+        title: vevent
+            .get_summary()
+            .filter(|title| *title != series.title)
+            .map(ToOwned::to_owned),
+        cancelled,
+    })
+}
+
+/// The instant, the zone it was given in and whether it is just a date
+fn utc_from_ical(
+    date: DatePerhapsTime,
+    zones: &TimeZones,
+) -> Option<(UtcDateTime, Option<Tz>, bool)> {
+    match date {
+        DatePerhapsTime::Date(date) => Some((
+            Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0)?),
+            None,
+            true,
+        )),
+        DatePerhapsTime::DateTime(CalendarDateTime::Utc(date_time)) => {
+            Some((date_time, None, false))
+        }
+        // floating times are taken as UTC
+        DatePerhapsTime::DateTime(CalendarDateTime::Floating(date_time)) => {
+            Some((Utc.from_utc_datetime(&date_time), None, false))
+        }
+        DatePerhapsTime::DateTime(CalendarDateTime::WithTimezone { date_time, tzid }) => {
+            let tz = zones.resolve(&tzid)?;
+            Some((local_to_utc(tz, date_time)?, Some(tz), false))
+        }
+    }
+}
+
+/// What the `TZID`s of a file stand for
+#[derive(Clone, Debug, Default)]
+struct TimeZones {
+    /// The IANA zones the VTIMEZONEs of the file name as `X-LIC-LOCATION`,
+    /// by their `TZID`
+    locations: HashMap<String, String>,
+}
+
+impl TimeZones {
+    fn from_ical(ical: &str) -> Self {
+        let unfolded = ical
+            .replace("\r\n ", "")
+            .replace("\r\n\t", "")
+            .replace("\n ", "")
+            .replace("\n\t", "");
+        let mut locations = HashMap::new();
+        let mut zone: Option<(Option<String>, Option<String>)> = None;
+        for line in unfolded.lines() {
+            let Some((name, value)) = line.split_once(':') else {
+                continue;
+            };
+            let name = name.split(';').next().unwrap_or_default();
+            match (name.to_ascii_uppercase().as_str(), &mut zone) {
+                ("BEGIN", None) if value.eq_ignore_ascii_case("VTIMEZONE") => {
+                    zone = Some((None, None));
+                }
+                ("END", Some(_)) if value.eq_ignore_ascii_case("VTIMEZONE") => {
+                    if let Some((Some(tzid), Some(location))) = zone.take() {
+                        locations.insert(tzid, location);
+                    }
+                }
+                ("TZID", Some((tzid, _))) => *tzid = Some(value.to_owned()),
+                ("X-LIC-LOCATION", Some((_, location))) => *location = Some(value.to_owned()),
+                _ => {}
+            }
+        }
+        TimeZones { locations }
+    }
+
+    /// The zone by its IANA name, the location its VTIMEZONE gives, its
+    /// Windows name or an IANA name it ends with, like in
+    /// `/mozilla.org/20050126_1/Europe/Berlin`
+    fn resolve(&self, tzid: &str) -> Option<Tz> {
+        let tzid = tzid.trim_matches('"');
+        tzid.parse()
+            .ok()
+            .or_else(|| self.locations.get(tzid)?.parse().ok())
+            .or_else(|| windows_zone(tzid))
+            .or_else(|| {
+                let parts: Vec<&str> = tzid.split('/').collect();
+                (1..parts.len()).find_map(|start| parts[start..].join("/").parse().ok())
+            })
+    }
+}
+
+/// The first `TZID` of the VEVENT we can’t make sense of
+fn unknown_time_zone(vevent: &iCalEvent, zones: &TimeZones) -> Option<String> {
+    let single = vevent.properties().values();
+    let multi = vevent.multi_properties().values().flatten();
+    single.chain(multi).find_map(|property| {
+        let tzid = property.params().get("TZID")?.value();
+        zones.resolve(tzid).is_none().then(|| tzid.to_owned())
+    })
+}
+
+/// The IANA zone of the Windows zone names Outlook and Exchange use
+fn windows_zone(name: &str) -> Option<Tz> {
+    let iana = match name {
+        "Dateline Standard Time" => "Etc/GMT+12",
+        "Hawaiian Standard Time" => "Pacific/Honolulu",
+        "Alaskan Standard Time" => "America/Anchorage",
+        "Pacific Standard Time" => "America/Los_Angeles",
+        "US Mountain Standard Time" => "America/Phoenix",
+        "Mountain Standard Time" => "America/Denver",
+        "Central America Standard Time" => "America/Guatemala",
+        "Central Standard Time" => "America/Chicago",
+        "Central Standard Time (Mexico)" => "America/Mexico_City",
+        "Eastern Standard Time" => "America/New_York",
+        "Atlantic Standard Time" => "America/Halifax",
+        "Newfoundland Standard Time" => "America/St_Johns",
+        "E. South America Standard Time" => "America/Sao_Paulo",
+        "Argentina Standard Time" => "America/Argentina/Buenos_Aires",
+        "GMT Standard Time" => "Europe/London",
+        "Greenwich Standard Time" => "Atlantic/Reykjavik",
+        "W. Europe Standard Time" => "Europe/Berlin",
+        "Central Europe Standard Time" => "Europe/Budapest",
+        "Romance Standard Time" => "Europe/Paris",
+        "Central European Standard Time" => "Europe/Warsaw",
+        "W. Central Africa Standard Time" => "Africa/Lagos",
+        "GTB Standard Time" => "Europe/Bucharest",
+        "FLE Standard Time" => "Europe/Kiev",
+        "E. Europe Standard Time" => "Europe/Chisinau",
+        "Egypt Standard Time" => "Africa/Cairo",
+        "South Africa Standard Time" => "Africa/Johannesburg",
+        "Israel Standard Time" => "Asia/Jerusalem",
+        "Turkey Standard Time" => "Europe/Istanbul",
+        "Russian Standard Time" => "Europe/Moscow",
+        "E. Africa Standard Time" => "Africa/Nairobi",
+        "Arabian Standard Time" => "Asia/Dubai",
+        "Iran Standard Time" => "Asia/Tehran",
+        "Pakistan Standard Time" => "Asia/Karachi",
+        "India Standard Time" => "Asia/Kolkata",
+        "Nepal Standard Time" => "Asia/Kathmandu",
+        "Bangladesh Standard Time" => "Asia/Dhaka",
+        "SE Asia Standard Time" => "Asia/Bangkok",
+        "China Standard Time" => "Asia/Shanghai",
+        "Singapore Standard Time" => "Asia/Singapore",
+        "Taipei Standard Time" => "Asia/Taipei",
+        "Tokyo Standard Time" => "Asia/Tokyo",
+        "Korea Standard Time" => "Asia/Seoul",
+        "W. Australia Standard Time" => "Australia/Perth",
+        "Cen. Australia Standard Time" => "Australia/Adelaide",
+        "E. Australia Standard Time" => "Australia/Brisbane",
+        "AUS Eastern Standard Time" => "Australia/Sydney",
+        "New Zealand Standard Time" => "Pacific/Auckland",
+        _ => return None,
+    };
+    iana.parse().ok()
+}
+
+/// The dates of a property like `EXDATE`, which may come as a comma separated
+/// list or many properties
+fn ical_dates(vevent: &iCalEvent, key: &str, zones: &TimeZones) -> Vec<UtcDateTime> {
+    let single = vevent.properties().get(key).into_iter();
+    let multi = vevent.multi_properties().get(key).into_iter().flatten();
+    single
+        .chain(multi)
+        .flat_map(|property| {
+            let tz = property
+                .params()
+                .get("TZID")
+                .and_then(|p| zones.resolve(p.value()));
+            property
+                .value()
+                .split(',')
+                .filter_map(|value| parse_ical_date(value.trim(), tz))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+fn parse_ical_date(value: &str, tz: Option<Tz>) -> Option<UtcDateTime> {
+    if let Some(utc) = value.strip_suffix('Z') {
+        let date_time = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some(Utc.from_utc_datetime(&date_time));
+    }
+    let local = match NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S") {
+        Ok(local) => local,
+        Err(_) => NaiveDate::parse_from_str(value, "%Y%m%d")
+            .ok()?
+            .and_hms_opt(0, 0, 0)?,
+    };
+    local_to_utc(tz.unwrap_or(Tz::UTC), local)
+}
+
+/// Links become virtual locations, anything else a physical one
+fn locations_from_vevent(vevent: &iCalEvent) -> Vec<EventLocation> {
+    let mut locations = vec![];
+    if let Some(location) = vevent
+        .property_value("LOCATION")
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+    {
+        if is_link(location) {
+            locations.push(virtual_location(location));
+        } else {
+            locations.push(EventLocation::Physical {
+                name: Some(location.to_owned()),
+                description: None,
+                icon: None,
+                coordinates: vevent.property_value("GEO").and_then(geo_uri),
+                uri: None,
+            });
+        }
+    }
+    // video calls as given by RFC 7986 and Google
+    for key in ["CONFERENCE", "X-GOOGLE-CONFERENCE"] {
+        let Some(uri) = vevent.property_value(key).map(str::trim) else {
+            continue;
+        };
+        let known = locations
+            .iter()
+            .any(|l| matches!(l, EventLocation::Virtual { uri: known, .. } if known == uri));
+        if is_link(uri) && !known {
+            locations.push(virtual_location(uri));
+        }
+    }
+    locations
+}
+
+fn is_link(value: &str) -> bool {
+    value.starts_with("https://") || value.starts_with("http://")
+}
+
+fn virtual_location(uri: &str) -> EventLocation {
+    EventLocation::Virtual {
+        uri: uri.to_owned(),
+        name: None,
+        description: None,
+        icon: None,
+    }
+}
+
+/// `GEO` is `latitude;longitude`
+fn geo_uri(geo: &str) -> Option<String> {
+    let (latitude, longitude) = geo.split_once(';')?;
+    Some(format!("geo:{},{}", latitude.trim(), longitude.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ActerModel, TestModelBuilder};
+
+    const ICAL: &str = "BEGIN:VCALENDAR\r
+VERSION:2.0\r
+PRODID:-//Partner//Events//EN\r
+BEGIN:VEVENT\r
+UID:sync@partner.example.org\r
+SUMMARY:Weekly sync\r
+DESCRIPTION:Bring your updates\r
+DTSTART;TZID=Europe/Berlin:20240506T190000\r
+DTEND;TZID=Europe/Berlin:20240506T200000\r
+RRULE:FREQ=WEEKLY;COUNT=10\r
+EXDATE;TZID=Europe/Berlin:20240513T190000\r
+LOCATION:Community hall\r
+GEO:52.52;13.40\r
+CONFERENCE:https://meet.example.org/sync\r
+CATEGORIES:meetings\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:sync@partner.example.org\r
+RECURRENCE-ID;TZID=Europe/Berlin:20240520T190000\r
+SUMMARY:Weekly sync\r
+DTSTART;TZID=Europe/Berlin:20240521T190000\r
+DTEND;TZID=Europe/Berlin:20240521T200000\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:summerfest@partner.example.org\r
+SUMMARY:Summer fest\r
+STATUS:CANCELLED\r
+DTSTART;VALUE=DATE:20240720\r
+LOCATION:https://partner.example.org/fest\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:broken@partner.example.org\r
+SUMMARY:Without a start\r
+END:VEVENT\r
+END:VCALENDAR\r
+";
+
+    #[test]
+    fn reads_vevents() {
+        let import = CalendarImport::from_ical(ICAL).unwrap();
+        assert_eq!(import.events.len(), 2);
+        assert_eq!(import.skipped, ["Without a start: no valid start"]);
+
+        let sync = &import.events[0];
+        assert_eq!(
+            sync.external_uid.as_deref(),
+            Some("sync@partner.example.org")
+        );
+        assert_eq!(
+            sync.utc_start,
+            "2024-05-06T17:00:00Z".parse::<UtcDateTime>().unwrap()
+        );
+        assert_eq!(sync.time_zone.as_deref(), Some("Europe/Berlin"));
+        assert_eq!(sync.recurrence.as_deref(), Some("FREQ=WEEKLY;COUNT=10"));
+        assert_eq!(
+            sync.excluded_dates,
+            ["2024-05-13T17:00:00Z".parse::<UtcDateTime>().unwrap()]
+        );
+        assert_eq!(
+            sync.description.as_ref().map(|d| d.body.as_str()),
+            Some("Bring your updates")
+        );
+        assert_eq!(sync.categories, ["meetings"]);
+        assert!(matches!(
+            &sync.locations[0],
+            EventLocation::Physical { name: Some(name), coordinates: Some(geo), .. }
+                if name == "Community hall" && geo == "geo:52.52,13.40"
+        ));
+        assert!(matches!(
+            &sync.locations[1],
+            EventLocation::Virtual { uri, .. } if uri == "https://meet.example.org/sync"
+        ));
+        assert_eq!(
+            sync.occurrence_overrides,
+            [OccurrenceOverride {
+                recurrence_id: "2024-05-20T17:00:00Z".parse().unwrap(),
+                utc_start: Some("2024-05-21T17:00:00Z".parse().unwrap()),
+                utc_end: Some("2024-05-21T18:00:00Z".parse().unwrap()),
+                title: None,
+                cancelled: false,
+            }]
+        );
+
+        let fest = &import.events[1];
+        assert!(fest.show_without_time);
+        assert_eq!(fest.utc_end - fest.utc_start, Duration::days(1));
+        assert_eq!(fest.status, EventStatus::Cancelled);
+        assert_eq!(sync.status, EventStatus::Confirmed);
+        assert!(matches!(&fest.locations[0], EventLocation::Virtual { .. }));
+    }
+
+    #[test]
+    fn resolves_other_zone_names() {
+        let ical = "BEGIN:VCALENDAR\r
+VERSION:2.0\r
+PRODID:-//Outlook//EN\r
+BEGIN:VTIMEZONE\r
+TZID:Berlin, Amsterdam\r
+X-LIC-LOCATION:Europe/Berlin\r
+BEGIN:STANDARD\r
+DTSTART:16011028T030000\r
+TZOFFSETFROM:+0200\r
+TZOFFSETTO:+0100\r
+END:STANDARD\r
+END:VTIMEZONE\r
+BEGIN:VEVENT\r
+UID:windows@partner.example.org\r
+SUMMARY:Windows\r
+DTSTART;TZID=W. Europe Standard Time:20240506T190000\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:vtimezone@partner.example.org\r
+SUMMARY:VTIMEZONE\r
+DTSTART;TZID=\"Berlin, Amsterdam\":20240506T190000\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:prefixed@partner.example.org\r
+SUMMARY:Prefixed\r
+DTSTART;TZID=/mozilla.org/20050126_1/Europe/Berlin:20240506T190000\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:mars@partner.example.org\r
+SUMMARY:Landing\r
+DTSTART;TZID=Olympus Mons Time:20240506T190000\r
+END:VEVENT\r
+END:VCALENDAR\r
+";
+        let import = CalendarImport::from_ical(ical).unwrap();
+        assert_eq!(
+            import.skipped,
+            ["Landing: unknown time zone Olympus Mons Time"]
+        );
+        assert_eq!(import.events.len(), 3);
+        for event in &import.events {
+            assert_eq!(
+                event.utc_start,
+                "2024-05-06T17:00:00Z".parse::<UtcDateTime>().unwrap(),
+                "{}",
+                event.title
+            );
+            assert_eq!(event.time_zone.as_deref(), Some("Europe/Berlin"));
+        }
+    }
+
+    #[test]
+    fn updates_only_changes() {
+        let import = CalendarImport::from_ical(ICAL).unwrap();
+        let mut inner = serde_json::to_value(&import.events[0]).unwrap();
+        let existing: CalendarEvent = serde_json::from_value(serde_json::json!({
+            "inner": inner.clone(),
+            "meta": TestModelBuilder::fake_meta(),
+        }))
+        .unwrap();
+        assert_eq!(existing.ical_uid(), existing.event_id().to_string());
+        assert!(existing.update_from_import(&import.events[0]).is_none());
+
+        inner["title"] = "Weekly sync (old)".into();
+        let outdated: CalendarEvent = serde_json::from_value(serde_json::json!({
+            "inner": inner,
+            "meta": TestModelBuilder::fake_meta(),
+        }))
+        .unwrap();
+        let update = outdated
+            .update_from_import(&import.events[0])
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(update.title.as_deref(), Some("Weekly sync"));
+        assert!(update.utc_start.is_none());
+    }
+}
@@ -0,0 +1,314 @@
+use chrono::{Duration, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+use super::CalendarEvent;
+use crate::events::UtcDateTime;
+
+/// Never expand more than this many occurrences of a single event
+const MAX_OCCURRENCES: usize = 100_000;
+
+/// A single instance of a (recurring) calendar event
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CalendarEventOccurrence {
+    recurrence_id: UtcDateTime,
+    utc_start: UtcDateTime,
+    utc_end: UtcDateTime,
+    title: String,
+    moved: bool,
+}
+
+impl CalendarEventOccurrence {
+    /// The start this occurrence originally had, identifying it
+    pub fn recurrence_id(&self) -> UtcDateTime {
+        self.recurrence_id
+    }
+
+    pub fn utc_start(&self) -> UtcDateTime {
+        self.utc_start
+    }
+
+    pub fn utc_end(&self) -> UtcDateTime {
+        self.utc_end
+    }
+
+    pub fn title(&self) -> String {
+        self.title.clone()
+    }
+
+    /// Whether this occurrence doesn’t take place at its original time
+    pub fn is_moved(&self) -> bool {
+        self.moved
+    }
+}
+
+impl CalendarEvent {
+    pub fn is_recurring(&self) -> bool {
+        self.recurrence_rule().is_some() || !self.recurrence_dates.is_empty()
+    }
+
+    /// The occurrences overlapping the window from `start` to `end`, ordered
+    /// by their start
+    ///
+    /// Events that don’t recur have their one occurrence, if it falls into the
+    /// window. Excluded and cancelled occurrences are left out, moved ones show
+    /// up at their new time.
+    pub fn occurrences_between(
+        &self,
+        start: UtcDateTime,
+        end: UtcDateTime,
+    ) -> Vec<CalendarEventOccurrence> {
+        // a moved occurrence might have originally been after the window
+        let limit = self
+            .occurrence_overrides
+            .iter()
+            .filter(|o| o.utc_start.is_some())
+            .map(|o| o.recurrence_id + Duration::seconds(1))
+            .fold(end, |limit, after| limit.max(after));
+        let duration = self.utc_end - self.utc_start;
+
+        let mut occurrences: Vec<CalendarEventOccurrence> = self
+            .original_starts(limit)
+            .into_iter()
+            .filter_map(|recurrence_id| {
+                let change = self
+                    .occurrence_overrides
+                    .iter()
+                    .find(|o| o.recurrence_id == recurrence_id);
+                if change.map(|o| o.cancelled).unwrap_or_default() {
+                    return None;
+                }
+                let utc_start = change.and_then(|o| o.utc_start).unwrap_or(recurrence_id);
+                let utc_end = change
+                    .and_then(|o| o.utc_end)
+                    .unwrap_or(utc_start + duration);
+                Some(CalendarEventOccurrence {
+                    recurrence_id,
+                    utc_start,
+                    utc_end,
+                    title: change
+                        .and_then(|o| o.title.clone())
+                        .unwrap_or_else(|| self.title.clone()),
+                    moved: utc_start != recurrence_id,
+                })
+            })
+            .filter(|o| o.utc_start < end && (o.utc_end > start || o.utc_start >= start))
+            .collect();
+        occurrences.sort_by_key(|o| o.utc_start);
+        occurrences
+    }
+
+    /// The starts as given by the rule and the extra dates, before `limit`
+    ///
+    /// The rule repeats the local time of day in the time zone of the event,
+    /// so occurrences stay at the same wall clock time across DST changes.
+    fn original_starts(&self, limit: UtcDateTime) -> Vec<UtcDateTime> {
+        let first = self.utc_start;
+        let tz = self.time_zone().unwrap_or(Tz::UTC);
+        let local_first = first.with_timezone(&tz).naive_local();
+        let time_of_day = local_first.time();
+        let mut starts = vec![first];
+        let mut date = local_first.date();
+        let mut rule = self.recurrence_rule();
+        while let Some(current) = rule.take() {
+            if starts.len() >= MAX_OCCURRENCES {
+                break;
+            }
+            let Some(next) = current.next_after(date) else {
+                break;
+            };
+            let Some(start) = local_to_utc(tz, next.and_time(time_of_day)) else {
+                break;
+            };
+            if start >= limit {
+                break;
+            }
+            starts.push(start);
+            date = next;
+            rule = current.remaining();
+        }
+        starts.extend(self.recurrence_dates.iter().filter(|d| **d < limit));
+        starts.sort();
+        starts.dedup();
+        starts.retain(|s| !self.excluded_dates.contains(s));
+        starts
+    }
+}
+
+/// The instant of the wall clock time in the given zone
+///
+/// Times skipped by a DST change are moved past it, ambiguous ones take the
+/// earlier instant.
+pub(super) fn local_to_utc(tz: Tz, local: NaiveDateTime) -> Option<UtcDateTime> {
+    tz.from_local_datetime(&local)
+        .earliest()
+        .or_else(|| {
+            tz.from_local_datetime(&(local + Duration::hours(1)))
+                .earliest()
+        })
+        .map(|d| d.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::TestModelBuilder;
+
+    fn at(rfc3339: &str) -> UtcDateTime {
+        rfc3339.parse().unwrap()
+    }
+
+    #[test]
+    fn expands_weekly_meeting() {
+        let event: CalendarEvent = serde_json::from_value(serde_json::json!({
+            "inner": {
+                "title": "Weekly sync",
+                "utc_start": "2024-05-06T09:00:00Z",
+                "utc_end": "2024-05-06T10:00:00Z",
+                "recurrence": "FREQ=WEEKLY;COUNT=6",
+                "recurrence_dates": ["2024-05-22T15:00:00Z"],
+                "excluded_dates": ["2024-05-13T09:00:00Z"],
+                "occurrence_overrides": [
+                    { "recurrence_id": "2024-05-20T09:00:00Z", "cancelled": true },
+                    {
+                        "recurrence_id": "2024-06-10T09:00:00Z",
+                        "utc_start": "2024-05-28T09:00:00Z",
+                        "utc_end": "2024-05-28T11:00:00Z",
+                        "title": "Weekly sync, moved",
+                    },
+                ],
+            },
+            "meta": TestModelBuilder::fake_meta(),
+        }))
+        .unwrap();
+        assert!(event.is_recurring());
+
+        let occurrences =
+            event.occurrences_between(at("2024-05-06T00:00:00Z"), at("2024-06-01T00:00:00Z"));
+        let starts: Vec<_> = occurrences.iter().map(|o| o.utc_start()).collect();
+        assert_eq!(
+            starts,
+            [
+                at("2024-05-06T09:00:00Z"),
+                at("2024-05-22T15:00:00Z"),
+                at("2024-05-27T09:00:00Z"),
+                at("2024-05-28T09:00:00Z"),
+            ]
+        );
+        assert_eq!(occurrences[1].utc_end(), at("2024-05-22T16:00:00Z"));
+        let moved = &occurrences[3];
+        assert!(moved.is_moved());
+        assert_eq!(moved.recurrence_id(), at("2024-06-10T09:00:00Z"));
+        assert_eq!(moved.utc_end(), at("2024-05-28T11:00:00Z"));
+        assert_eq!(moved.title(), "Weekly sync, moved");
+
+        // COUNT=6 ends with the one moved away
+        let later =
+            event.occurrences_between(at("2024-06-01T00:00:00Z"), at("2024-07-01T00:00:00Z"));
+        assert_eq!(
+            later.iter().map(|o| o.utc_start()).collect::<Vec<_>>(),
+            [at("2024-06-03T09:00:00Z")]
+        );
+    }
+
+    #[test]
+    fn keeps_local_time_across_dst() {
+        let event: CalendarEvent = serde_json::from_value(serde_json::json!({
+            "inner": {
+                "title": "Choir practice",
+                "utc_start": "2024-03-21T18:00:00Z",
+                "utc_end": "2024-03-21T20:00:00Z",
+                "time_zone": "Europe/Berlin",
+                "recurrence": "FREQ=WEEKLY",
+            },
+            "meta": TestModelBuilder::fake_meta(),
+        }))
+        .unwrap();
+        let occurrences =
+            event.occurrences_between(at("2024-03-21T00:00:00Z"), at("2024-04-01T00:00:00Z"));
+        // 19:00 in Berlin, before and after the switch to summer time
+        assert_eq!(occurrences[0].utc_start(), at("2024-03-21T18:00:00Z"));
+        assert_eq!(occurrences[1].utc_start(), at("2024-03-28T18:00:00Z"));
+        let after_switch =
+            event.occurrences_between(at("2024-04-01T00:00:00Z"), at("2024-04-05T00:00:00Z"));
+        assert_eq!(after_switch[0].utc_start(), at("2024-04-04T17:00:00Z"));
+        assert_eq!(after_switch[0].utc_end(), at("2024-04-04T19:00:00Z"));
+    }
+
+    #[test]
+    fn stays_on_the_last_of_the_month() {
+        let event: CalendarEvent = serde_json::from_value(serde_json::json!({
+            "inner": {
+                "title": "Month end closing",
+                "utc_start": "2024-01-31T16:00:00Z",
+                "utc_end": "2024-01-31T17:00:00Z",
+                "recurrence": "FREQ=MONTHLY;COUNT=4",
+            },
+            "meta": TestModelBuilder::fake_meta(),
+        }))
+        .unwrap();
+        let occurrences =
+            event.occurrences_between(at("2024-01-01T00:00:00Z"), at("2025-01-01T00:00:00Z"));
+        // months without a 31st are skipped rather than moving the event
+        assert_eq!(
+            occurrences
+                .iter()
+                .map(|o| o.utc_start())
+                .collect::<Vec<_>>(),
+            [
+                at("2024-01-31T16:00:00Z"),
+                at("2024-03-31T16:00:00Z"),
+                at("2024-05-31T16:00:00Z"),
+                at("2024-07-31T16:00:00Z"),
+            ]
+        );
+    }
+
+    #[test]
+    fn unsupported_rules_dont_repeat() {
+        let event: CalendarEvent = serde_json::from_value(serde_json::json!({
+            "inner": {
+                "title": "Standup",
+                "utc_start": "2024-05-06T09:00:00Z",
+                "utc_end": "2024-05-06T09:15:00Z",
+                "recurrence": "FREQ=MONTHLY;BYDAY=MO,TU;BYSETPOS=1",
+            },
+            "meta": TestModelBuilder::fake_meta(),
+        }))
+        .unwrap();
+        assert!(!event.is_recurring());
+        let occurrences =
+            event.occurrences_between(at("2024-05-01T00:00:00Z"), at("2024-08-01T00:00:00Z"));
+        assert_eq!(
+            occurrences
+                .iter()
+                .map(|o| o.utc_start())
+                .collect::<Vec<_>>(),
+            [at("2024-05-06T09:00:00Z")]
+        );
+    }
+
+    #[test]
+    fn unknown_zones_fall_back_to_utc() {
+        let event: CalendarEvent = serde_json::from_value(serde_json::json!({
+            "inner": {
+                "title": "Landing",
+                "utc_start": "2024-05-06T09:00:00Z",
+                "utc_end": "2024-05-06T10:00:00Z",
+                "time_zone": "Mars/Olympus_Mons",
+                "recurrence": "FREQ=DAILY;COUNT=2",
+            },
+            "meta": TestModelBuilder::fake_meta(),
+        }))
+        .unwrap();
+        assert_eq!(event.time_zone(), Some(Tz::UTC));
+        let occurrences =
+            event.occurrences_between(at("2024-05-01T00:00:00Z"), at("2024-06-01T00:00:00Z"));
+        assert_eq!(
+            occurrences
+                .iter()
+                .map(|o| o.utc_start())
+                .collect::<Vec<_>>(),
+            [at("2024-05-06T09:00:00Z"), at("2024-05-07T09:00:00Z")]
+        );
+    }
+}
@@ -8,7 +8,7 @@ use tracing::{error, trace};
 
 use super::{ActerModel, AnyActerModel, Capability, EventMeta};
 use crate::{
-    events::rsvp::{RsvpBuilder, RsvpEventContent},
+    events::rsvp::{RsvpBuilder, RsvpEventContent, RsvpStatus},
     store::Store,
     Result,
 };
@@ -20,6 +20,106 @@ static RSVP_STATS_FIELD: &str = "rsvp_stats";
 pub struct RsvpStats {
     has_rsvp_entries: bool,
     total_rsvp_count: u32,
+    /// People attending with a seat
+    #[serde(default)]
+    confirmed_count: u32,
+    /// People who said yes after the event was full
+    #[serde(default)]
+    waitlisted_count: u32,
+    /// Seats left, `None` if the event has no capacity limit
+    #[serde(default)]
+    free_seats: Option<u32>,
+    /// People coming, counting those with a seat and their guests
+    #[serde(default)]
+    expected_attendance: u32,
+}
+
+/// Whether someone saying yes got a seat
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RsvpSeat {
+    Confirmed,
+    Waitlisted,
+}
+
+/// Who holds a seat at an event and who is waiting for one
+///
+/// Seats go to the yes-RSVPs in the order they were sent, each taking one for
+/// its sender and one per guest. Saying yes again keeps one’s place, it counts
+/// from the first yes since the last other response. A response that doesn’t
+/// fit anymore goes onto the waitlist, as do all after it. When someone with a
+/// seat changes their mind, the waitlist moves up. A party bigger than the
+/// event only takes as many seats as there are, so it can still move up once
+/// the seats before it are free.
+#[derive(Clone, Debug, Default, Getters)]
+pub struct RsvpAttendance {
+    /// in the order they said yes
+    confirmed: Vec<OwnedUserId>,
+    /// in the order they will move up
+    waitlisted: Vec<OwnedUserId>,
+    capacity: Option<u32>,
+    /// seats taken by the confirmed and their guests
+    taken_seats: u32,
+}
+
+impl RsvpAttendance {
+    fn new(history: &[Rsvp], capacity: Option<u32>) -> Self {
+        let mut by_sender: HashMap<&OwnedUserId, Vec<&Rsvp>> = HashMap::new();
+        for rsvp in history {
+            by_sender.entry(&rsvp.meta.sender).or_default().push(rsvp);
+        }
+        let mut yes: Vec<_> = by_sender
+            .into_values()
+            .filter_map(|mut rsvps| {
+                rsvps.sort_by_key(|r| r.meta.origin_server_ts);
+                let latest = *rsvps.last()?;
+                let since = rsvps
+                    .iter()
+                    .rev()
+                    .take_while(|r| r.status == RsvpStatus::Yes)
+                    .last()?;
+                Some((since.meta.origin_server_ts, latest))
+            })
+            .collect();
+        yes.sort_by(|(a_since, a), (b_since, b)| {
+            a_since
+                .cmp(b_since)
+                .then_with(|| a.meta.sender.cmp(&b.meta.sender))
+        });
+        let mut attendance = RsvpAttendance {
+            capacity,
+            ..Default::default()
+        };
+        for (_, rsvp) in yes {
+            let sender = rsvp.meta.sender.clone();
+            let party_size = capacity
+                .map(|c| rsvp.party_size().min(c.max(1)))
+                .unwrap_or_else(|| rsvp.party_size());
+            let seats = attendance.taken_seats.saturating_add(party_size);
+            let fits = capacity.map(|c| seats <= c).unwrap_or(true);
+            if fits && attendance.waitlisted.is_empty() {
+                attendance.taken_seats = seats;
+                attendance.confirmed.push(sender);
+            } else {
+                attendance.waitlisted.push(sender);
+            }
+        }
+        attendance
+    }
+
+    /// Seats left, `None` if the event has no capacity limit
+    pub fn free_seats(&self) -> Option<u32> {
+        self.capacity.map(|c| c.saturating_sub(self.taken_seats))
+    }
+
+    pub fn seat_of(&self, user_id: &UserId) -> Option<RsvpSeat> {
+        if self.confirmed.iter().any(|u| u == user_id) {
+            Some(RsvpSeat::Confirmed)
+        } else if self.waitlisted.iter().any(|u| u == user_id) {
+            Some(RsvpSeat::Waitlisted)
+        } else {
+            None
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -52,21 +152,59 @@ impl RsvpManager {
         self.event_id.clone()
     }
 
-    pub async fn rsvp_entries(&self) -> Result<HashMap<OwnedUserId, Rsvp>> {
-        let mut entries = HashMap::new();
-        for mdl in self
+    /// Every RSVP sent for this event, including those replaced by later ones
+    async fn rsvp_history(&self) -> Result<Vec<Rsvp>> {
+        Ok(self
             .store
             .get_list(&Rsvp::index_for(&self.event_id))
             .await?
-        {
-            if let AnyActerModel::Rsvp(c) = mdl {
-                let key = c.clone().meta.sender;
+            .filter_map(|mdl| match mdl {
+                AnyActerModel::Rsvp(c) => Some(c),
+                _ => None,
+            })
+            .collect())
+    }
+
+    /// The latest RSVP of every user who responded
+    pub async fn rsvp_entries(&self) -> Result<HashMap<OwnedUserId, Rsvp>> {
+        let mut entries: HashMap<OwnedUserId, Rsvp> = HashMap::new();
+        for c in self.rsvp_history().await? {
+            let key = c.clone().meta.sender;
+            if entries
+                .get(&key)
+                .map(|known| known.meta.origin_server_ts <= c.meta.origin_server_ts)
+                .unwrap_or(true)
+            {
                 entries.insert(key, c);
             }
         }
         Ok(entries)
     }
 
+    /// Who got a seat and who is waiting, given the capacity of the event
+    pub async fn attendance(&self) -> Result<RsvpAttendance> {
+        let history = self.rsvp_history().await?;
+        Ok(RsvpAttendance::new(&history, self.capacity().await))
+    }
+
+    async fn capacity(&self) -> Option<u32> {
+        match self.store.get(self.event_id.as_str()).await {
+            Ok(AnyActerModel::CalendarEvent(event)) => event.capacity,
+            _ => None,
+        }
+    }
+
+    /// Recount the seats, after an RSVP came in or the capacity changed
+    pub(crate) async fn update_attendance(&mut self) -> Result<()> {
+        let history = self.rsvp_history().await?;
+        let attendance = RsvpAttendance::new(&history, self.capacity().await);
+        self.stats.expected_attendance = attendance.taken_seats;
+        self.stats.confirmed_count = attendance.confirmed.len() as u32;
+        self.stats.waitlisted_count = attendance.waitlisted.len() as u32;
+        self.stats.free_seats = attendance.free_seats();
+        Ok(())
+    }
+
     pub(crate) fn add_rsvp_entry(&mut self, _entry: &Rsvp) -> Result<bool> {
         self.stats.has_rsvp_entries = true;
         self.stats.total_rsvp_count += 1;
@@ -161,7 +299,8 @@ impl ActerModel for Rsvp {
 
         let mut updates = store.save(self.clone().into()).await?;
         trace!(event_id=?self.event_id(), "saved rsvp entry");
-        if let Some(manager) = manager {
+        if let Some(mut manager) = manager {
+            manager.update_attendance().await?;
             updates.push(manager.save().await?);
         }
         Ok(updates)
@@ -195,3 +334,273 @@ impl From<OriginalMessageLikeEvent<RsvpEventContent>> for Rsvp {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        events::rsvp::MAX_GUESTS,
+        models::{fresh_executor, TestModelBuilder, TestRsvps},
+    };
+    use matrix_sdk_base::ruma::user_id;
+
+    #[tokio::test]
+    async fn waitlist_moves_up() -> Result<()> {
+        let executor = fresh_executor().await?;
+        let meta = TestModelBuilder::fake_meta();
+        let event_id = meta.event_id.clone();
+        let mut rsvps = TestRsvps::new(&event_id);
+
+        executor
+            .handle(TestModelBuilder::calendar_event(
+                meta,
+                serde_json::json!({
+                    "title": "Pottery workshop",
+                    "utc_start": "2024-05-06T09:00:00Z",
+                    "utc_end": "2024-05-06T12:00:00Z",
+                    "capacity": 2,
+                }),
+            ))
+            .await?;
+        let manager = RsvpManager::from_store_and_event_id(executor.store(), &event_id).await;
+        assert_eq!(*manager.free_seats(), Some(2));
+
+        for (sender, status) in [
+            ("@ada:example.org", "yes"),
+            ("@bob:example.org", "maybe"),
+            ("@cyd:example.org", "yes"),
+            ("@dan:example.org", "yes"),
+            ("@eve:example.org", "yes"),
+        ] {
+            executor.handle(rsvps.rsvp(sender, status)).await?;
+        }
+        let manager = RsvpManager::from_store_and_event_id(executor.store(), &event_id).await;
+        assert_eq!(*manager.confirmed_count(), 2);
+        assert_eq!(*manager.waitlisted_count(), 2);
+        assert_eq!(*manager.free_seats(), Some(0));
+        let attendance = manager.attendance().await?;
+        assert_eq!(
+            attendance.seat_of(user_id!("@dan:example.org")),
+            Some(RsvpSeat::Waitlisted)
+        );
+        assert_eq!(attendance.seat_of(user_id!("@bob:example.org")), None);
+
+        // ada can’t make it after all, dan moves up
+        executor
+            .handle(rsvps.rsvp("@ada:example.org", "no"))
+            .await?;
+        let manager = RsvpManager::from_store_and_event_id(executor.store(), &event_id).await;
+        let attendance = manager.attendance().await?;
+        assert_eq!(
+            attendance.confirmed(),
+            &[
+                user_id!("@cyd:example.org").to_owned(),
+                user_id!("@dan:example.org").to_owned()
+            ]
+        );
+        assert_eq!(
+            attendance.waitlisted(),
+            &[user_id!("@eve:example.org").to_owned()]
+        );
+        assert_eq!(*manager.waitlisted_count(), 1);
+
+        // and a bigger room makes space for everyone
+        executor
+            .handle(TestModelBuilder::calendar_event_update(
+                &event_id,
+                serde_json::json!({ "capacity": 5 }),
+            ))
+            .await?;
+        let manager = RsvpManager::from_store_and_event_id(executor.store(), &event_id).await;
+        assert_eq!(*manager.confirmed_count(), 3);
+        assert_eq!(*manager.waitlisted_count(), 0);
+        assert_eq!(*manager.free_seats(), Some(2));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn guests_take_seats() -> Result<()> {
+        let executor = fresh_executor().await?;
+        let meta = TestModelBuilder::fake_meta();
+        let event_id = meta.event_id.clone();
+        let mut rsvps = TestRsvps::new(&event_id);
+
+        executor
+            .handle(TestModelBuilder::calendar_event(
+                meta,
+                serde_json::json!({
+                    "title": "Boat trip",
+                    "utc_start": "2024-05-06T09:00:00Z",
+                    "utc_end": "2024-05-06T17:00:00Z",
+                    "capacity": 4,
+                }),
+            ))
+            .await?;
+        for (sender, extra) in [
+            ("@ada:example.org", serde_json::json!({ "guests": 2 })),
+            (
+                "@bob:example.org",
+                serde_json::json!({ "note": "arriving late" }),
+            ),
+            ("@cyd:example.org", serde_json::json!({ "guests": 1 })),
+            ("@dan:example.org", serde_json::json!({})),
+        ] {
+            executor
+                .handle(rsvps.rsvp_with(sender, "yes", extra))
+                .await?;
+        }
+        let manager = RsvpManager::from_store_and_event_id(executor.store(), &event_id).await;
+        assert_eq!(*manager.expected_attendance(), 4);
+        assert_eq!(*manager.confirmed_count(), 2);
+        assert_eq!(*manager.waitlisted_count(), 2);
+        assert_eq!(*manager.free_seats(), Some(0));
+        let entries = manager.rsvp_entries().await?;
+        let bob = &entries[user_id!("@bob:example.org")];
+        assert_eq!(bob.note.as_deref(), Some("arriving late"));
+        assert_eq!(bob.party_size(), 1);
+
+        // cyd and their guest don’t fit into the one seat bob leaves, and dan
+        // doesn’t skip the line
+        executor
+            .handle(rsvps.rsvp("@bob:example.org", "no"))
+            .await?;
+        let manager = RsvpManager::from_store_and_event_id(executor.store(), &event_id).await;
+        assert_eq!(*manager.expected_attendance(), 3);
+        assert_eq!(*manager.free_seats(), Some(1));
+        let attendance = manager.attendance().await?;
+        assert_eq!(
+            attendance.waitlisted(),
+            &[
+                user_id!("@cyd:example.org").to_owned(),
+                user_id!("@dan:example.org").to_owned()
+            ]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn saying_yes_again_keeps_the_seat() -> Result<()> {
+        let executor = fresh_executor().await?;
+        let meta = TestModelBuilder::fake_meta();
+        let event_id = meta.event_id.clone();
+        let mut rsvps = TestRsvps::new(&event_id);
+
+        executor
+            .handle(TestModelBuilder::calendar_event(
+                meta,
+                serde_json::json!({
+                    "title": "Cooking class",
+                    "utc_start": "2024-05-06T18:00:00Z",
+                    "utc_end": "2024-05-06T21:00:00Z",
+                    "capacity": 1,
+                }),
+            ))
+            .await?;
+        executor
+            .handle(rsvps.rsvp("@ada:example.org", "yes"))
+            .await?;
+        executor
+            .handle(rsvps.rsvp("@bob:example.org", "yes"))
+            .await?;
+        executor
+            .handle(rsvps.rsvp("@ada:example.org", "yes"))
+            .await?;
+        let manager = RsvpManager::from_store_and_event_id(executor.store(), &event_id).await;
+        let attendance = manager.attendance().await?;
+        assert_eq!(
+            attendance.confirmed(),
+            &[user_id!("@ada:example.org").to_owned()]
+        );
+        assert_eq!(
+            attendance.waitlisted(),
+            &[user_id!("@bob:example.org").to_owned()]
+        );
+
+        // after a maybe, ada has to queue up behind bob again
+        executor
+            .handle(rsvps.rsvp("@ada:example.org", "maybe"))
+            .await?;
+        executor
+            .handle(rsvps.rsvp("@ada:example.org", "yes"))
+            .await?;
+        let manager = RsvpManager::from_store_and_event_id(executor.store(), &event_id).await;
+        let attendance = manager.attendance().await?;
+        assert_eq!(
+            attendance.confirmed(),
+            &[user_id!("@bob:example.org").to_owned()]
+        );
+        assert_eq!(
+            attendance.waitlisted(),
+            &[user_id!("@ada:example.org").to_owned()]
+        );
+        assert_eq!(*manager.confirmed_count(), 1);
+        assert_eq!(*manager.waitlisted_count(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn huge_parties_dont_lock_out() -> Result<()> {
+        let executor = fresh_executor().await?;
+        let meta = TestModelBuilder::fake_meta();
+        let event_id = meta.event_id.clone();
+        let mut rsvps = TestRsvps::new(&event_id);
+
+        // we don’t send more guests than allowed
+        assert!(RsvpBuilder::default()
+            .to(event_id.clone())
+            .status(RsvpStatus::Yes)
+            .guests(Some(MAX_GUESTS + 1))
+            .build()
+            .is_err());
+
+        executor
+            .handle(TestModelBuilder::calendar_event(
+                meta,
+                serde_json::json!({
+                    "title": "Sailing",
+                    "utc_start": "2024-05-06T09:00:00Z",
+                    "utc_end": "2024-05-06T17:00:00Z",
+                    "capacity": 3,
+                }),
+            ))
+            .await?;
+        executor
+            .handle(rsvps.rsvp_with(
+                "@ada:example.org",
+                "yes",
+                serde_json::json!({ "guests": 1 }),
+            ))
+            .await?;
+        executor
+            .handle(rsvps.rsvp_with(
+                "@bob:example.org",
+                "yes",
+                serde_json::json!({ "guests": 50 }),
+            ))
+            .await?;
+        executor
+            .handle(rsvps.rsvp("@cyd:example.org", "yes"))
+            .await?;
+        let manager = RsvpManager::from_store_and_event_id(executor.store(), &event_id).await;
+        assert_eq!(*manager.confirmed_count(), 1);
+        assert_eq!(*manager.waitlisted_count(), 2);
+
+        // once ada leaves, bob’s party takes all seats the event has
+        executor
+            .handle(rsvps.rsvp("@ada:example.org", "no"))
+            .await?;
+        let manager = RsvpManager::from_store_and_event_id(executor.store(), &event_id).await;
+        let attendance = manager.attendance().await?;
+        assert_eq!(
+            attendance.confirmed(),
+            &[user_id!("@bob:example.org").to_owned()]
+        );
+        assert_eq!(
+            attendance.waitlisted(),
+            &[user_id!("@cyd:example.org").to_owned()]
+        );
+        assert_eq!(*manager.expected_attendance(), 3);
+        assert_eq!(*manager.free_seats(), Some(0));
+        Ok(())
+    }
+}
@@ -1,7 +1,20 @@
+mod burndown;
+mod duplicate;
+mod history;
+mod ical;
+mod query;
 mod task;
 mod task_list;
+mod time_log;
 
-pub use task::{Task, TaskSelfAssign, TaskSelfUnassign, TaskUpdate};
+pub use burndown::BurndownDay;
+pub use duplicate::TaskListDuplicateOptions;
+pub use history::{TaskFieldChange, TaskHistoryEntry, TaskHistoryKind};
+pub use query::{TaskQuery, TaskQueryBuilder, TaskSort, TaskSortKey};
+pub use task::{Task, TaskAssignment, TaskSelfAssign, TaskSelfUnassign, TaskUpdate};
 pub use task_list::{TaskList, TaskListUpdate, TaskStats};
+pub use time_log::TaskTimeLog;
+
+pub(crate) use ical::ical_categories;
 
 use crate::statics::KEYS::TASKS as KEYS;
@@ -0,0 +1,161 @@
+use chrono::{DateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use matrix_sdk_base::ruma::{MilliSecondsSinceUnixEpoch, UInt};
+use serde::{Deserialize, Serialize};
+
+use crate::events::Date;
+
+/// When a task has been created and marked done or undone since
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub(super) struct DoneHistory {
+    pub(super) created_at: MilliSecondsSinceUnixEpoch,
+    /// ordered by time
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(super) changes: Vec<(MilliSecondsSinceUnixEpoch, bool)>,
+}
+
+impl DoneHistory {
+    fn exists_at(&self, instant: MilliSecondsSinceUnixEpoch) -> bool {
+        self.created_at < instant
+    }
+
+    fn is_done_at(&self, instant: MilliSecondsSinceUnixEpoch) -> bool {
+        self.changes
+            .iter()
+            .rev()
+            .find(|(ts, _)| *ts < instant)
+            .map(|(_, done)| *done)
+            .unwrap_or_default()
+    }
+}
+
+/// Record that the task has been marked done or undone at the given time
+///
+/// Updates might arrive out of order, the changes are kept sorted by time.
+pub(super) fn record_done_change(
+    changes: &mut Vec<(MilliSecondsSinceUnixEpoch, bool)>,
+    instant: MilliSecondsSinceUnixEpoch,
+    done: bool,
+) {
+    let idx = changes.partition_point(|(ts, _)| *ts <= instant);
+    changes.insert(idx, (instant, done));
+}
+
+/// How many tasks of a list were open and done at the end of a day
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BurndownDay {
+    date: Date,
+    open: u32,
+    done: u32,
+}
+
+impl BurndownDay {
+    pub fn date(&self) -> Date {
+        self.date
+    }
+
+    pub fn date_str(&self) -> String {
+        self.date.format("%Y-%m-%d").to_string()
+    }
+
+    pub fn open_count(&self) -> u32 {
+        self.open
+    }
+
+    pub fn done_count(&self) -> u32 {
+        self.done
+    }
+}
+
+/// The open and done counts at the end of every day from `start` to `end`
+///
+/// Days end at midnight in the given time zone.
+pub(super) fn burndown<'a>(
+    histories: impl Iterator<Item = &'a DoneHistory> + Clone,
+    start: Date,
+    end: Date,
+    tz: Tz,
+) -> Vec<BurndownDay> {
+    start
+        .iter_days()
+        .take_while(|date| *date <= end)
+        .map(|date| {
+            let end_of_day = end_of_day(date, tz);
+            let (mut open, mut done) = (0, 0);
+            for history in histories.clone().filter(|h| h.exists_at(end_of_day)) {
+                if history.is_done_at(end_of_day) {
+                    done += 1;
+                } else {
+                    open += 1;
+                }
+            }
+            BurndownDay { date, open, done }
+        })
+        .collect()
+}
+
+fn end_of_day(date: Date, tz: Tz) -> MilliSecondsSinceUnixEpoch {
+    date.succ_opt()
+        .and_then(|next| next.and_hms_opt(0, 0, 0))
+        .and_then(|midnight| tz.from_local_datetime(&midnight).earliest())
+        .map(|local| millis(local.with_timezone(&Utc)))
+        .unwrap_or(MilliSecondsSinceUnixEpoch(UInt::MAX))
+}
+
+fn millis(instant: DateTime<Utc>) -> MilliSecondsSinceUnixEpoch {
+    let millis = u64::try_from(instant.timestamp_millis()).unwrap_or_default();
+    MilliSecondsSinceUnixEpoch(UInt::new_saturating(millis))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ts(rfc3339: &str) -> MilliSecondsSinceUnixEpoch {
+        millis(rfc3339.parse().unwrap())
+    }
+
+    #[test]
+    fn counts_per_day() {
+        let mut reopened = DoneHistory {
+            created_at: ts("2024-05-01T09:00:00Z"),
+            changes: vec![],
+        };
+        // arriving out of order
+        record_done_change(&mut reopened.changes, ts("2024-05-03T20:00:00Z"), false);
+        record_done_change(&mut reopened.changes, ts("2024-05-02T10:00:00Z"), true);
+        let later = DoneHistory {
+            created_at: ts("2024-05-02T23:30:00Z"),
+            changes: vec![(ts("2024-05-03T08:00:00Z"), true)],
+        };
+        let histories = [reopened, later];
+
+        let days = burndown(
+            histories.iter(),
+            Date::from_ymd_opt(2024, 5, 1).unwrap(),
+            Date::from_ymd_opt(2024, 5, 3).unwrap(),
+            Tz::UTC,
+        );
+        let counts: Vec<_> = days
+            .iter()
+            .map(|d| (d.date_str(), d.open_count(), d.done_count()))
+            .collect();
+        assert_eq!(
+            counts,
+            [
+                ("2024-05-01".to_owned(), 1, 0),
+                ("2024-05-02".to_owned(), 1, 1),
+                ("2024-05-03".to_owned(), 1, 1),
+            ]
+        );
+
+        // in Berlin, the second task was created on the 3rd already
+        let days = burndown(
+            histories.iter(),
+            Date::from_ymd_opt(2024, 5, 2).unwrap(),
+            Date::from_ymd_opt(2024, 5, 2).unwrap(),
+            Tz::Europe__Berlin,
+        );
+        assert_eq!((days[0].open_count(), days[0].done_count()), (0, 1));
+    }
+}
@@ -0,0 +1,281 @@
+use chrono::TimeDelta;
+use matrix_sdk_base::ruma::OwnedEventId;
+use std::collections::{HashMap, HashSet};
+
+use super::{Task, TaskList};
+use crate::{
+    events::{
+        tasks::{ChecklistItem, TaskBuilder, TaskListBuilder, TaskState},
+        BelongsTo,
+    },
+    Error, Result,
+};
+
+/// What to change about the tasks when duplicating a [`TaskList`]
+#[derive(Clone, Debug, Default)]
+pub struct TaskListDuplicateOptions {
+    /// Start every copy undone, in no state and with its checklist unchecked
+    pub reset_progress: bool,
+    /// Move the due and start dates of every copy by this many days
+    pub shift_days: i64,
+    /// Assign the copies to the same members, as far as they are in the
+    /// target space
+    pub keep_assignees: bool,
+}
+
+impl TaskList {
+    /// A new list with the name, description, display, categories and
+    /// workflow states of this one
+    ///
+    /// The copy never takes over the inbox or trash role.
+    pub fn duplicate_builder(&self) -> TaskListBuilder {
+        let mut builder = TaskListBuilder::default();
+        builder
+            .name(self.name.clone())
+            .description(self.description.clone())
+            .display(self.display.clone())
+            .sort_order(self.sort_order)
+            .time_zone(self.time_zone)
+            .keywords(self.keywords.clone())
+            .categories(self.categories.clone())
+            .states(self.states.iter().fold(Vec::new(), |mut states, state| {
+                // older lists might repeat a key, the first one wins like in `state_for`
+                if !states.iter().any(|s: &TaskState| s.key == state.key) {
+                    states.push(state.clone());
+                }
+                states
+            }));
+        builder
+    }
+}
+
+impl Task {
+    /// A copy of this task in the given list
+    ///
+    /// References to the parent task and blocking tasks are kept as far as
+    /// they have been copied before, as given by `copied`. Fails if the shift
+    /// moves the dates out of the range we can represent.
+    pub fn duplicate_builder(
+        &self,
+        task_list_id: OwnedEventId,
+        options: &TaskListDuplicateOptions,
+        copied: &HashMap<OwnedEventId, OwnedEventId>,
+    ) -> Result<TaskBuilder> {
+        let out_of_range = || Error::Custom(format!("Can’t shift by {} days", options.shift_days));
+        let shift = TimeDelta::try_days(options.shift_days).ok_or_else(out_of_range)?;
+        let due_date = self
+            .due_date
+            .map(|d| d.checked_add_signed(shift).ok_or_else(out_of_range))
+            .transpose()?;
+        let utc_start = self
+            .utc_start
+            .map(|d| d.checked_add_signed(shift).ok_or_else(out_of_range))
+            .transpose()?;
+        let checklist: Vec<ChecklistItem> = self
+            .checklist
+            .iter()
+            .map(|item| {
+                let mut item = item.clone();
+                if options.reset_progress {
+                    item.done = false;
+                }
+                item
+            })
+            .collect();
+        let mut builder = TaskBuilder::default();
+        builder
+            .title(self.title.clone())
+            .task_list_id(task_list_id)
+            .parent_task_id(
+                self.parent_task_id
+                    .as_ref()
+                    .and_then(|p| copied.get(&p.event_id))
+                    .map(|id| BelongsTo::from(id.clone())),
+            )
+            .description(self.description.clone())
+            .due_date(due_date)
+            .utc_due_time_of_day(self.utc_due_time_of_day)
+            .due_time_of_day(self.due_time_of_day)
+            .time_zone(self.time_zone)
+            .recurrence(self.recurrence.clone())
+            .utc_start(utc_start)
+            .progress_percent(if options.reset_progress {
+                None
+            } else {
+                self.progress_percent
+            })
+            .estimate_seconds(self.estimate_seconds)
+            .sort_order(self.sort_order)
+            .state(if options.reset_progress {
+                None
+            } else {
+                self.state.clone()
+            })
+            .priority(self.priority.clone())
+            .display(self.display.clone())
+            .keywords(self.keywords.clone())
+            .categories(self.categories.clone())
+            .blocked_by(
+                self.blocked_by
+                    .iter()
+                    .filter_map(|id| copied.get(id).cloned())
+                    .collect::<Vec<_>>(),
+            )
+            .checklist(checklist)
+            .progress_from_checklist(self.progress_from_checklist);
+        Ok(builder)
+    }
+
+    /// The tasks ordered so that parents and blocking tasks come before the
+    /// tasks referencing them, as far as that is possible
+    pub fn duplication_order(mut tasks: Vec<Task>) -> Vec<Task> {
+        tasks.sort_by_key(|t| t.sort_order);
+        let ids: HashSet<OwnedEventId> = tasks.iter().map(|t| t.meta.event_id.clone()).collect();
+        let mut ordered: Vec<Task> = Vec::with_capacity(tasks.len());
+        let mut placed: HashSet<OwnedEventId> = HashSet::new();
+        while !tasks.is_empty() {
+            let ready = tasks.iter().position(|task| {
+                task.parent_task_id
+                    .iter()
+                    .map(|p| &p.event_id)
+                    .chain(task.blocked_by.iter())
+                    .all(|id| !ids.contains(id) || placed.contains(id))
+            });
+            // a cycle, just go on with the next one
+            let task = tasks.remove(ready.unwrap_or_default());
+            placed.insert(task.meta.event_id.clone());
+            ordered.push(task);
+        }
+        ordered
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{events::Date, models::TestModelBuilder};
+
+    fn task(id: &str, content: serde_json::Value) -> Task {
+        let mut meta = TestModelBuilder::fake_meta();
+        meta.event_id = id.try_into().unwrap();
+        let mut inner = serde_json::json!({
+            "title": id,
+            "m.relates_to": {
+                "rel_type": "global.acter.dev.belongs_to",
+                "event_id": "$tasklist",
+            },
+        });
+        inner
+            .as_object_mut()
+            .unwrap()
+            .extend(content.as_object().unwrap().clone());
+        serde_json::from_value(serde_json::json!({
+            "inner": inner,
+            "meta": meta,
+            "assignees": ["@test:example.org"],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn copies_in_dependency_order() {
+        let tasks = vec![
+            task(
+                "$child",
+                serde_json::json!({
+                    "parent_task_id": {
+                        "rel_type": "global.acter.dev.belongs_to",
+                        "event_id": "$parent",
+                    },
+                    "sort_order": 1,
+                }),
+            ),
+            task("$blocked", serde_json::json!({ "blocked_by": ["$parent"] })),
+            task(
+                "$parent",
+                serde_json::json!({
+                    "due_date": "2024-05-03",
+                    "progress_percent": 50,
+                    "checklist": [{ "id": "a", "title": "Pack the van", "done": true }],
+                    "sort_order": 2,
+                }),
+            ),
+        ];
+        let ordered: Vec<_> = Task::duplication_order(tasks)
+            .into_iter()
+            .map(|t| t.meta.event_id.to_string())
+            .collect();
+        assert_eq!(ordered, ["$parent", "$blocked", "$child"]);
+    }
+
+    #[test]
+    fn resets_and_shifts() {
+        let new_list_id: OwnedEventId = "$newlist".try_into().unwrap();
+        let options = TaskListDuplicateOptions {
+            reset_progress: true,
+            shift_days: 7,
+            keep_assignees: false,
+        };
+        let parent = task(
+            "$parent",
+            serde_json::json!({
+                "due_date": "2024-05-03",
+                "progress_percent": 50,
+                "state": "review",
+                "checklist": [{ "id": "a", "title": "Pack the van", "done": true }],
+            }),
+        );
+        let copy = parent
+            .duplicate_builder(new_list_id.clone(), &options, &HashMap::new())
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(copy.task_list_id.event_id, new_list_id);
+        assert_eq!(copy.due_date, Date::from_ymd_opt(2024, 5, 10));
+        assert_eq!(copy.progress_percent, None);
+        assert_eq!(copy.state, None);
+        assert!(!copy.checklist[0].done);
+
+        let copied = HashMap::from([(
+            parent.meta.event_id.clone(),
+            "$parentcopy".try_into().unwrap(),
+        )]);
+        let child = task(
+            "$child",
+            serde_json::json!({
+                "parent_task_id": {
+                    "rel_type": "global.acter.dev.belongs_to",
+                    "event_id": "$parent",
+                },
+                "blocked_by": ["$parent", "$elsewhere"],
+            }),
+        );
+        let copy = child
+            .duplicate_builder(new_list_id, &options, &copied)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(
+            copy.parent_task_id.map(|p| p.event_id.to_string()),
+            Some("$parentcopy".to_owned())
+        );
+        assert_eq!(
+            copy.blocked_by,
+            copied.values().cloned().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn refuses_shifts_out_of_range() {
+        let due = task("$due", serde_json::json!({ "due_date": "2024-05-03" }));
+        for shift_days in [i64::MAX, i64::MIN, 400_000_000] {
+            let options = TaskListDuplicateOptions {
+                shift_days,
+                ..Default::default()
+            };
+            assert!(due
+                .duplicate_builder("$newlist".try_into().unwrap(), &options, &HashMap::new())
+                .is_err());
+        }
+    }
+}
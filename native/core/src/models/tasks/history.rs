@@ -0,0 +1,222 @@
+use matrix_sdk_base::ruma::{EventId, MilliSecondsSinceUnixEpoch, OwnedEventId, OwnedUserId};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::super::{AnyActerModel, EventMeta, Store};
+use crate::{events::tasks::TaskEventContent, Result};
+
+/// A field of a task changed by an update, with its values as JSON
+///
+/// A missing value means the field wasn’t set.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct TaskFieldChange {
+    pub field: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub new: Option<Value>,
+}
+
+impl TaskFieldChange {
+    /// The field-by-field difference between the task before and after
+    pub(super) fn diff(before: &TaskEventContent, after: &TaskEventContent) -> Vec<Self> {
+        let (Ok(Value::Object(before)), Ok(Value::Object(after))) =
+            (serde_json::to_value(before), serde_json::to_value(after))
+        else {
+            return vec![];
+        };
+        let mut fields: Vec<&String> = before.keys().chain(after.keys()).collect();
+        fields.sort();
+        fields.dedup();
+        fields
+            .into_iter()
+            .filter_map(|field| {
+                let old = before.get(field);
+                let new = after.get(field);
+                if old == new {
+                    return None;
+                }
+                Some(TaskFieldChange {
+                    field: field_name(field),
+                    old: old.cloned(),
+                    new: new.cloned(),
+                })
+            })
+            .collect()
+    }
+
+    pub fn field(&self) -> String {
+        self.field.clone()
+    }
+
+    pub fn old_value(&self) -> Option<String> {
+        self.old.as_ref().map(to_string)
+    }
+
+    pub fn new_value(&self) -> Option<String> {
+        self.new.as_ref().map(to_string)
+    }
+}
+
+fn field_name(key: &str) -> String {
+    match key {
+        "m.relates_to" => "task_list_id".to_owned(),
+        _ => key.to_owned(),
+    }
+}
+
+fn to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Object(o) if o.contains_key("event_id") => {
+            // a reference, like the task list, is just the event id
+            to_string(&o["event_id"])
+        }
+        _ => value.to_string(),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TaskHistoryKind {
+    Updated(Vec<TaskFieldChange>),
+    SelfAssigned,
+    SelfUnassigned,
+    Assigned(OwnedUserId),
+    Unassigned(OwnedUserId),
+}
+
+/// A change made to a task, as recorded in its history
+#[derive(Clone, Debug)]
+pub struct TaskHistoryEntry {
+    event_id: OwnedEventId,
+    sender: OwnedUserId,
+    origin_server_ts: MilliSecondsSinceUnixEpoch,
+    kind: TaskHistoryKind,
+}
+
+impl TaskHistoryEntry {
+    pub(super) fn new(meta: &EventMeta, kind: TaskHistoryKind) -> Self {
+        TaskHistoryEntry {
+            event_id: meta.event_id.clone(),
+            sender: meta.sender.clone(),
+            origin_server_ts: meta.origin_server_ts,
+            kind,
+        }
+    }
+
+    /// The history of the task, oldest change first
+    pub async fn for_task(store: &Store, task_id: &EventId) -> Result<Vec<TaskHistoryEntry>> {
+        let mut entries: Vec<TaskHistoryEntry> = store
+            .get_list(&history_key_for(task_id))
+            .await?
+            .filter_map(|mdl| match mdl {
+                AnyActerModel::TaskUpdate(m) => Some(m.history_entry()),
+                AnyActerModel::TaskSelfAssign(m) => Some(m.history_entry()),
+                AnyActerModel::TaskSelfUnassign(m) => Some(m.history_entry()),
+                AnyActerModel::TaskAssignment(m) => Some(m.history_entry()),
+                _ => None,
+            })
+            .collect();
+        entries.sort_by_key(|e| e.origin_server_ts);
+        Ok(entries)
+    }
+
+    pub fn event_id_str(&self) -> String {
+        self.event_id.to_string()
+    }
+
+    pub fn sender_str(&self) -> String {
+        self.sender.to_string()
+    }
+
+    pub fn origin_server_ts(&self) -> u64 {
+        self.origin_server_ts.get().into()
+    }
+
+    pub fn kind(&self) -> &TaskHistoryKind {
+        &self.kind
+    }
+
+    /// `updated`, `self_assigned`, `self_unassigned`, `assigned` or `unassigned`
+    pub fn kind_str(&self) -> String {
+        match self.kind {
+            TaskHistoryKind::Updated(_) => "updated",
+            TaskHistoryKind::SelfAssigned => "self_assigned",
+            TaskHistoryKind::SelfUnassigned => "self_unassigned",
+            TaskHistoryKind::Assigned(_) => "assigned",
+            TaskHistoryKind::Unassigned(_) => "unassigned",
+        }
+        .to_owned()
+    }
+
+    /// Who has been (un)assigned by someone else
+    pub fn assignee_str(&self) -> Option<String> {
+        match &self.kind {
+            TaskHistoryKind::Assigned(user_id) | TaskHistoryKind::Unassigned(user_id) => {
+                Some(user_id.to_string())
+            }
+            _ => None,
+        }
+    }
+
+    /// The fields changed by an update
+    pub fn changes(&self) -> Vec<TaskFieldChange> {
+        match &self.kind {
+            TaskHistoryKind::Updated(changes) => changes.clone(),
+            _ => vec![],
+        }
+    }
+}
+
+pub(super) fn history_key_for(task_id: &EventId) -> String {
+    format!("{task_id}::history")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::tasks::TaskBuilder;
+    use matrix_sdk_base::ruma::owned_event_id;
+
+    #[test]
+    fn diff_lists_changed_fields() {
+        let before = TaskBuilder::default()
+            .title("Water the plants".to_owned())
+            .task_list_id(owned_event_id!("$inbox"))
+            .due_date(chrono::NaiveDate::from_ymd_opt(2024, 5, 3))
+            .build()
+            .unwrap();
+        let mut after = before.clone();
+        after.title = "Water the garden".to_owned();
+        after.task_list_id = owned_event_id!("$garden").into();
+        after.due_date = None;
+        after.keywords = vec!["outdoor".to_owned()];
+
+        let changes = TaskFieldChange::diff(&before, &after);
+        let summary: Vec<_> = changes
+            .iter()
+            .map(|c| (c.field(), c.old_value(), c.new_value()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("due_date".to_owned(), Some("2024-05-03".to_owned()), None),
+                (
+                    "keywords".to_owned(),
+                    None,
+                    Some(r#"["outdoor"]"#.to_owned())
+                ),
+                (
+                    "task_list_id".to_owned(),
+                    Some("$inbox".to_owned()),
+                    Some("$garden".to_owned())
+                ),
+                (
+                    "title".to_owned(),
+                    Some("Water the plants".to_owned()),
+                    Some("Water the garden".to_owned())
+                ),
+            ]
+        );
+    }
+}
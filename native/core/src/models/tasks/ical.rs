@@ -0,0 +1,214 @@
+use chrono::{NaiveTime, Timelike, Utc};
+use chrono_tz::Tz;
+use icalendar::{
+    Calendar, CalendarDateTime, Component, DatePerhapsTime, EventLike, Todo, TodoStatus,
+};
+use matrix_sdk_base::ruma::events::room::message::TextMessageEventContent;
+use std::collections::HashSet;
+
+use super::{
+    super::{AnyActerModel, Store},
+    Task, TaskList,
+};
+use crate::{
+    events::tasks::{Priority, TaskBuilder},
+    Error, Result,
+};
+
+impl Task {
+    /// The UID of this task in iCalendar, the one it was imported with if any
+    pub fn ical_uid(&self) -> String {
+        self.external_uid
+            .clone()
+            .unwrap_or_else(|| self.meta.event_id.to_string())
+    }
+
+    pub fn as_ical_todo(&self) -> Todo {
+        let mut todo = Todo::new();
+        todo.uid(&self.ical_uid()).summary(&self.title);
+        if let Some(description) = &self.description {
+            todo.description(&description.body);
+        }
+        if let Some(start) = self.utc_start {
+            todo.starts(start);
+        }
+        if let Some(due_date) = self.due_date {
+            if self.due_time_of_day.is_some() || self.utc_due_time_of_day.is_some() {
+                if let Some(due) = self.due_at() {
+                    todo.due(due.with_timezone(&Utc));
+                }
+            } else {
+                todo.due(due_date);
+            }
+        }
+        if !matches!(self.priority, Priority::Undefined) {
+            todo.priority(self.priority.clone() as u32);
+        }
+        if let Some(percent) = self.percent() {
+            todo.percent_complete(percent);
+        }
+        todo.status(if self.is_done() {
+            TodoStatus::Completed
+        } else if self.percent().unwrap_or_default() > 0 {
+            TodoStatus::InProcess
+        } else {
+            TodoStatus::NeedsAction
+        });
+        if !self.categories.is_empty() {
+            todo.add_property("CATEGORIES", &self.categories.join(","));
+        }
+        for assignee in self.assignees() {
+            todo.add_multi_property("ATTENDEE", &assignee.matrix_uri(false).to_string());
+        }
+        todo.done()
+    }
+}
+
+impl TaskList {
+    /// The tasks to create for the VTODOs of `ical` that aren’t in this list yet
+    ///
+    /// Todos are matched by their UID, also against tasks imported before.
+    pub async fn tasks_from_ical(&self, store: &Store, ical: &str) -> Result<Vec<TaskBuilder>> {
+        let calendar: Calendar = ical.parse().map_err(Error::Custom)?;
+        let mut known: HashSet<String> = store
+            .get_list(&self.tasks_key())
+            .await?
+            .filter_map(|mdl| match mdl {
+                AnyActerModel::Task(task) => Some(task.ical_uid()),
+                _ => None,
+            })
+            .collect();
+        let mut builders = Vec::new();
+        for todo in calendar.components.iter().filter_map(|c| c.as_todo()) {
+            if let Some(uid) = todo.get_uid() {
+                if !known.insert(uid.to_owned()) {
+                    continue;
+                }
+            }
+            let mut builder = task_builder_from_ical_todo(todo);
+            builder.task_list_id(self.meta.event_id.clone());
+            builders.push(builder);
+        }
+        Ok(builders)
+    }
+}
+
+/// The task for this VTODO, without a list to put it in
+pub(crate) fn task_builder_from_ical_todo(todo: &Todo) -> TaskBuilder {
+    let mut builder = TaskBuilder::default();
+    builder
+        .title(todo.get_summary().unwrap_or_default().to_owned())
+        .external_uid(todo.get_uid().map(ToOwned::to_owned))
+        .description(
+            todo.get_description()
+                .map(|d| TextMessageEventContent::plain(d.to_owned())),
+        )
+        .categories(ical_categories(todo));
+    match todo.get_due() {
+        Some(DatePerhapsTime::Date(date)) => {
+            builder.due_date(date);
+        }
+        Some(DatePerhapsTime::DateTime(CalendarDateTime::Utc(due))) => {
+            builder
+                .due_date(due.date_naive())
+                .utc_due_time_of_day(seconds_since_midnight(&due.time()));
+        }
+        Some(DatePerhapsTime::DateTime(CalendarDateTime::Floating(due))) => {
+            builder
+                .due_date(due.date())
+                .due_time_of_day(seconds_since_midnight(&due.time()));
+        }
+        Some(DatePerhapsTime::DateTime(CalendarDateTime::WithTimezone { date_time, tzid })) => {
+            builder
+                .due_date(date_time.date())
+                .due_time_of_day(seconds_since_midnight(&date_time.time()))
+                .time_zone(tzid.parse::<Tz>().ok());
+        }
+        None => {}
+    }
+    if let Some(priority) = todo.get_priority() {
+        builder.priority(Priority::from_rank(
+            u8::try_from(priority).unwrap_or_default(),
+        ));
+    }
+    let percent = match todo.get_status() {
+        Some(TodoStatus::Completed) => Some(100),
+        _ => todo.get_percent_complete().map(|p| p.min(100)),
+    };
+    builder.progress_percent(percent);
+    builder
+}
+
+fn seconds_since_midnight(time: &NaiveTime) -> i32 {
+    time.num_seconds_from_midnight() as i32
+}
+
+/// Categories may come as one comma separated property or many
+pub(crate) fn ical_categories(component: &impl Component) -> Vec<String> {
+    let single = component.property_value("CATEGORIES").into_iter();
+    let multi = component
+        .multi_properties()
+        .get("CATEGORIES")
+        .into_iter()
+        .flatten()
+        .map(|p| p.value());
+    single
+        .chain(multi)
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+        .map(ToOwned::to_owned)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::TestModelBuilder;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn todo_round_trip() {
+        let task: Task = serde_json::from_value(serde_json::json!({
+            "inner": {
+                "title": "Print flyers",
+                "description": { "body": "Two hundred, on green paper" },
+                "due_date": "2024-05-03",
+                "priority": 2,
+                "progress_percent": 40,
+                "categories": ["print", "marketing"],
+                "external_uid": "flyers@example.org",
+                "m.relates_to": {
+                    "rel_type": "global.acter.dev.belongs_to",
+                    "event_id": "$tasklist",
+                },
+            },
+            "meta": TestModelBuilder::fake_meta(),
+            "assignees": ["@test:example.org"],
+        }))
+        .unwrap();
+
+        let ical = Calendar::from([task.as_ical_todo()]).to_string();
+        let calendar: Calendar = ical.parse().unwrap();
+        let todo = calendar.components[0].as_todo().unwrap();
+        assert_eq!(todo.get_uid(), Some("flyers@example.org"));
+        assert_eq!(todo.get_status(), Some(TodoStatus::InProcess));
+
+        let imported = task_builder_from_ical_todo(todo)
+            .task_list_id(TestModelBuilder::fake_meta().event_id)
+            .build()
+            .unwrap();
+        assert_eq!(imported.title, "Print flyers");
+        assert_eq!(
+            imported.description.map(|d| d.body),
+            Some("Two hundred, on green paper".to_owned())
+        );
+        assert_eq!(imported.due_date, NaiveDate::from_ymd_opt(2024, 5, 3));
+        assert_eq!(imported.priority, Priority::SecondHighest);
+        assert_eq!(imported.progress_percent, Some(40));
+        assert_eq!(imported.categories, ["print", "marketing"]);
+        assert_eq!(imported.external_uid, Some("flyers@example.org".to_owned()));
+        let attendees = todo.multi_properties().get("ATTENDEE").unwrap();
+        assert_eq!(attendees[0].value(), "matrix:u/test:example.org");
+    }
+}
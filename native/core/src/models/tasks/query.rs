@@ -0,0 +1,275 @@
+use chrono::{DateTime, Utc};
+use derive_builder::Builder;
+use matrix_sdk_base::ruma::{OwnedRoomId, OwnedUserId};
+use std::{cmp::Ordering, ops::RangeInclusive};
+
+use super::Task;
+use crate::events::tasks::Priority;
+
+/// What to order the results of a [`TaskQuery`] by
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TaskSortKey {
+    /// Earliest due first, tasks without a due date come last
+    DueDate,
+    /// Highest priority first, tasks without a priority come last
+    Priority,
+    /// As ordered within their list
+    SortOrder,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TaskSort {
+    pub key: TaskSortKey,
+    pub descending: bool,
+}
+
+impl TaskSort {
+    fn compare(&self, a: &Task, b: &Task) -> Ordering {
+        match self.key {
+            TaskSortKey::DueDate => compare_missing_last(a.due_at(), b.due_at(), self.descending),
+            TaskSortKey::Priority => {
+                compare_missing_last(priority_rank(a), priority_rank(b), self.descending)
+            }
+            TaskSortKey::SortOrder => {
+                compare_missing_last(Some(a.sort_order), Some(b.sort_order), self.descending)
+            }
+        }
+    }
+}
+
+/// A query over the tasks of all spaces
+///
+/// Filters that aren’t set match every task, multiple keywords, categories or
+/// spaces match a task having any of them. Tasks in the trash are left out
+/// unless asked for.
+#[derive(Clone, Debug, Default, Builder)]
+#[builder(name = "TaskQueryBuilder", default, derive(Debug))]
+pub struct TaskQuery {
+    /// Only tasks assigned to this user
+    #[builder(setter(into))]
+    pub assignee: Option<OwnedUserId>,
+
+    /// Only tasks due before this instant
+    #[builder(setter(into))]
+    pub due_before: Option<DateTime<Utc>>,
+
+    /// Only tasks due after this instant
+    #[builder(setter(into))]
+    pub due_after: Option<DateTime<Utc>>,
+
+    /// Only tasks with a priority in this range, 1 being the highest
+    #[builder(setter(into))]
+    pub priorities: Option<RangeInclusive<u8>>,
+
+    pub keywords: Vec<String>,
+
+    pub categories: Vec<String>,
+
+    /// Only tasks in these spaces
+    pub spaces: Vec<OwnedRoomId>,
+
+    /// Only tasks that are done or not done
+    #[builder(setter(into))]
+    pub done: Option<bool>,
+
+    pub include_trashed: bool,
+
+    /// Sort by these keys, later ones break ties of the earlier ones
+    pub sort: Vec<TaskSort>,
+}
+
+impl TaskQueryBuilder {
+    pub fn keyword(&mut self, keyword: String) -> &mut Self {
+        self.keywords.get_or_insert_with(Vec::new).push(keyword);
+        self
+    }
+
+    pub fn category(&mut self, category: String) -> &mut Self {
+        self.categories.get_or_insert_with(Vec::new).push(category);
+        self
+    }
+
+    pub fn space(&mut self, room_id: OwnedRoomId) -> &mut Self {
+        self.spaces.get_or_insert_with(Vec::new).push(room_id);
+        self
+    }
+
+    pub fn sort_by(&mut self, key: TaskSortKey, descending: bool) -> &mut Self {
+        self.sort
+            .get_or_insert_with(Vec::new)
+            .push(TaskSort { key, descending });
+        self
+    }
+}
+
+impl TaskQuery {
+    pub fn matches(&self, task: &Task) -> bool {
+        if !self.include_trashed && task.is_trashed() {
+            return false;
+        }
+        if let Some(assignee) = &self.assignee {
+            if !task.is_assigned(assignee) {
+                return false;
+            }
+        }
+        if let Some(done) = self.done {
+            if task.is_done() != done {
+                return false;
+            }
+        }
+        if !self.spaces.is_empty() && !self.spaces.iter().any(|s| s == task.room_id()) {
+            return false;
+        }
+        if !self.keywords.is_empty() && !task.keywords.iter().any(|k| self.keywords.contains(k)) {
+            return false;
+        }
+        if !self.categories.is_empty()
+            && !task.categories.iter().any(|c| self.categories.contains(c))
+        {
+            return false;
+        }
+        if let Some(priorities) = &self.priorities {
+            if !priority_rank(task)
+                .map(|p| priorities.contains(&p))
+                .unwrap_or_default()
+            {
+                return false;
+            }
+        }
+        if self.due_before.is_some() || self.due_after.is_some() {
+            let Some(due) = task.due_at() else {
+                return false;
+            };
+            if self.due_before.map(|b| due >= b).unwrap_or_default() {
+                return false;
+            }
+            if self.due_after.map(|a| due <= a).unwrap_or_default() {
+                return false;
+            }
+        }
+        true
+    }
+
+    pub fn sort(&self, tasks: &mut [Task]) {
+        tasks.sort_by(|a, b| {
+            self.sort
+                .iter()
+                .map(|s| s.compare(a, b))
+                .find(|o| o.is_ne())
+                .unwrap_or(Ordering::Equal)
+        });
+    }
+
+    /// The matching tasks, sorted
+    pub fn run(&self, tasks: impl IntoIterator<Item = Task>) -> Vec<Task> {
+        let mut tasks: Vec<Task> = tasks.into_iter().filter(|t| self.matches(t)).collect();
+        self.sort(&mut tasks);
+        tasks
+    }
+}
+
+fn priority_rank(task: &Task) -> Option<u8> {
+    match task.priority {
+        Priority::Undefined => None,
+        ref priority => Some(priority.clone() as u8),
+    }
+}
+
+/// Compares the values, missing ones come last either way
+fn compare_missing_last<T: Ord>(a: Option<T>, b: Option<T>, descending: bool) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) if descending => b.cmp(&a),
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::TestModelBuilder;
+    use matrix_sdk_base::ruma::user_id;
+
+    fn task(title: &str, content: serde_json::Value) -> Task {
+        let mut inner = serde_json::json!({
+            "title": title,
+            "m.relates_to": {
+                "rel_type": "global.acter.dev.belongs_to",
+                "event_id": "$tasklist",
+            },
+        });
+        inner
+            .as_object_mut()
+            .unwrap()
+            .extend(content.as_object().unwrap().clone());
+        serde_json::from_value(serde_json::json!({
+            "inner": inner,
+            "meta": TestModelBuilder::fake_meta(),
+            "assignees": ["@test:example.org"],
+        }))
+        .unwrap()
+    }
+
+    fn titles(tasks: &[Task]) -> Vec<&str> {
+        tasks.iter().map(|t| t.title().as_str()).collect()
+    }
+
+    #[test]
+    fn filters_and_sorts() {
+        let tasks = vec![
+            task(
+                "Book venue",
+                serde_json::json!({ "due_date": "2024-05-10", "priority": 2 }),
+            ),
+            task(
+                "Print flyers",
+                serde_json::json!({ "due_date": "2024-05-03", "keywords": ["print"] }),
+            ),
+            task(
+                "Order snacks",
+                serde_json::json!({ "priority": 1, "progress_percent": 100 }),
+            ),
+            task(
+                "Send invites",
+                serde_json::json!({ "due_date": "2024-05-05", "priority": 5 }),
+            ),
+        ];
+
+        let by_due = TaskQueryBuilder::default()
+            .done(false)
+            .sort_by(TaskSortKey::DueDate, false)
+            .build()
+            .unwrap();
+        assert_eq!(
+            titles(&by_due.run(tasks.clone())),
+            ["Print flyers", "Send invites", "Book venue"]
+        );
+
+        let urgent = TaskQueryBuilder::default()
+            .priorities(1u8..=2)
+            .sort_by(TaskSortKey::Priority, true)
+            .build()
+            .unwrap();
+        assert_eq!(
+            titles(&urgent.run(tasks.clone())),
+            ["Book venue", "Order snacks"]
+        );
+
+        let due_before = "2024-05-06T00:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let soon = TaskQueryBuilder::default()
+            .assignee(user_id!("@test:example.org").to_owned())
+            .due_before(due_before)
+            .keyword("print".to_owned())
+            .build()
+            .unwrap();
+        assert_eq!(titles(&soon.run(tasks.clone())), ["Print flyers"]);
+
+        let someone_else = TaskQueryBuilder::default()
+            .assignee(user_id!("@someone:example.org").to_owned())
+            .build()
+            .unwrap();
+        assert!(someone_else.run(tasks).is_empty());
+    }
+}
@@ -1,17 +1,34 @@
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use chrono_tz::Tz;
 use matrix_sdk_base::ruma::{
-    events::OriginalMessageLikeEvent, EventId, OwnedUserId, RoomId, UserId,
+    events::OriginalMessageLikeEvent, EventId, MilliSecondsSinceUnixEpoch, OwnedEventId,
+    OwnedUserId, RoomId, UserId,
 };
 use serde::{Deserialize, Serialize};
-use std::ops::Deref;
+use std::{
+    collections::{BTreeMap, HashSet},
+    ops::Deref,
+};
+use tracing::{trace, warn};
+
+/// How long before it is due a task is listed as due soon
+pub const DUE_SOON_WINDOW: Duration = Duration::hours(24);
 
 use super::{
     super::{default_model_execute, ActerModel, AnyActerModel, Capability, EventMeta, Store},
+    burndown::{record_done_change, DoneHistory},
+    history::{history_key_for, TaskFieldChange, TaskHistoryEntry, TaskHistoryKind},
+    time_log::time_log_key_for,
     KEYS,
 };
 use crate::{
-    events::tasks::{
-        TaskEventContent, TaskSelfAssignEventContent, TaskSelfUnassignEventContent,
-        TaskUpdateBuilder, TaskUpdateEventContent,
+    events::{
+        tasks::{
+            ChecklistItem, TaskAssignmentEventContent, TaskBuilder, TaskEventContent,
+            TaskSelfAssignEventContent, TaskSelfUnassignEventContent, TaskUpdateBuilder,
+            TaskUpdateEventContent,
+        },
+        Date,
     },
     Result,
 };
@@ -23,6 +40,40 @@ pub struct Task {
 
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     assignees: Vec<OwnedUserId>,
+
+    /// the subtasks of this task and whether they are done
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    subtasks: BTreeMap<OwnedEventId, bool>,
+
+    /// the tasks blocking this task and whether they are done
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    blockers: BTreeMap<OwnedEventId, bool>,
+
+    /// the tasks that are blocked by this task
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    blocking: Vec<OwnedEventId>,
+
+    /// when this task has been moved to the trash
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    trashed_at: Option<MilliSecondsSinceUnixEpoch>,
+
+    /// the time zone of the list this task is in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    list_time_zone: Option<Tz>,
+
+    /// the time logged on this task, by the id of the log
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    time_logs: BTreeMap<OwnedEventId, LoggedTime>,
+
+    /// when this task has been marked done or undone, ordered by time
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    done_changes: Vec<(MilliSecondsSinceUnixEpoch, bool)>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct LoggedTime {
+    user_id: OwnedUserId,
+    seconds: u32,
 }
 
 impl Deref for Task {
@@ -51,7 +102,7 @@ impl Task {
 
     pub fn is_done(&self) -> bool {
         self.inner
-            .progress_percent
+            .effective_progress()
             .map(|u| u >= 100)
             .unwrap_or_default()
     }
@@ -61,7 +112,7 @@ impl Task {
     }
 
     pub fn percent(&self) -> Option<u8> {
-        self.inner.progress_percent
+        self.inner.effective_progress()
     }
 
     pub fn due_date(&self) -> Option<String> {
@@ -74,6 +125,100 @@ impl Task {
         self.inner.utc_due_time_of_day
     }
 
+    /// The time zone the due date is given in, the one of the task or its list
+    pub fn resolved_time_zone(&self) -> Option<Tz> {
+        self.inner.time_zone.or(self.list_time_zone)
+    }
+
+    /// The instant this task is due, in its time zone
+    ///
+    /// Without a time of day, the task is due by the end of its due date.
+    pub fn due_at(&self) -> Option<DateTime<Tz>> {
+        let due_date = self.inner.due_date?;
+        let tz = self.resolved_time_zone().unwrap_or(Tz::UTC);
+        let local = match (self.inner.due_time_of_day, self.inner.utc_due_time_of_day) {
+            (Some(seconds), _) => {
+                due_date.and_hms_opt(0, 0, 0)? + Duration::seconds(seconds.into())
+            }
+            (None, Some(seconds)) => {
+                let utc = due_date.and_hms_opt(0, 0, 0)? + Duration::seconds(seconds.into());
+                return Some(tz.from_utc_datetime(&utc));
+            }
+            (None, None) => due_date.succ_opt()?.and_hms_opt(0, 0, 0)?,
+        };
+        // a time skipped by switching to daylight saving time is due an hour later
+        tz.from_local_datetime(&local).earliest().or_else(|| {
+            tz.from_local_datetime(&(local + Duration::hours(1)))
+                .earliest()
+        })
+    }
+
+    pub fn due_at_rfc3339(&self) -> Option<String> {
+        self.due_at()
+            .map(|d| d.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))
+    }
+
+    /// Whether the task is past its due time without being done at the given instant
+    pub fn is_overdue_at(&self, instant: &DateTime<Utc>) -> bool {
+        !self.is_done() && self.due_at().map(|due| due < *instant).unwrap_or_default()
+    }
+
+    /// Not done yet and due within the [`DUE_SOON_WINDOW`] after `instant`
+    pub fn is_due_soon_at(&self, instant: &DateTime<Utc>) -> bool {
+        !self.is_done()
+            && self
+                .due_at()
+                .map(|due| due >= *instant && due - DUE_SOON_WINDOW < *instant)
+                .unwrap_or_default()
+    }
+
+    /// The due indizes of this task for the user at `instant`
+    ///
+    /// Unlike the other indizes, these change as time passes. They are not part
+    /// of [`ActerModel::indizes`], which the store expects to stay the same for
+    /// the same model, but updated whenever the task changes and kept up to
+    /// date by the [`DueTasksScheduler`](crate::scheduler::DueTasksScheduler).
+    pub fn due_indizes_at(&self, user_id: &UserId, instant: &DateTime<Utc>) -> Vec<String> {
+        if !self.is_assigned(user_id) || self.is_trashed() {
+            return vec![];
+        }
+        if self.is_overdue_at(instant) {
+            vec![KEYS::MY_OVERDUE_TASKS.to_owned()]
+        } else if self.is_due_soon_at(instant) {
+            vec![KEYS::MY_DUE_SOON_TASKS.to_owned()]
+        } else {
+            vec![]
+        }
+    }
+
+    /// Move the task into or out of the due indizes as of `instant`
+    ///
+    /// Returns the indizes that changed.
+    pub(crate) fn sync_due_indizes(&self, store: &Store, instant: &DateTime<Utc>) -> Vec<String> {
+        let task_key = self.meta.event_id.to_string();
+        let indizes = self.due_indizes_at(store.user_id(), instant);
+        [KEYS::MY_OVERDUE_TASKS, KEYS::MY_DUE_SOON_TASKS]
+            .into_iter()
+            .filter(|key| {
+                let member = indizes.iter().any(|i| i == key);
+                store.set_index_membership(key, &task_key, member)
+            })
+            .map(ToOwned::to_owned)
+            .collect()
+    }
+
+    /// The next moment after `instant` at which the task becomes due soon or
+    /// overdue
+    pub fn next_due_change_after(&self, instant: &DateTime<Utc>) -> Option<DateTime<Utc>> {
+        if self.is_done() || self.is_trashed() {
+            return None;
+        }
+        let due = self.due_at()?.with_timezone(&Utc);
+        [due - DUE_SOON_WINDOW, due]
+            .into_iter()
+            .find(|change| change > instant)
+    }
+
     pub fn utc_start_rfc3339(&self) -> Option<String> {
         self.inner
             .utc_start
@@ -81,12 +226,181 @@ impl Task {
             .map(|d| d.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))
     }
 
+    pub fn parent_task_id(&self) -> Option<OwnedEventId> {
+        self.inner
+            .parent_task_id
+            .as_ref()
+            .map(|p| p.event_id.clone())
+    }
+
+    pub fn is_subtask(&self) -> bool {
+        self.inner.parent_task_id.is_some()
+    }
+
+    pub fn subtasks_key(&self) -> String {
+        Self::subtasks_key_for(&self.meta.event_id)
+    }
+
+    pub fn subtasks_key_for<T: AsRef<str>>(task_id: &T) -> String {
+        let r = task_id.as_ref();
+        format!("{r}::{}", KEYS::SUBTASKS)
+    }
+
+    /// The tasks listing the given one in their `blocked_by`
+    pub fn blocked_by_key_for<T: AsRef<str>>(task_id: &T) -> String {
+        let r = task_id.as_ref();
+        format!("{r}::{}", KEYS::BLOCKED_BY)
+    }
+
+    /// The key of the list of updates and (un)assignments made to this task
+    pub fn history_key(&self) -> String {
+        history_key_for(&self.meta.event_id)
+    }
+
+    /// When this task has been created and marked done or undone since
+    pub(super) fn done_history(&self) -> DoneHistory {
+        DoneHistory {
+            created_at: self.meta.origin_server_ts,
+            changes: self.done_changes.clone(),
+        }
+    }
+
+    /// The key of the list of time logged on this task
+    pub fn time_log_key(&self) -> String {
+        time_log_key_for(&self.meta.event_id)
+    }
+
+    /// How much time has been logged on this task in total, in seconds
+    pub fn time_spent_seconds(&self) -> u64 {
+        self.time_logs.values().map(|l| u64::from(l.seconds)).sum()
+    }
+
+    /// How much time each member logged on this task, in seconds
+    pub fn time_spent_per_user(&self) -> BTreeMap<OwnedUserId, u64> {
+        let mut per_user = BTreeMap::new();
+        for log in self.time_logs.values() {
+            *per_user.entry(log.user_id.clone()).or_default() += u64::from(log.seconds);
+        }
+        per_user
+    }
+
+    pub fn has_subtasks(&self) -> bool {
+        !self.subtasks.is_empty()
+    }
+
+    pub fn subtasks_count(&self) -> u32 {
+        self.subtasks.len() as u32
+    }
+
+    pub fn done_subtasks_count(&self) -> u32 {
+        self.subtasks.values().filter(|done| **done).count() as u32
+    }
+
+    /// How many of the subtasks are done in percent, None if there are no subtasks
+    pub fn subtasks_done_percent(&self) -> Option<u8> {
+        if self.subtasks.is_empty() {
+            return None;
+        }
+        let done = self.done_subtasks_count() * 100;
+        Some((done / self.subtasks_count()) as u8)
+    }
+
+    /// Whether any of the tasks this is blocked by isn’t done yet
+    pub fn is_blocked(&self) -> bool {
+        self.blockers.values().any(|done| !done)
+    }
+
+    /// The tasks that are blocked by this task
+    pub fn blocking(&self) -> Vec<OwnedEventId> {
+        self.blocking.clone()
+    }
+
+    pub fn is_recurring(&self) -> bool {
+        self.inner.recurrence_rule().is_some()
+    }
+
+    /// The due date of the next occurrence, if this is a recurring task
+    pub fn next_due_date(&self) -> Option<Date> {
+        let due_date = self.inner.due_date?;
+        self.inner.recurrence_rule()?.next_after(due_date)
+    }
+
+    /// Prepare the next occurrence of this recurring task
+    ///
+    /// Carries over everything but the progress, start and blockers, unchecks
+    /// the checklist and moves the due date forward as defined by the
+    /// recurrence rule. None if this isn’t a recurring task or the rule has no
+    /// further occurrence.
+    pub fn next_occurrence(&self) -> Option<TaskBuilder> {
+        let due_date = self.next_due_date()?;
+        let recurrence = self
+            .inner
+            .recurrence_rule()
+            .and_then(|r| r.remaining())
+            .map(|r| r.to_string());
+        let checklist = self
+            .inner
+            .checklist
+            .iter()
+            .map(|item| ChecklistItem::new(item.id.clone(), item.title.clone()))
+            .collect::<Vec<_>>();
+        let mut builder = TaskBuilder::default();
+        builder
+            .title(self.inner.title.clone())
+            .task_list_id(self.inner.task_list_id.clone())
+            .parent_task_id(self.inner.parent_task_id.clone())
+            .description(self.inner.description.clone())
+            .due_date(Some(due_date))
+            .utc_due_time_of_day(self.inner.utc_due_time_of_day)
+            .due_time_of_day(self.inner.due_time_of_day)
+            .time_zone(self.inner.time_zone)
+            .recurrence(recurrence)
+            .sort_order(self.inner.sort_order)
+            .priority(self.inner.priority.clone())
+            .display(self.inner.display.clone())
+            .keywords(self.inner.keywords.clone())
+            .categories(self.inner.categories.clone())
+            .checklist(checklist)
+            .progress_from_checklist(self.inner.progress_from_checklist);
+        Some(builder)
+    }
+
     pub fn updater(&self) -> TaskUpdateBuilder {
         TaskUpdateBuilder::default()
             .task(self.meta.event_id.clone())
             .to_owned()
     }
 
+    /// Whether this task has been moved to the trash
+    pub fn is_trashed(&self) -> bool {
+        self.inner.trashed_from.is_some()
+    }
+
+    /// When this task has been moved to the trash, as seen by the server
+    pub fn trashed_at(&self) -> Option<MilliSecondsSinceUnixEpoch> {
+        self.trashed_at
+    }
+
+    /// The update moving this task into the given trash list
+    ///
+    /// Remembers the list the task is in, so it can be restored there later.
+    pub fn trash_updater(&self, trash_list_id: OwnedEventId) -> TaskUpdateBuilder {
+        let mut updater = self.updater();
+        updater
+            .task_list_id(Some(trash_list_id.into()))
+            .trashed_from(Some(Some(self.inner.task_list_id.clone())));
+        updater
+    }
+
+    /// The update moving this task out of the trash into the given list
+    pub fn restore_updater(&self, task_list_id: OwnedEventId) -> TaskUpdateBuilder {
+        let mut updater = self.updater();
+        updater
+            .task_list_id(Some(task_list_id.into()))
+            .trashed_from(Some(None));
+        updater
+    }
+
     pub fn self_assign_event_content(&self) -> TaskSelfAssignEventContent {
         TaskSelfAssignEventContent {
             task: self.meta.event_id.clone().into(),
@@ -98,21 +412,37 @@ impl Task {
             task: self.meta.event_id.clone().into(),
         }
     }
+
+    pub fn assignment_event_content(
+        &self,
+        assignee: OwnedUserId,
+        assigned: bool,
+    ) -> TaskAssignmentEventContent {
+        TaskAssignmentEventContent {
+            task: self.meta.event_id.clone().into(),
+            assignee,
+            assigned,
+        }
+    }
 }
 
 impl ActerModel for Task {
     fn indizes(&self, user_id: &UserId) -> Vec<String> {
         let tasks_key = KEYS::TASKS;
         let task_list_id_idx = format!("{}::{tasks_key}", self.inner.task_list_id.event_id);
-        if self.is_assigned(user_id) {
+        let mut indizes = vec![task_list_id_idx, KEYS::ALL_TASKS.to_owned()];
+        if let Some(parent) = &self.inner.parent_task_id {
+            indizes.push(Self::subtasks_key_for(&parent.event_id));
+        }
+        indizes.extend(self.inner.blocked_by.iter().map(Self::blocked_by_key_for));
+        if self.is_assigned(user_id) && !self.is_trashed() {
             if self.is_done() {
-                return vec![KEYS::MY_DONE_TASKS.to_owned(), task_list_id_idx];
+                indizes.insert(0, KEYS::MY_DONE_TASKS.to_owned());
             } else {
-                return vec![KEYS::MY_OPEN_TASKS.to_owned(), task_list_id_idx];
+                indizes.insert(0, KEYS::MY_OPEN_TASKS.to_owned());
             }
         }
-        // not mine
-        vec![task_list_id_idx]
+        indizes
     }
 
     fn event_id(&self) -> &EventId {
@@ -130,24 +460,259 @@ impl ActerModel for Task {
         ]
     }
 
-    async fn execute(self, store: &Store) -> Result<Vec<String>> {
-        default_model_execute(store, self.into()).await
+    async fn execute(mut self, store: &Store) -> Result<Vec<String>> {
+        if let Ok(AnyActerModel::TaskList(task_list)) =
+            store.get(self.inner.task_list_id.event_id.as_str()).await
+        {
+            self.list_time_zone = task_list.time_zone;
+        }
+        let task_id = self.meta.event_id.clone();
+        let has_blockers = !self.inner.blocked_by.is_empty();
+        let mut keys = default_model_execute(store, self.into()).await?;
+        if has_blockers {
+            keys.extend(Task::sync_blockers(store, &task_id).await?);
+        }
+        // tasks blocked by us that arrived before we did
+        let waiting: Vec<OwnedEventId> = store
+            .get_list(&Task::blocked_by_key_for(&task_id))
+            .await?
+            .map(|model| model.event_id().to_owned())
+            .collect();
+        for waiting_id in waiting {
+            keys.extend(Task::sync_blockers(store, &waiting_id).await?);
+        }
+        keys.extend(Task::sync_due_indizes_now(store, &task_id).await);
+        Ok(Task::with_all_tasks(keys))
     }
 
     fn belongs_to(&self) -> Option<Vec<String>> {
-        Some(vec![self.inner.task_list_id.event_id.to_string()])
+        let mut parents = vec![self.inner.task_list_id.event_id.to_string()];
+        if let Some(parent) = &self.inner.parent_task_id {
+            parents.push(parent.event_id.to_string());
+        }
+        // the tasks we are blocking need to know when we are done
+        parents.extend(self.blocking.iter().map(ToString::to_string));
+        Some(parents)
     }
 
     fn transition(&mut self, model: &AnyActerModel) -> Result<bool> {
         match model {
-            AnyActerModel::TaskUpdate(update) => update.apply(&mut self.inner),
+            AnyActerModel::TaskUpdate(update) => {
+                let was_done = self.is_done();
+                let updated = update.apply(&mut self.inner)?;
+                if let Some(trashed_from) = &update.inner.trashed_from {
+                    self.trashed_at = trashed_from.as_ref().map(|_| update.meta.origin_server_ts);
+                }
+                let is_done = self.is_done();
+                if is_done != was_done {
+                    record_done_change(
+                        &mut self.done_changes,
+                        update.meta.origin_server_ts,
+                        is_done,
+                    );
+                }
+                Ok(updated)
+            }
             AnyActerModel::TaskSelfAssign(update) => update.apply(self),
             AnyActerModel::TaskSelfUnassign(update) => update.apply(self),
+            AnyActerModel::TaskAssignment(update) => update.apply(self),
+            AnyActerModel::TaskTimeLog(log) => {
+                let logged = LoggedTime {
+                    user_id: log.user_id().to_owned(),
+                    seconds: log.seconds,
+                };
+                self.time_logs.insert(log.event_id().to_owned(), logged);
+                Ok(true)
+            }
+            AnyActerModel::Task(other) => {
+                let subtask_changed = self.apply_subtask(other)?;
+                let blocker_changed = self.apply_blocker(other);
+                Ok(subtask_changed || blocker_changed)
+            }
+            AnyActerModel::RedactedActerModel(redacted) => {
+                // a subtask, blocking task or time log might have been removed
+                let subtask_removed = self.subtasks.remove(redacted.event_id()).is_some();
+                let blocker_removed = self.blockers.remove(redacted.event_id()).is_some();
+                let time_log_removed = self.time_logs.remove(redacted.event_id()).is_some();
+                Ok(subtask_removed || blocker_removed || time_log_removed)
+            }
             _ => Ok(false),
         }
     }
 }
 
+impl Task {
+    fn apply_subtask(&mut self, child: &Task) -> Result<bool> {
+        let Some(parent) = &child.inner.parent_task_id else {
+            return Ok(false);
+        };
+        if parent.event_id != self.meta.event_id {
+            return Ok(false);
+        }
+        let child_id = child.meta.event_id.clone();
+        let is_done = child.is_done();
+        trace!(?child_id, is_done, parent = ?self.meta.event_id, "subtask changed");
+        Ok(self.subtasks.insert(child_id, is_done) != Some(is_done))
+    }
+
+    fn apply_blocker(&mut self, blocker: &Task) -> bool {
+        let is_done = blocker.is_done();
+        match self.blockers.get_mut(blocker.event_id()) {
+            Some(done) if *done != is_done => {
+                trace!(blocker = ?blocker.meta.event_id, is_done, task = ?self.meta.event_id, "blocker changed");
+                *done = is_done;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Sync the dependency state between the task and the tasks it is blocked by
+    pub(crate) async fn sync_blockers(store: &Store, task_id: &EventId) -> Result<Vec<String>> {
+        let AnyActerModel::Task(mut task) = store.get(task_id.as_str()).await? else {
+            warn!(?task_id, "Not a task, can’t sync blockers");
+            return Ok(vec![]);
+        };
+        let mut models: Vec<AnyActerModel> = Vec::new();
+
+        // those no longer blocking us, need to forget about us
+        let removed: Vec<OwnedEventId> = task
+            .blockers
+            .keys()
+            .filter(|k| !task.inner.blocked_by.contains(k))
+            .cloned()
+            .collect();
+        for blocker_id in removed {
+            task.blockers.remove(&blocker_id);
+            if let Ok(AnyActerModel::Task(mut blocker)) = store.get(blocker_id.as_str()).await {
+                blocker.blocking.retain(|b| **b != *task_id);
+                models.push(blocker.into());
+            }
+        }
+
+        for blocker_id in task.inner.blocked_by.clone() {
+            let Ok(AnyActerModel::Task(mut blocker)) = store.get(blocker_id.as_str()).await else {
+                warn!(?task_id, ?blocker_id, "Blocking task not found");
+                task.blockers.insert(blocker_id, false);
+                continue;
+            };
+            task.blockers.insert(blocker_id, blocker.is_done());
+            if !blocker.blocking.iter().any(|b| **b == *task_id) {
+                blocker.blocking.push(task_id.to_owned());
+                models.push(blocker.into());
+            }
+        }
+
+        models.push(task.into());
+        store.save_many(models).await
+    }
+
+    /// Update the due indizes of a changed task right away
+    ///
+    /// Otherwise it would stay overdue or due soon until the next tick of the
+    /// [`DueTasksScheduler`](crate::scheduler::DueTasksScheduler).
+    async fn sync_due_indizes_now(store: &Store, task_id: &EventId) -> Vec<String> {
+        match store.get(task_id.as_str()).await {
+            Ok(AnyActerModel::Task(task)) => task.sync_due_indizes(store, &Utc::now()),
+            _ => vec![],
+        }
+    }
+
+    /// The keys to notify, including the list of all tasks
+    ///
+    /// A task changing in place stays in that list, so the store doesn’t
+    /// report it, but queries over all tasks need to run again.
+    fn with_all_tasks(mut keys: Vec<String>) -> Vec<String> {
+        let all_tasks = KEYS::ALL_TASKS.to_owned();
+        if !keys.contains(&all_tasks) {
+            keys.push(all_tasks);
+        }
+        keys
+    }
+
+    /// Inform the list the task was in before it has been moved away
+    ///
+    /// The task only belongs to its current list, so the previous one isn’t
+    /// reached through the regular transition tree.
+    async fn leave_task_list(
+        store: &Store,
+        task_id: &EventId,
+        list_id: &EventId,
+    ) -> Result<Vec<String>> {
+        let task = store.get(task_id.as_str()).await?;
+        let AnyActerModel::Task(inner) = &task else {
+            return Ok(vec![]);
+        };
+        if *inner.inner.task_list_id.event_id == *list_id {
+            // not moved after all
+            return Ok(vec![]);
+        }
+        let mut list = store.get(list_id.as_str()).await?;
+        if !list.transition(&task)? {
+            return Ok(vec![]);
+        }
+        store.save(list).await
+    }
+
+    /// Let the tasks of the list know about its time zone to resolve their due dates
+    pub(crate) async fn sync_list_time_zone(
+        store: &Store,
+        task_list_id: &EventId,
+    ) -> Result<Vec<String>> {
+        let Ok(AnyActerModel::TaskList(task_list)) = store.get(task_list_id.as_str()).await else {
+            return Ok(vec![]);
+        };
+        let time_zone = task_list.time_zone;
+        let tasks: Vec<Task> = store
+            .get_list(&task_list.tasks_key())
+            .await?
+            .filter_map(|mdl| match mdl {
+                AnyActerModel::Task(mut task) if task.list_time_zone != time_zone => {
+                    task.list_time_zone = time_zone;
+                    Some(task)
+                }
+                _ => None,
+            })
+            .collect();
+        if tasks.is_empty() {
+            return Ok(vec![]);
+        }
+        let now = Utc::now();
+        let due_keys: Vec<String> = tasks
+            .iter()
+            .flat_map(|task| task.sync_due_indizes(store, &now))
+            .collect();
+        let mut keys = store
+            .save_many(tasks.into_iter().map(Into::into).collect())
+            .await?;
+        keys.extend(due_keys);
+        Ok(Task::with_all_tasks(keys))
+    }
+
+    /// Whether letting `task_id` be blocked by `blocked_by` would create a cycle
+    /// of tasks waiting on each other
+    pub async fn creates_dependency_cycle(
+        store: &Store,
+        task_id: &EventId,
+        blocked_by: &[OwnedEventId],
+    ) -> bool {
+        let mut to_check = blocked_by.to_vec();
+        let mut seen = HashSet::new();
+        while let Some(current) = to_check.pop() {
+            if *current == *task_id {
+                return true;
+            }
+            if !seen.insert(current.clone()) {
+                continue;
+            }
+            if let Ok(AnyActerModel::Task(t)) = store.get(current.as_str()).await {
+                to_check.extend(t.inner.blocked_by.iter().cloned());
+            }
+        }
+        false
+    }
+}
+
 impl From<OriginalMessageLikeEvent<TaskEventContent>> for Task {
     fn from(outer: OriginalMessageLikeEvent<TaskEventContent>) -> Self {
         let OriginalMessageLikeEvent {
@@ -158,9 +723,20 @@ impl From<OriginalMessageLikeEvent<TaskEventContent>> for Task {
             origin_server_ts,
             ..
         } = outer;
+        let mut done_changes = vec![];
+        if content.effective_progress().is_some_and(|p| p >= 100) {
+            done_changes.push((origin_server_ts, true));
+        }
         Task {
             inner: content,
             assignees: Vec::with_capacity(0),
+            subtasks: Default::default(),
+            blockers: Default::default(),
+            blocking: Default::default(),
+            trashed_at: None,
+            list_time_zone: None,
+            time_logs: Default::default(),
+            done_changes,
             meta: EventMeta {
                 room_id,
                 event_id,
@@ -176,11 +752,21 @@ impl From<OriginalMessageLikeEvent<TaskEventContent>> for Task {
 pub struct TaskUpdate {
     inner: TaskUpdateEventContent,
     meta: EventMeta,
+
+    /// what this update changed on the task, as far as it was known then
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    changes: Vec<TaskFieldChange>,
+}
+
+impl TaskUpdate {
+    pub fn history_entry(&self) -> TaskHistoryEntry {
+        TaskHistoryEntry::new(&self.meta, TaskHistoryKind::Updated(self.changes.clone()))
+    }
 }
 
 impl ActerModel for TaskUpdate {
     fn indizes(&self, _user_id: &UserId) -> Vec<String> {
-        vec![format!("{:}::history", self.inner.task.event_id)]
+        vec![history_key_for(&self.inner.task.event_id)]
     }
 
     fn event_id(&self) -> &EventId {
@@ -190,8 +776,51 @@ impl ActerModel for TaskUpdate {
         &self.meta.room_id
     }
 
-    async fn execute(self, store: &Store) -> Result<Vec<String>> {
-        default_model_execute(store, self.into()).await
+    async fn execute(mut self, store: &Store) -> Result<Vec<String>> {
+        let task_id = self.inner.task.event_id.clone();
+        if let Ok(AnyActerModel::Task(task)) = store.get(task_id.as_str()).await {
+            if let Some(list) = &self.inner.task_list_id {
+                if let Ok(AnyActerModel::TaskList(task_list)) =
+                    store.get(list.event_id.as_str()).await
+                {
+                    if task_list.room_id() != task.room_id() {
+                        let list_id = &list.event_id;
+                        warn!(
+                            ?task_id,
+                            ?list_id,
+                            "Task list is in another space, not moving"
+                        );
+                        self.inner.task_list_id = None;
+                    }
+                }
+            }
+            let mut updated = task.inner.clone();
+            if self.inner.apply(&mut updated)? {
+                self.changes = TaskFieldChange::diff(&task.inner, &updated);
+            }
+        }
+        let changes_blockers = self.inner.blocked_by.is_some();
+        let new_list_id = self.inner.task_list_id.as_ref().map(|l| l.event_id.clone());
+        let previous_list_id = if self.inner.task_list_id.is_some() {
+            match store.get(task_id.as_str()).await? {
+                AnyActerModel::Task(task) => Some(task.inner.task_list_id.event_id),
+                _ => None,
+            }
+        } else {
+            None
+        };
+        let mut keys = default_model_execute(store, self.into()).await?;
+        if changes_blockers {
+            keys.extend(Task::sync_blockers(store, &task_id).await?);
+        }
+        if let Some(list_id) = previous_list_id {
+            keys.extend(Task::leave_task_list(store, &task_id, &list_id).await?);
+            if let Some(new_list) = &new_list_id {
+                keys.extend(Task::sync_list_time_zone(store, new_list).await?);
+            }
+        }
+        keys.extend(Task::sync_due_indizes_now(store, &task_id).await);
+        Ok(Task::with_all_tasks(keys))
     }
 
     fn belongs_to(&self) -> Option<Vec<String>> {
@@ -225,6 +854,7 @@ impl From<OriginalMessageLikeEvent<TaskUpdateEventContent>> for TaskUpdate {
                 origin_server_ts,
                 redacted: None,
             },
+            changes: Default::default(),
         }
     }
 }
@@ -244,11 +874,15 @@ impl TaskSelfAssign {
         task.assignees.insert(0, new_user_id);
         Ok(true)
     }
+
+    pub fn history_entry(&self) -> TaskHistoryEntry {
+        TaskHistoryEntry::new(&self.meta, TaskHistoryKind::SelfAssigned)
+    }
 }
 
 impl ActerModel for TaskSelfAssign {
     fn indizes(&self, _user_id: &UserId) -> Vec<String> {
-        vec![format!("{:}::history", self.inner.task.event_id)]
+        vec![history_key_for(&self.inner.task.event_id)]
     }
 
     fn event_id(&self) -> &EventId {
@@ -259,7 +893,10 @@ impl ActerModel for TaskSelfAssign {
     }
 
     async fn execute(self, store: &Store) -> Result<Vec<String>> {
-        default_model_execute(store, self.into()).await
+        let task_id = self.inner.task.event_id.clone();
+        let mut keys = default_model_execute(store, self.into()).await?;
+        keys.extend(Task::sync_due_indizes_now(store, &task_id).await);
+        Ok(Task::with_all_tasks(keys))
     }
 
     fn belongs_to(&self) -> Option<Vec<String>> {
@@ -303,11 +940,15 @@ impl TaskSelfUnassign {
         task.assignees.retain(|u| u != &new_user_id);
         Ok(true)
     }
+
+    pub fn history_entry(&self) -> TaskHistoryEntry {
+        TaskHistoryEntry::new(&self.meta, TaskHistoryKind::SelfUnassigned)
+    }
 }
 
 impl ActerModel for TaskSelfUnassign {
     fn indizes(&self, _user_id: &UserId) -> Vec<String> {
-        vec![format!("{:}::history", self.inner.task.event_id)]
+        vec![history_key_for(&self.inner.task.event_id)]
     }
 
     fn event_id(&self) -> &EventId {
@@ -318,7 +959,10 @@ impl ActerModel for TaskSelfUnassign {
     }
 
     async fn execute(self, store: &Store) -> Result<Vec<String>> {
-        default_model_execute(store, self.into()).await
+        let task_id = self.inner.task.event_id.clone();
+        let mut keys = default_model_execute(store, self.into()).await?;
+        keys.extend(Task::sync_due_indizes_now(store, &task_id).await);
+        Ok(Task::with_all_tasks(keys))
     }
 
     fn belongs_to(&self) -> Option<Vec<String>> {
@@ -348,3 +992,695 @@ impl From<OriginalMessageLikeEvent<TaskSelfUnassignEventContent>> for TaskSelfUn
         }
     }
 }
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TaskAssignment {
+    inner: TaskAssignmentEventContent,
+    meta: EventMeta,
+}
+
+impl TaskAssignment {
+    fn apply(&self, task: &mut Task) -> Result<bool> {
+        let assignee = &self.inner.assignee;
+        // remove any existing instance of the user in the list.
+        task.assignees.retain(|u| u != assignee);
+        if self.inner.assigned {
+            // add it at the new first entry;
+            task.assignees.insert(0, assignee.clone());
+        }
+        Ok(true)
+    }
+
+    pub fn history_entry(&self) -> TaskHistoryEntry {
+        let assignee = self.inner.assignee.clone();
+        let kind = if self.inner.assigned {
+            TaskHistoryKind::Assigned(assignee)
+        } else {
+            TaskHistoryKind::Unassigned(assignee)
+        };
+        TaskHistoryEntry::new(&self.meta, kind)
+    }
+}
+
+impl Deref for TaskAssignment {
+    type Target = TaskAssignmentEventContent;
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl ActerModel for TaskAssignment {
+    fn indizes(&self, _user_id: &UserId) -> Vec<String> {
+        vec![history_key_for(&self.inner.task.event_id)]
+    }
+
+    fn event_id(&self) -> &EventId {
+        &self.meta.event_id
+    }
+    fn room_id(&self) -> &RoomId {
+        &self.meta.room_id
+    }
+
+    async fn execute(self, store: &Store) -> Result<Vec<String>> {
+        let task_id = self.inner.task.event_id.clone();
+        let mut keys = default_model_execute(store, self.into()).await?;
+        keys.extend(Task::sync_due_indizes_now(store, &task_id).await);
+        Ok(Task::with_all_tasks(keys))
+    }
+
+    fn belongs_to(&self) -> Option<Vec<String>> {
+        Some(vec![self.inner.task.event_id.to_string()])
+    }
+}
+
+impl From<OriginalMessageLikeEvent<TaskAssignmentEventContent>> for TaskAssignment {
+    fn from(outer: OriginalMessageLikeEvent<TaskAssignmentEventContent>) -> Self {
+        let OriginalMessageLikeEvent {
+            content,
+            room_id,
+            event_id,
+            sender,
+            origin_server_ts,
+            ..
+        } = outer;
+        TaskAssignment {
+            inner: content,
+            meta: EventMeta {
+                room_id,
+                event_id,
+                sender,
+                origin_server_ts,
+                redacted: None,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        executor::Executor,
+        models::{fresh_executor, TaskList, TaskTimeLog, TestModelBuilder},
+    };
+    use matrix_sdk_base::ruma::{events::UnsignedRoomRedactionEvent, user_id};
+
+    fn task(task_list_id: &EventId, title: &str, blocked_by: Vec<OwnedEventId>) -> Task {
+        let inner = TaskBuilder::default()
+            .title(title.to_owned())
+            .task_list_id(task_list_id.to_owned())
+            .blocked_by(blocked_by)
+            .build()
+            .unwrap();
+        Task {
+            inner,
+            meta: TestModelBuilder::fake_meta(),
+            assignees: Default::default(),
+            subtasks: Default::default(),
+            blockers: Default::default(),
+            blocking: Default::default(),
+            trashed_at: None,
+            list_time_zone: None,
+            time_logs: Default::default(),
+            done_changes: Default::default(),
+        }
+    }
+
+    fn task_list(name: &str) -> TaskList {
+        task_list_in(name, &TestModelBuilder::fake_meta().room_id)
+    }
+
+    fn task_list_in(name: &str, room_id: &RoomId) -> TaskList {
+        let mut meta = TestModelBuilder::fake_meta();
+        meta.room_id = room_id.to_owned();
+        serde_json::from_value(serde_json::json!({
+            "inner": { "name": name },
+            "meta": meta,
+            "task_stats": { "has_tasks": false, "tasks_count": 0 },
+        }))
+        .unwrap()
+    }
+
+    async fn get_task_list(executor: &Executor, task_list_id: &EventId) -> TaskList {
+        let AnyActerModel::TaskList(task_list) =
+            executor.store().get(task_list_id.as_str()).await.unwrap()
+        else {
+            panic!("Not a task list");
+        };
+        task_list
+    }
+
+    async fn get_task(executor: &Executor, task_id: &EventId) -> Task {
+        let AnyActerModel::Task(task) = executor.store().get(task_id.as_str()).await.unwrap()
+        else {
+            panic!("Not a task");
+        };
+        task
+    }
+
+    #[tokio::test]
+    async fn blocked_until_blocker_is_done() -> Result<()> {
+        let _ = env_logger::try_init();
+        let executor = fresh_executor().await?;
+        let task_list = TestModelBuilder::default().simple().build().unwrap();
+        let task_list_id = task_list.event_id().to_owned();
+        executor.handle(task_list.into()).await?;
+
+        let blocker = task(&task_list_id, "Book the room", vec![]);
+        let blocker_id = blocker.event_id().to_owned();
+        executor.handle(blocker.into()).await?;
+
+        let blocked = task(&task_list_id, "Send invites", vec![blocker_id.clone()]);
+        let blocked_id = blocked.event_id().to_owned();
+        executor.handle(blocked.into()).await?;
+
+        assert!(get_task(&executor, &blocked_id).await.is_blocked());
+        assert_eq!(
+            get_task(&executor, &blocker_id).await.blocking(),
+            vec![blocked_id.clone()]
+        );
+
+        let update = TaskUpdate {
+            inner: TaskUpdateBuilder::default()
+                .task(blocker_id.clone())
+                .progress_percent(Some(Some(100)))
+                .build()
+                .unwrap(),
+            meta: TestModelBuilder::fake_meta(),
+            changes: vec![],
+        };
+        executor.handle(update.into()).await?;
+
+        let blocker = get_task(&executor, &blocker_id).await;
+        assert!(blocker.is_done());
+        assert_eq!(blocker.done_history().changes.len(), 1);
+        assert!(!get_task(&executor, &blocked_id).await.is_blocked());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn blocker_arriving_late() -> Result<()> {
+        let _ = env_logger::try_init();
+        let executor = fresh_executor().await?;
+        let task_list = TestModelBuilder::default().simple().build().unwrap();
+        let task_list_id = task_list.event_id().to_owned();
+        executor.handle(task_list.into()).await?;
+
+        let blocker = task(&task_list_id, "Book the room", vec![]);
+        let blocker_id = blocker.event_id().to_owned();
+        let blocked = task(&task_list_id, "Send invites", vec![blocker_id.clone()]);
+        let blocked_id = blocked.event_id().to_owned();
+        executor.handle(blocked.into()).await?;
+        executor.handle(blocker.into()).await?;
+
+        assert!(get_task(&executor, &blocked_id).await.is_blocked());
+        assert_eq!(
+            get_task(&executor, &blocker_id).await.blocking(),
+            vec![blocked_id.clone()]
+        );
+
+        let update = TaskUpdate {
+            inner: TaskUpdateBuilder::default()
+                .task(blocker_id.clone())
+                .progress_percent(Some(Some(100)))
+                .build()
+                .unwrap(),
+            meta: TestModelBuilder::fake_meta(),
+            changes: vec![],
+        };
+        executor.handle(update.into()).await?;
+        assert!(!get_task(&executor, &blocked_id).await.is_blocked());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn detects_dependency_cycles() -> Result<()> {
+        let _ = env_logger::try_init();
+        let executor = fresh_executor().await?;
+        let task_list = TestModelBuilder::default().simple().build().unwrap();
+        let task_list_id = task_list.event_id().to_owned();
+        executor.handle(task_list.into()).await?;
+
+        let first = task(&task_list_id, "first", vec![]);
+        let first_id = first.event_id().to_owned();
+        executor.handle(first.into()).await?;
+
+        let second = task(&task_list_id, "second", vec![first_id.clone()]);
+        let second_id = second.event_id().to_owned();
+        executor.handle(second.into()).await?;
+
+        let third = task(&task_list_id, "third", vec![second_id.clone()]);
+        let third_id = third.event_id().to_owned();
+        executor.handle(third.into()).await?;
+
+        let store = executor.store();
+        assert!(Task::creates_dependency_cycle(store, &first_id, &[third_id.clone()]).await);
+        assert!(Task::creates_dependency_cycle(store, &first_id, &[first_id.clone()]).await);
+        assert!(!Task::creates_dependency_cycle(store, &third_id, &[first_id.clone()]).await);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn moving_task_updates_both_lists() -> Result<()> {
+        let _ = env_logger::try_init();
+        let executor = fresh_executor().await?;
+        let old_list = task_list("Inbox");
+        let old_list_id = old_list.event_id().to_owned();
+        let room_id = old_list.room_id().to_owned();
+        executor.handle(old_list.into()).await?;
+        let new_list = task_list_in("Chores", &room_id);
+        let new_list_id = new_list.event_id().to_owned();
+        executor.handle(new_list.into()).await?;
+
+        let mut moving = task(&old_list_id, "Water the plants", vec![]);
+        moving.meta.room_id = room_id;
+        let moving_id = moving.event_id().to_owned();
+        executor.handle(moving.into()).await?;
+        let staying = task(&old_list_id, "Clean up", vec![]);
+        executor.handle(staying.into()).await?;
+        let old_list = get_task_list(&executor, &old_list_id).await;
+        assert_eq!(*old_list.stats().tasks_count(), 2);
+
+        let update = TaskUpdate {
+            inner: TaskUpdateBuilder::default()
+                .task(moving_id.clone())
+                .task_list_id(Some(new_list_id.clone().into()))
+                .build()
+                .unwrap(),
+            meta: TestModelBuilder::fake_meta(),
+            changes: vec![],
+        };
+        executor.handle(update.into()).await?;
+
+        let store = executor.store();
+        let history = TaskHistoryEntry::for_task(store, &moving_id).await?;
+        assert_eq!(history.len(), 1);
+        let changes = history[0].changes();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].field(), "task_list_id");
+        assert_eq!(changes[0].old_value(), Some(old_list_id.to_string()));
+        assert_eq!(changes[0].new_value(), Some(new_list_id.to_string()));
+
+        let old_list = get_task_list(&executor, &old_list_id).await;
+        assert_eq!(*old_list.stats().tasks_count(), 1);
+        assert_eq!(store.get_list(&old_list.tasks_key()).await?.count(), 1);
+
+        let new_list = get_task_list(&executor, &new_list_id).await;
+        assert_eq!(*new_list.stats().tasks_count(), 1);
+        let mut moved = store.get_list(&new_list.tasks_key()).await?;
+        assert_eq!(
+            moved.next().map(|t| t.event_id().to_owned()),
+            Some(moving_id)
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn stays_in_its_space() -> Result<()> {
+        let _ = env_logger::try_init();
+        let executor = fresh_executor().await?;
+        let list = task_list("Inbox");
+        let list_id = list.event_id().to_owned();
+        let room_id = list.room_id().to_owned();
+        executor.handle(list.into()).await?;
+        let elsewhere = task_list("Their chores");
+        let elsewhere_id = elsewhere.event_id().to_owned();
+        executor.handle(elsewhere.into()).await?;
+
+        let mut moving = task(&list_id, "Water the plants", vec![]);
+        moving.meta.room_id = room_id;
+        let moving_id = moving.event_id().to_owned();
+        executor.handle(moving.into()).await?;
+
+        let update = TaskUpdate {
+            inner: TaskUpdateBuilder::default()
+                .task(moving_id.clone())
+                .title(Some("Water all plants".to_owned()))
+                .task_list_id(Some(elsewhere_id.clone().into()))
+                .build()
+                .unwrap(),
+            meta: TestModelBuilder::fake_meta(),
+            changes: vec![],
+        };
+        executor.handle(update.into()).await?;
+
+        let moving = get_task(&executor, &moving_id).await;
+        assert_eq!(moving.title, "Water all plants");
+        assert_eq!(moving.task_list_id.event_id, list_id);
+        let elsewhere = get_task_list(&executor, &elsewhere_id).await;
+        assert_eq!(*elsewhere.stats().tasks_count(), 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn trashing_and_restoring() -> Result<()> {
+        let _ = env_logger::try_init();
+        let executor = fresh_executor().await?;
+        let chores = task_list("Chores");
+        let chores_id = chores.event_id().to_owned();
+        executor.handle(chores.into()).await?;
+        let trash = task_list("Trash");
+        let trash_id = trash.event_id().to_owned();
+        executor.handle(trash.into()).await?;
+
+        let chore = task(&chores_id, "Take out the bins", vec![]);
+        let chore_id = chore.event_id().to_owned();
+        executor.handle(chore.clone().into()).await?;
+
+        let meta = TestModelBuilder::fake_meta();
+        let trashed_at = meta.origin_server_ts;
+        let update = TaskUpdate {
+            inner: chore.trash_updater(trash_id.clone()).build().unwrap(),
+            meta,
+            changes: vec![],
+        };
+        executor.handle(update.into()).await?;
+
+        let trashed = get_task(&executor, &chore_id).await;
+        assert!(trashed.is_trashed());
+        assert_eq!(trashed.trashed_at(), Some(trashed_at));
+        assert_eq!(
+            *get_task_list(&executor, &chores_id)
+                .await
+                .stats()
+                .tasks_count(),
+            0
+        );
+        assert_eq!(
+            *get_task_list(&executor, &trash_id)
+                .await
+                .stats()
+                .tasks_count(),
+            1
+        );
+
+        let update = TaskUpdate {
+            inner: trashed.restore_updater(chores_id.clone()).build().unwrap(),
+            meta: TestModelBuilder::fake_meta(),
+            changes: vec![],
+        };
+        executor.handle(update.into()).await?;
+
+        let restored = get_task(&executor, &chore_id).await;
+        assert!(!restored.is_trashed());
+        assert_eq!(restored.trashed_at(), None);
+        assert_eq!(
+            *get_task_list(&executor, &chores_id)
+                .await
+                .stats()
+                .tasks_count(),
+            1
+        );
+        assert_eq!(
+            *get_task_list(&executor, &trash_id)
+                .await
+                .stats()
+                .tasks_count(),
+            0
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn due_in_time_zone_of_list() -> Result<()> {
+        let _ = env_logger::try_init();
+        let executor = fresh_executor().await?;
+        let task_list: TaskList = serde_json::from_value(serde_json::json!({
+            "inner": { "name": "Berlin office", "time_zone": "Europe/Berlin" },
+            "meta": TestModelBuilder::fake_meta(),
+            "task_stats": { "has_tasks": false, "tasks_count": 0 },
+        }))?;
+        let task_list_id = task_list.event_id().to_owned();
+        executor.handle(task_list.into()).await?;
+
+        let mut report = task(&task_list_id, "Send the report", vec![]);
+        report.inner.due_date = Date::from_ymd_opt(2024, 5, 3);
+        report.inner.due_time_of_day = Some(18 * 60 * 60);
+        let report_id = report.event_id().to_owned();
+        executor.handle(report.into()).await?;
+
+        let report = get_task(&executor, &report_id).await;
+        assert_eq!(report.resolved_time_zone(), Some(Tz::Europe__Berlin));
+        assert_eq!(
+            report.due_at_rfc3339().as_deref(),
+            Some("2024-05-03T18:00:00+02:00")
+        );
+        let before = "2024-05-03T15:59:00Z".parse::<DateTime<Utc>>().unwrap();
+        let after = "2024-05-03T16:01:00Z".parse::<DateTime<Utc>>().unwrap();
+        assert!(!report.is_overdue_at(&before));
+        assert!(report.is_overdue_at(&after));
+
+        // the time zone of the task wins over the one of its list
+        let mut call = task(&task_list_id, "Call New York", vec![]);
+        call.inner.due_date = Date::from_ymd_opt(2024, 5, 3);
+        call.inner.time_zone = Some(Tz::America__New_York);
+        let call_id = call.event_id().to_owned();
+        executor.handle(call.into()).await?;
+
+        let call = get_task(&executor, &call_id).await;
+        // without a time of day, it is due by the end of the day
+        assert_eq!(
+            call.due_at_rfc3339().as_deref(),
+            Some("2024-05-04T00:00:00-04:00")
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn assigned_by_someone_else() -> Result<()> {
+        let _ = env_logger::try_init();
+        let executor = fresh_executor().await?;
+        let task_list = TestModelBuilder::default().simple().build().unwrap();
+        let task_list_id = task_list.event_id().to_owned();
+        executor.handle(task_list.into()).await?;
+
+        let volunteer_task = task(&task_list_id, "Hang up posters", vec![]);
+        let task_id = volunteer_task.event_id().to_owned();
+        let assign = |assigned| {
+            let mut meta = TestModelBuilder::fake_meta();
+            meta.sender = user_id!("@coordinator:example.org").to_owned();
+            TaskAssignment {
+                inner: volunteer_task
+                    .assignment_event_content(user_id!("@test:example.org").to_owned(), assigned),
+                meta,
+            }
+        };
+        let assignment = assign(true);
+        let unassignment = assign(false);
+        executor.handle(volunteer_task.into()).await?;
+
+        let store = executor.store();
+        assert_eq!(store.get_list(KEYS::MY_OPEN_TASKS).await?.count(), 0);
+
+        executor.handle(assignment.into()).await?;
+        let mut my_tasks = store.get_list(KEYS::MY_OPEN_TASKS).await?;
+        assert_eq!(
+            my_tasks.next().map(|t| t.event_id().to_owned()),
+            Some(task_id.clone())
+        );
+
+        executor.handle(unassignment.into()).await?;
+        assert_eq!(store.get_list(KEYS::MY_OPEN_TASKS).await?.count(), 0);
+        assert!(get_task(&executor, &task_id).await.assignees().is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn counts_tasks_per_state() -> Result<()> {
+        let _ = env_logger::try_init();
+        let executor = fresh_executor().await?;
+        let board: TaskList = serde_json::from_value(serde_json::json!({
+            "inner": {
+                "name": "Board",
+                // other clients might repeat a key
+                "states": [
+                    { "key": "doing", "name": "Doing" },
+                    { "key": "done", "name": "Done" },
+                    { "key": "doing", "name": "Doing again" },
+                ],
+            },
+            "meta": TestModelBuilder::fake_meta(),
+            "task_stats": { "has_tasks": false, "tasks_count": 0 },
+        }))
+        .unwrap();
+        let board_id = board.event_id().to_owned();
+        executor.handle(board.into()).await?;
+
+        let mut task_ids = vec![];
+        for state in ["doing", "doing", "review"] {
+            let mut card = task(&board_id, state, vec![]);
+            card.inner.state = Some(state.to_owned());
+            task_ids.push(card.event_id().to_owned());
+            executor.handle(card.into()).await?;
+        }
+
+        let board = get_task_list(&executor, &board_id).await;
+        assert_eq!(board.tasks_count_in_state(Some("doing")), 2);
+        assert_eq!(board.tasks_count_in_state(Some("done")), 0);
+        // the unknown state counts as none
+        assert_eq!(board.tasks_count_in_state(None), 1);
+
+        let update = TaskUpdate {
+            inner: TaskUpdateBuilder::default()
+                .task(task_ids[0].clone())
+                .state(Some(Some("done".to_owned())))
+                .build()
+                .unwrap(),
+            meta: TestModelBuilder::fake_meta(),
+            changes: vec![],
+        };
+        executor.handle(update.into()).await?;
+
+        let board = get_task_list(&executor, &board_id).await;
+        assert_eq!(board.tasks_count_in_state(Some("doing")), 1);
+        assert_eq!(board.tasks_count_in_state(Some("done")), 1);
+        assert_eq!(*board.stats().tasks_count(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn next_occurrence_moves_due_date() {
+        let task_list_id = TestModelBuilder::fake_meta().event_id;
+        let mut weekly = task(&task_list_id, "Newsletter", vec![]);
+        assert!(weekly.next_occurrence().is_none());
+
+        weekly.inner.due_date = Date::from_ymd_opt(2024, 5, 3);
+        weekly.inner.recurrence = Some("FREQ=WEEKLY;COUNT=2".to_owned());
+        weekly.inner.progress_percent = Some(100);
+
+        let next = weekly.next_occurrence().unwrap().build().unwrap();
+        assert_eq!(next.title, "Newsletter");
+        assert_eq!(next.due_date, Date::from_ymd_opt(2024, 5, 10));
+        assert_eq!(next.progress_percent, None);
+        assert_eq!(next.recurrence.as_deref(), Some("FREQ=WEEKLY;COUNT=1"));
+    }
+
+    #[test]
+    fn keeps_tasks_with_unsupported_rules() {
+        let task: Task = serde_json::from_value(serde_json::json!({
+            "inner": {
+                "title": "Pay the rent",
+                "due_date": "2024-05-15",
+                "recurrence": "FREQ=MONTHLY;BYMONTHDAY=15,-1",
+                "m.relates_to": {
+                    "rel_type": "global.acter.dev.belongs_to",
+                    "event_id": "$tasklist",
+                },
+            },
+            "meta": TestModelBuilder::fake_meta(),
+        }))
+        .unwrap();
+        assert!(!task.is_recurring());
+        assert!(task.next_occurrence().is_none());
+    }
+
+    fn time_log(task_id: &EventId, meta: &EventMeta, seconds: u32) -> TaskTimeLog {
+        serde_json::from_value(serde_json::json!({
+            "inner": {
+                "m.relates_to": {
+                    "rel_type": "global.acter.dev.belongs_to",
+                    "event_id": task_id,
+                },
+                "seconds": seconds,
+            },
+            "meta": meta,
+        }))
+        .unwrap()
+    }
+
+    fn meta_from(sender: &UserId) -> EventMeta {
+        let mut meta = TestModelBuilder::fake_meta();
+        meta.sender = sender.to_owned();
+        meta
+    }
+
+    #[tokio::test]
+    async fn time_logs_add_up() -> Result<()> {
+        let _ = env_logger::try_init();
+        let executor = fresh_executor().await?;
+        let chores = task_list("Chores");
+        let chores_id = chores.event_id().to_owned();
+        executor.handle(chores.into()).await?;
+
+        let mut plants = task(&chores_id, "Water the plants", vec![]);
+        plants.inner.estimate_seconds = Some(3600);
+        let plants_id = plants.event_id().to_owned();
+        executor.handle(plants.into()).await?;
+        let bins = task(&chores_id, "Take out the bins", vec![]);
+        let bins_id = bins.event_id().to_owned();
+        executor.handle(bins.into()).await?;
+
+        let alice = user_id!("@alice:example.org");
+        let bob = user_id!("@bob:example.org");
+        let log = time_log(&plants_id, &meta_from(alice), 1200);
+        executor.handle(log.into()).await?;
+        let log = time_log(&plants_id, &meta_from(bob), 600);
+        executor.handle(log.into()).await?;
+        let removed_meta = meta_from(alice);
+        let log = time_log(&bins_id, &removed_meta, 300);
+        executor.handle(log.into()).await?;
+
+        let plants = get_task(&executor, &plants_id).await;
+        assert_eq!(plants.time_spent_seconds(), 1800);
+        assert_eq!(plants.time_spent_per_user()[alice], 1200);
+        let logs = TaskTimeLog::for_task(executor.store(), &plants_id).await?;
+        assert_eq!(logs.len(), 2);
+
+        let stats = get_task_list(&executor, &chores_id).await.stats().clone();
+        assert_eq!(stats.time_spent_seconds(), 2100);
+        assert_eq!(stats.time_spent_by(alice), 1500);
+        assert_eq!(stats.time_spent_by(bob), 600);
+        assert_eq!(stats.estimate_seconds(), 3600);
+
+        let redaction: UnsignedRoomRedactionEvent = serde_json::from_value(serde_json::json!({
+            "event_id": "$redaction",
+            "sender": alice,
+            "origin_server_ts": 123456,
+            "content": { "redacts": removed_meta.event_id },
+        }))?;
+        executor
+            .redact(
+                "global.acter.dev.task.time_log".to_owned(),
+                removed_meta,
+                redaction,
+            )
+            .await?;
+        let stats = get_task_list(&executor, &chores_id).await.stats().clone();
+        assert_eq!(stats.time_spent_by(alice), 1200);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn changes_reach_all_tasks() -> Result<()> {
+        let _ = env_logger::try_init();
+        let executor = fresh_executor().await?;
+        let chores = task_list("Chores");
+        let chores_id = chores.event_id().to_owned();
+        executor.handle(chores.into()).await?;
+        let chore = task(&chores_id, "Take out the bins", vec![]);
+        let chore_id = chore.event_id().to_owned();
+        executor.handle(chore.clone().into()).await?;
+
+        let all_tasks = executor.subscribe(KEYS::ALL_TASKS.to_owned());
+        let update = TaskUpdate {
+            inner: TaskUpdateBuilder::default()
+                .task(chore_id)
+                .progress_percent(Some(Some(100)))
+                .build()
+                .unwrap(),
+            meta: TestModelBuilder::fake_meta(),
+            changes: vec![],
+        };
+        executor.handle(update.into()).await?;
+        assert!(!all_tasks.is_empty(), "marking as done wasn’t notified");
+
+        let all_tasks = all_tasks.resubscribe();
+        let assign = TaskSelfAssign {
+            inner: chore.self_assign_event_content(),
+            meta: TestModelBuilder::fake_meta(),
+        };
+        executor.handle(assign.into()).await?;
+        assert!(!all_tasks.is_empty(), "self-assigning wasn’t notified");
+        Ok(())
+    }
+}
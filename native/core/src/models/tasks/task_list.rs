@@ -1,14 +1,25 @@
+use chrono_tz::Tz;
 use derive_getters::Getters;
-use matrix_sdk_base::ruma::{events::OriginalMessageLikeEvent, EventId, RoomId, UserId};
+use matrix_sdk_base::ruma::{
+    events::OriginalMessageLikeEvent, EventId, OwnedEventId, OwnedUserId, RoomId, UserId,
+};
 use serde::{Deserialize, Serialize};
-use std::ops::Deref;
+use std::{collections::BTreeMap, ops::Deref};
 use tracing::{trace, warn};
 
-use super::super::{
-    default_model_execute, ActerModel, AnyActerModel, Capability, EventMeta, Store,
+use super::{
+    super::{default_model_execute, ActerModel, AnyActerModel, Capability, EventMeta, Store},
+    burndown::{burndown, BurndownDay, DoneHistory},
+    Task,
 };
 use crate::{
-    events::tasks::{TaskListEventContent, TaskListUpdateBuilder, TaskListUpdateEventContent},
+    events::{
+        tasks::{
+            SpecialTaskListRole, TaskListEventContent, TaskListUpdateBuilder,
+            TaskListUpdateEventContent, TaskState,
+        },
+        Date,
+    },
     statics::KEYS,
     Result,
 };
@@ -17,6 +28,103 @@ use crate::{
 pub struct TaskStats {
     has_tasks: bool,
     tasks_count: u32,
+
+    /// the tasks currently in this list and their workflow state
+    #[getter(skip)]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    tasks: BTreeMap<OwnedEventId, Option<String>>,
+
+    /// the time logged on the tasks in this list, per member
+    #[getter(skip)]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    time_spent: BTreeMap<OwnedEventId, BTreeMap<OwnedUserId, u64>>,
+
+    /// the estimates of the tasks in this list
+    #[getter(skip)]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    estimates: BTreeMap<OwnedEventId, u32>,
+
+    /// when the tasks in this list have been created and done
+    #[getter(skip)]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    done_histories: BTreeMap<OwnedEventId, DoneHistory>,
+}
+
+impl TaskStats {
+    /// How many tasks are in the given workflow state, or have none
+    pub fn tasks_in_state(&self, state: Option<&str>) -> u32 {
+        self.tasks
+            .values()
+            .filter(|s| s.as_deref() == state)
+            .count() as u32
+    }
+
+    /// How much time has been logged on all tasks, in seconds
+    pub fn time_spent_seconds(&self) -> u64 {
+        self.time_spent.values().flat_map(|t| t.values()).sum()
+    }
+
+    /// How much time the member logged on all tasks, in seconds
+    pub fn time_spent_by(&self, user_id: &UserId) -> u64 {
+        self.time_spent
+            .values()
+            .filter_map(|t| t.get(user_id))
+            .sum()
+    }
+
+    /// How much time each member logged on all tasks, in seconds
+    pub fn time_spent_per_user(&self) -> BTreeMap<OwnedUserId, u64> {
+        let mut per_user = BTreeMap::new();
+        for (user_id, seconds) in self.time_spent.values().flatten() {
+            *per_user.entry(user_id.clone()).or_default() += seconds;
+        }
+        per_user
+    }
+
+    /// The estimates of all tasks added up, in seconds
+    pub fn estimate_seconds(&self) -> u64 {
+        self.estimates.values().map(|s| u64::from(*s)).sum()
+    }
+
+    /// The open and done counts at the end of every day from `start` to
+    /// `end`, days ending at midnight in the given time zone
+    pub fn burndown(&self, start: Date, end: Date, tz: Tz) -> Vec<BurndownDay> {
+        burndown(self.done_histories.values(), start, end, tz)
+    }
+
+    fn add_task(&mut self, task: &Task) -> bool {
+        let task_id = task.event_id().to_owned();
+        let state = task.state.clone();
+        let previous = self.tasks.insert(task_id.clone(), state.clone());
+        let time_spent = task.time_spent_per_user();
+        if time_spent.is_empty() {
+            self.time_spent.remove(&task_id);
+        } else {
+            self.time_spent.insert(task_id.clone(), time_spent);
+        }
+        self.done_histories
+            .insert(task_id.clone(), task.done_history());
+        match task.estimate_seconds {
+            Some(estimate) => self.estimates.insert(task_id, estimate),
+            None => self.estimates.remove(&task_id),
+        };
+        self.refresh_counts();
+        previous != Some(state)
+    }
+
+    fn remove_task(&mut self, task_id: &EventId) -> bool {
+        let removed = self.tasks.remove(task_id).is_some();
+        self.time_spent.remove(task_id);
+        self.estimates.remove(task_id);
+        self.done_histories.remove(task_id);
+        self.refresh_counts();
+        removed
+    }
+
+    fn refresh_counts(&mut self) {
+        self.tasks_count = self.tasks.len() as u32;
+        self.has_tasks = !self.tasks.is_empty();
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -42,6 +150,46 @@ impl TaskList {
         &self.task_stats
     }
 
+    /// Whether this is the special list of the given role in its space
+    pub fn has_role(&self, role: SpecialTaskListRole) -> bool {
+        self.inner.role == Some(role)
+    }
+
+    /// Whether the workflow state is defined for this list
+    pub fn has_state(&self, key: &str) -> bool {
+        self.inner.states.iter().any(|s| s.key == key)
+    }
+
+    /// The workflow state of this list the given task state refers to
+    ///
+    /// None if the task has no state or one that isn’t defined (anymore).
+    pub fn state_for(&self, key: Option<&str>) -> Option<&TaskState> {
+        let key = key?;
+        self.inner.states.iter().find(|s| s.key == key)
+    }
+
+    /// How many tasks are in the given workflow state
+    ///
+    /// Tasks with a state that isn’t defined for this list count as having none.
+    pub fn tasks_count_in_state(&self, key: Option<&str>) -> u32 {
+        match key {
+            Some(key) => self.task_stats.tasks_in_state(Some(key)),
+            None => self
+                .task_stats
+                .tasks
+                .values()
+                .filter(|state| self.state_for(state.as_deref()).is_none())
+                .count() as u32,
+        }
+    }
+
+    /// The open and done counts of this list at the end of every day from
+    /// `start` to `end`, in the time zone of this list
+    pub fn burndown(&self, start: Date, end: Date) -> Vec<BurndownDay> {
+        let tz = self.inner.time_zone.unwrap_or(Tz::UTC);
+        self.task_stats.burndown(start, end, tz)
+    }
+
     pub fn tasks_key(&self) -> String {
         format!("{}::{}", self.meta.event_id, KEYS::TASKS::TASKS)
     }
@@ -109,7 +257,11 @@ impl ActerModel for TaskList {
     }
 
     async fn execute(self, store: &Store) -> Result<Vec<String>> {
-        default_model_execute(store, self.into()).await
+        let task_list_id = self.meta.event_id.clone();
+        let mut keys = default_model_execute(store, self.into()).await?;
+        // tasks might have arrived before their list
+        keys.extend(Task::sync_list_time_zone(store, &task_list_id).await?);
+        Ok(keys)
     }
 
     fn transition(&mut self, model: &AnyActerModel) -> Result<bool> {
@@ -117,11 +269,18 @@ impl ActerModel for TaskList {
             AnyActerModel::TaskListUpdate(update) => update.apply(&mut self.inner),
             AnyActerModel::Task(task) => {
                 let key = self.event_id().to_owned();
+                if *task.task_list_id.event_id != *key {
+                    trace!(?key, ?task, "task moved to another list");
+                    return Ok(self.task_stats.remove_task(task.event_id()));
+                }
                 trace!(?key, ?task, "adding task to list");
-                self.task_stats.tasks_count += 1;
-                self.task_stats.has_tasks = true;
+                self.task_stats.add_task(task);
+                // one of our tasks changed either way
                 Ok(true)
             }
+            AnyActerModel::RedactedActerModel(redacted) => {
+                Ok(self.task_stats.remove_task(redacted.event_id()))
+            }
             _ => {
                 warn!(?model, "Trying to transition with an unknown model");
                 Ok(false)
@@ -152,7 +311,13 @@ impl ActerModel for TaskListUpdate {
     }
 
     async fn execute(self, store: &Store) -> Result<Vec<String>> {
-        default_model_execute(store, self.into()).await
+        let task_list_id = self.inner.task_list.event_id.clone();
+        let changes_time_zone = self.inner.time_zone.is_some();
+        let mut keys = default_model_execute(store, self.into()).await?;
+        if changes_time_zone {
+            keys.extend(Task::sync_list_time_zone(store, &task_list_id).await?);
+        }
+        Ok(keys)
     }
 
     fn belongs_to(&self) -> Option<Vec<String>> {
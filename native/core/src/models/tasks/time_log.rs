@@ -0,0 +1,110 @@
+use matrix_sdk_base::ruma::{events::OriginalMessageLikeEvent, EventId, RoomId, UserId};
+use serde::{Deserialize, Serialize};
+use std::ops::Deref;
+
+use super::super::{default_model_execute, ActerModel, AnyActerModel, EventMeta, Store};
+use crate::{events::tasks::TaskTimeLogEventContent, Result};
+
+/// Time a member logged on a task
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TaskTimeLog {
+    inner: TaskTimeLogEventContent,
+    meta: EventMeta,
+}
+
+impl Deref for TaskTimeLog {
+    type Target = TaskTimeLogEventContent;
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl TaskTimeLog {
+    /// The time logged on the task, oldest entry first
+    pub async fn for_task(store: &Store, task_id: &EventId) -> Result<Vec<TaskTimeLog>> {
+        let mut entries: Vec<TaskTimeLog> = store
+            .get_list(&time_log_key_for(task_id))
+            .await?
+            .filter_map(|mdl| match mdl {
+                AnyActerModel::TaskTimeLog(m) => Some(m),
+                _ => None,
+            })
+            .collect();
+        entries.sort_by_key(|e| e.meta.origin_server_ts);
+        Ok(entries)
+    }
+
+    /// Who spent the time, the sender of the log
+    pub fn user_id(&self) -> &UserId {
+        &self.meta.sender
+    }
+
+    pub fn sender_str(&self) -> String {
+        self.meta.sender.to_string()
+    }
+
+    /// How long was worked on the task, in seconds
+    pub fn seconds(&self) -> u32 {
+        self.inner.seconds
+    }
+
+    pub fn note(&self) -> Option<String> {
+        self.inner.note.clone()
+    }
+
+    pub fn event_id_str(&self) -> String {
+        self.meta.event_id.to_string()
+    }
+
+    pub fn origin_server_ts(&self) -> u64 {
+        self.meta.origin_server_ts.get().into()
+    }
+}
+
+impl ActerModel for TaskTimeLog {
+    fn indizes(&self, _user_id: &UserId) -> Vec<String> {
+        vec![time_log_key_for(&self.inner.task.event_id)]
+    }
+
+    fn event_id(&self) -> &EventId {
+        &self.meta.event_id
+    }
+    fn room_id(&self) -> &RoomId {
+        &self.meta.room_id
+    }
+
+    async fn execute(self, store: &Store) -> Result<Vec<String>> {
+        default_model_execute(store, self.into()).await
+    }
+
+    fn belongs_to(&self) -> Option<Vec<String>> {
+        Some(vec![self.inner.task.event_id.to_string()])
+    }
+}
+
+impl From<OriginalMessageLikeEvent<TaskTimeLogEventContent>> for TaskTimeLog {
+    fn from(outer: OriginalMessageLikeEvent<TaskTimeLogEventContent>) -> Self {
+        let OriginalMessageLikeEvent {
+            content,
+            room_id,
+            event_id,
+            sender,
+            origin_server_ts,
+            ..
+        } = outer;
+        TaskTimeLog {
+            inner: content,
+            meta: EventMeta {
+                room_id,
+                event_id,
+                sender,
+                origin_server_ts,
+                redacted: None,
+            },
+        }
+    }
+}
+
+pub(super) fn time_log_key_for(task_id: &EventId) -> String {
+    format!("{task_id}::time_log")
+}
@@ -1,13 +1,37 @@
 use derive_builder::Builder;
 use matrix_sdk_base::ruma::{
-    user_id, EventId, MilliSecondsSinceUnixEpoch, OwnedEventId, OwnedRoomId, RoomId, UserId,
+    user_id, EventId, MilliSecondsSinceUnixEpoch, OwnedEventId, OwnedRoomId, RoomId, UInt, UserId,
 };
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use super::{default_model_execute, ActerModel, AnyActerModel, Capability, EventMeta};
+#[cfg(test)]
+use crate::executor::Executor;
 use crate::{store::Store, Result};
 
+/// An executor on a fresh store in memory, for `@test:example.org`
+#[cfg(test)]
+pub async fn fresh_executor() -> Result<Executor> {
+    use matrix_sdk::Client;
+    use matrix_sdk_base::{
+        ruma::api::MatrixVersion,
+        store::{MemoryStore, StoreConfig},
+    };
+
+    let config = StoreConfig::new("tests".to_owned()).state_store(MemoryStore::new());
+    let client = Client::builder()
+        .homeserver_url("http://localhost")
+        .server_versions([MatrixVersion::V1_5])
+        .store_config(config)
+        .build()
+        .await
+        .unwrap();
+
+    let store = Store::new_with_auth(client, user_id!("@test:example.org").to_owned()).await?;
+    Executor::new(store).await
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, Builder)]
 #[builder(build_fn(name = "derive_builder_build"))]
 pub struct TestModel {
@@ -42,6 +66,30 @@ impl TestModelBuilder {
         }
     }
 
+    /// A model from its JSON as kept in the store, like
+    /// `{"Rsvp": {"inner": …, "meta": …}}`
+    pub fn model_from_json(json: serde_json::Value) -> AnyActerModel {
+        serde_json::from_value(json).unwrap()
+    }
+
+    /// A calendar event with the given content
+    pub fn calendar_event(meta: EventMeta, inner: serde_json::Value) -> AnyActerModel {
+        Self::model_from_json(serde_json::json!({
+            "CalendarEvent": { "inner": inner, "meta": meta },
+        }))
+    }
+
+    /// An update to the calendar event with the given changes
+    pub fn calendar_event_update(event_id: &EventId, changes: serde_json::Value) -> AnyActerModel {
+        let mut inner = serde_json::json!({
+            "m.relates_to": { "rel_type": "global.acter.dev.update", "event_id": event_id },
+        });
+        extend_json(&mut inner, changes);
+        Self::model_from_json(serde_json::json!({
+            "CalendarEventUpdate": { "inner": inner, "meta": Self::fake_meta() },
+        }))
+    }
+
     pub fn build(&mut self) -> std::result::Result<TestModel, TestModelBuilderError> {
         if self.room_id.is_none() {
             let room_id = Uuid::new_v4().hyphenated().to_string();
@@ -91,3 +139,52 @@ impl ActerModel for TestModel {
         default_model_execute(store, self.into()).await
     }
 }
+
+/// RSVPs to one event, each sent a moment after the one before
+pub struct TestRsvps {
+    event_id: OwnedEventId,
+    ts: u32,
+}
+
+impl TestRsvps {
+    pub fn new(event_id: &EventId) -> Self {
+        TestRsvps {
+            event_id: event_id.to_owned(),
+            ts: 0,
+        }
+    }
+
+    pub fn rsvp(&mut self, sender: &str, status: &str) -> AnyActerModel {
+        self.rsvp_with(sender, status, serde_json::json!({}))
+    }
+
+    /// With further content, like the `guests`
+    pub fn rsvp_with(
+        &mut self,
+        sender: &str,
+        status: &str,
+        extra: serde_json::Value,
+    ) -> AnyActerModel {
+        self.ts += 1;
+        let mut meta = TestModelBuilder::fake_meta();
+        meta.sender = sender.try_into().unwrap();
+        meta.origin_server_ts = MilliSecondsSinceUnixEpoch(UInt::from(self.ts));
+        let mut inner = serde_json::json!({
+            "m.relates_to": {
+                "rel_type": "global.acter.dev.belongs_to",
+                "event_id": self.event_id,
+            },
+            "status": { "type": status },
+        });
+        extend_json(&mut inner, extra);
+        TestModelBuilder::model_from_json(serde_json::json!({
+            "Rsvp": { "inner": inner, "meta": meta },
+        }))
+    }
+}
+
+fn extend_json(object: &mut serde_json::Value, extra: serde_json::Value) {
+    if let (Some(object), serde_json::Value::Object(extra)) = (object.as_object_mut(), extra) {
+        object.extend(extra);
+    }
+}
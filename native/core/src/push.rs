@@ -11,5 +11,20 @@ pub fn default_rules() -> Vec<NewPushRule> {
             }],
             vec![Action::Notify],
         )),
+        // and when an event is called off
+        NewPushRule::Underride(NewConditionalPushRule::new(
+            "global.acter.dev.calendar_event.cancelled".to_owned(),
+            vec![
+                PushCondition::EventMatch {
+                    key: "type".to_owned(),
+                    pattern: "global.acter.dev.calendar_event.update".to_owned(),
+                },
+                PushCondition::EventMatch {
+                    key: "content.status".to_owned(),
+                    pattern: "cancelled".to_owned(),
+                },
+            ],
+            vec![Action::Notify],
+        )),
     ]
 }
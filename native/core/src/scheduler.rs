@@ -0,0 +1,217 @@
+use chrono::{DateTime, Utc};
+use std::{collections::HashSet, time::Duration};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{error, trace};
+
+use crate::{
+    executor::Executor,
+    models::{ActerModel, AnyActerModel},
+    statics::KEYS,
+    Result,
+};
+
+/// Check again after this long even if nothing is due, in case the clock jumped
+static MAX_WAIT: Duration = Duration::from_secs(60 * 60);
+
+/// Keeps the indizes of overdue and due soon tasks up to date as time passes
+///
+/// Tasks only move between those when their due time comes near or passes,
+/// which no event tells us about. The scheduler sleeps until the next such
+/// moment, updates the indizes and notifies their listeners, so the app can
+/// raise reminders locally.
+#[derive(Clone, Debug)]
+pub struct DueTasksScheduler {
+    executor: Executor,
+}
+
+impl DueTasksScheduler {
+    pub fn new(executor: Executor) -> Self {
+        DueTasksScheduler { executor }
+    }
+
+    /// Update the due indizes for `now` and notify those that changed
+    ///
+    /// Returns when the next task becomes due soon or overdue, if any.
+    pub async fn tick(&self, now: DateTime<Utc>) -> Result<Option<DateTime<Utc>>> {
+        let store = self.executor.store();
+        let user_id = store.user_id();
+        let due_keys = [
+            KEYS::TASKS::MY_OVERDUE_TASKS,
+            KEYS::TASKS::MY_DUE_SOON_TASKS,
+        ];
+        let mut changed = Vec::new();
+        let mut next: Option<DateTime<Utc>> = None;
+        let mut tasks = HashSet::new();
+        for mdl in store.get_list(KEYS::TASKS::ALL_TASKS).await? {
+            let AnyActerModel::Task(task) = mdl else {
+                continue;
+            };
+            tasks.insert(task.event_id().to_string());
+            changed.extend(task.sync_due_indizes(store, &now));
+            if task.is_assigned(user_id) {
+                if let Some(change) = task.next_due_change_after(&now) {
+                    next = Some(next.map_or(change, |n| n.min(change)));
+                }
+            }
+        }
+        // redacted tasks aren’t listed anymore, drop them from the due indizes
+        for key in due_keys {
+            for mdl in store.get_list(key).await? {
+                let mdl_key = mdl.event_id().to_string();
+                if !tasks.contains(&mdl_key) && store.set_index_membership(key, &mdl_key, false) {
+                    changed.push(key.to_owned());
+                }
+            }
+        }
+        changed.sort();
+        changed.dedup();
+        self.executor.notify(changed);
+        Ok(next)
+    }
+
+    /// Tick whenever a task becomes due soon or overdue or any task changes
+    ///
+    /// Runs until the executor goes away, spawn it on the runtime.
+    pub async fn run(self) {
+        let mut tasks_changed = self.executor.subscribe(KEYS::TASKS::ALL_TASKS.to_owned());
+        loop {
+            let now = Utc::now();
+            let next = match self.tick(now).await {
+                Ok(next) => next,
+                Err(error) => {
+                    error!(?error, "updating due tasks failed");
+                    None
+                }
+            };
+            let wait = next
+                .map(|n| (n - now).to_std().unwrap_or_default().min(MAX_WAIT))
+                .unwrap_or(MAX_WAIT);
+            trace!(?next, ?wait, "waiting for tasks to become due");
+            tokio::select! {
+                _ = tokio::time::sleep(wait) => {}
+                res = tasks_changed.recv() => {
+                    if let Err(RecvError::Closed) = res {
+                        tasks_changed = self.executor.subscribe(KEYS::TASKS::ALL_TASKS.to_owned());
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{fresh_executor, Task, TestModelBuilder};
+
+    fn at(instant: &str) -> DateTime<Utc> {
+        instant.parse().unwrap()
+    }
+
+    async fn listed(executor: &Executor, key: &str) -> Result<usize> {
+        Ok(executor.store().get_list(key).await?.count())
+    }
+
+    #[tokio::test]
+    async fn moves_tasks_as_they_become_due() -> Result<()> {
+        let _ = env_logger::try_init();
+        let executor = fresh_executor().await?;
+        let json = serde_json::json!({
+            "inner": {
+                "title": "Hand in the report",
+                "due_date": "2024-05-03",
+                "m.relates_to": {
+                    "rel_type": "global.acter.dev.belongs_to",
+                    "event_id": "$tasklist",
+                },
+            },
+            "meta": TestModelBuilder::fake_meta(),
+            "assignees": ["@test:example.org"],
+        });
+        let task: Task = serde_json::from_value(json.clone()).unwrap();
+        let task_id = task.event_id().to_owned();
+        executor.store().save(task.into()).await?;
+        let scheduler = DueTasksScheduler::new(executor.clone());
+
+        let mut due_soon = executor.subscribe(KEYS::TASKS::MY_DUE_SOON_TASKS.to_owned());
+        let next = scheduler.tick(at("2024-05-01T12:00:00Z")).await?;
+        assert_eq!(listed(&executor, KEYS::TASKS::MY_OVERDUE_TASKS).await?, 0);
+        assert_eq!(listed(&executor, KEYS::TASKS::MY_DUE_SOON_TASKS).await?, 0);
+        assert_eq!(next, Some(at("2024-05-03T00:00:00Z")));
+
+        let next = scheduler.tick(at("2024-05-03T08:00:00Z")).await?;
+        assert!(due_soon.try_recv().is_ok(), "due soon wasn’t notified");
+        let mut listed_soon = executor
+            .store()
+            .get_list(KEYS::TASKS::MY_DUE_SOON_TASKS)
+            .await?;
+        assert_eq!(
+            listed_soon.next().map(|t| t.event_id().to_owned()),
+            Some(task_id)
+        );
+        assert_eq!(next, Some(at("2024-05-04T00:00:00Z")));
+
+        let mut overdue = executor.subscribe(KEYS::TASKS::MY_OVERDUE_TASKS.to_owned());
+        let next = scheduler.tick(at("2024-05-04T01:00:00Z")).await?;
+        assert!(overdue.try_recv().is_ok(), "overdue wasn’t notified");
+        assert_eq!(listed(&executor, KEYS::TASKS::MY_OVERDUE_TASKS).await?, 1);
+        assert_eq!(listed(&executor, KEYS::TASKS::MY_DUE_SOON_TASKS).await?, 0);
+        assert_eq!(next, None);
+
+        // saving doesn’t touch the due indizes, the next tick does
+        let mut done = json;
+        done["inner"]["progress_percent"] = 100.into();
+        let done: Task = serde_json::from_value(done).unwrap();
+        executor.store().save(done.into()).await?;
+        assert_eq!(listed(&executor, KEYS::TASKS::MY_OVERDUE_TASKS).await?, 1);
+        scheduler.tick(at("2024-05-04T02:00:00Z")).await?;
+        assert_eq!(listed(&executor, KEYS::TASKS::MY_OVERDUE_TASKS).await?, 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn done_update_leaves_overdue_right_away() -> Result<()> {
+        let _ = env_logger::try_init();
+        let executor = fresh_executor().await?;
+        let task_list = TestModelBuilder::default().simple().build().unwrap();
+        let task_list_id = task_list.event_id().to_owned();
+        executor.handle(task_list.into()).await?;
+        let task: Task = serde_json::from_value(serde_json::json!({
+            "inner": {
+                "title": "Hand in the report",
+                "due_date": "2024-05-03",
+                "m.relates_to": {
+                    "rel_type": "global.acter.dev.belongs_to",
+                    "event_id": task_list_id,
+                },
+            },
+            "meta": TestModelBuilder::fake_meta(),
+            "assignees": ["@test:example.org"],
+        }))
+        .unwrap();
+        let task_id = task.event_id().to_owned();
+        executor.store().save(task.into()).await?;
+        let scheduler = DueTasksScheduler::new(executor.clone());
+        scheduler.tick(at("2024-05-04T01:00:00Z")).await?;
+        assert_eq!(listed(&executor, KEYS::TASKS::MY_OVERDUE_TASKS).await?, 1);
+
+        let mut overdue = executor.subscribe(KEYS::TASKS::MY_OVERDUE_TASKS.to_owned());
+        let done: AnyActerModel = serde_json::from_value(serde_json::json!({
+            "TaskUpdate": {
+                "inner": {
+                    "m.relates_to": {
+                        "rel_type": "global.acter.dev.update",
+                        "event_id": task_id,
+                    },
+                    "progress_percent": 100,
+                },
+                "meta": TestModelBuilder::fake_meta(),
+            },
+        }))
+        .unwrap();
+        executor.handle(done).await?;
+        assert!(overdue.try_recv().is_ok(), "overdue wasn’t notified");
+        assert_eq!(listed(&executor, KEYS::TASKS::MY_OVERDUE_TASKS).await?, 0);
+        Ok(())
+    }
+}
@@ -13,6 +13,11 @@ pub mod KEYS {
         pub static TASKS: &str = "tasks";
         pub static MY_OPEN_TASKS: &str = "tasks_my_open";
         pub static MY_DONE_TASKS: &str = "tasks_my_done";
+        pub static ALL_TASKS: &str = "tasks_all";
+        pub static MY_OVERDUE_TASKS: &str = "tasks_my_overdue";
+        pub static MY_DUE_SOON_TASKS: &str = "tasks_my_due_soon";
+        pub static SUBTASKS: &str = "subtasks";
+        pub static BLOCKED_BY: &str = "blocked_by";
     }
     pub static CALENDAR: &str = "calendar";
     pub static NEWS: &str = "news";
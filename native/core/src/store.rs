@@ -21,7 +21,7 @@ pub struct Store {
 
 static ALL_MODELS_KEY: &str = "ACTER::ALL";
 static DB_VERSION_KEY: &str = "ACTER::DB_VERSION";
-static CURRENT_DB_VERSION: u32 = 1;
+static CURRENT_DB_VERSION: u32 = 4;
 
 type ModelKeysAndIndizes = (Vec<String>, Vec<String>);
 
@@ -279,6 +279,35 @@ impl Store {
             .collect())
     }
 
+    /// Add the model to or remove it from the index, without saving anything
+    ///
+    /// Meant for indizes that change as time passes rather than by events.
+    /// Returns whether the index changed.
+    pub fn set_index_membership(&self, index: &str, model_key: &str, member: bool) -> bool {
+        match self.indizes.entry(index.to_owned()) {
+            Entry::Occupied(mut o) => {
+                let keys = o.get_mut();
+                let found = keys.iter().position(|k| k == model_key);
+                match (found, member) {
+                    (Some(pos), false) => {
+                        keys.remove(pos);
+                        true
+                    }
+                    (None, true) => {
+                        keys.push(model_key.to_owned());
+                        true
+                    }
+                    _ => false,
+                }
+            }
+            Entry::Vacant(v) if member => {
+                v.insert_entry(vec![model_key.to_owned()]);
+                true
+            }
+            Entry::Vacant(_) => false,
+        }
+    }
+
     pub async fn save(&self, mdl: AnyActerModel) -> Result<Vec<String>> {
         let keys = self.save_model_inner(mdl).await?;
         self.sync().await?; // FIXME: should we really run this every time?
@@ -0,0 +1,117 @@
+//! Reading the JSON exports of other task managers
+//!
+//! The exports are turned into plain task lists, tasks and comments which the
+//! client then creates one event after the other.
+
+use chrono::{DateTime, NaiveDateTime, Timelike, Utc};
+
+use crate::{events::Date, Result};
+
+mod todoist;
+mod trello;
+
+/// Task lists read from an export, ready to be created in a space
+#[derive(Clone, Debug, Default)]
+pub struct TaskImport {
+    pub task_lists: Vec<ImportedTaskList>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ImportedTaskList {
+    pub name: String,
+    pub description: Option<String>,
+    pub tasks: Vec<ImportedTask>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ImportedTask {
+    pub title: String,
+    /// Markdown, with the checklists of the original task as task lists
+    pub description: Option<String>,
+    pub due_date: Option<Date>,
+    pub utc_due_time_of_day: Option<i32>,
+    pub due_time_of_day: Option<i32>,
+    pub done: bool,
+    pub keywords: Vec<String>,
+    pub comments: Vec<ImportedComment>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ImportedComment {
+    pub author: Option<String>,
+    pub posted_at: Option<DateTime<Utc>>,
+    pub body: String,
+}
+
+impl TaskImport {
+    /// Read the JSON export of a Trello board
+    pub fn from_trello_json(data: &str) -> Result<Self> {
+        trello::parse(data)
+    }
+
+    /// Read the JSON export of a Todoist account, as returned by its sync API
+    pub fn from_todoist_json(data: &str) -> Result<Self> {
+        todoist::parse(data)
+    }
+
+    pub fn tasks_count(&self) -> u32 {
+        self.task_lists.iter().map(|l| l.tasks.len() as u32).sum()
+    }
+
+    /// How many items are created, task lists, tasks and comments together
+    pub fn total(&self) -> u32 {
+        let comments: u32 = self
+            .task_lists
+            .iter()
+            .flat_map(|l| l.tasks.iter())
+            .map(|t| t.comments.len() as u32)
+            .sum();
+        self.task_lists.len() as u32 + self.tasks_count() + comments
+    }
+}
+
+impl ImportedTask {
+    fn due_at_utc(&mut self, due: DateTime<Utc>) {
+        self.due_date = Some(due.date_naive());
+        self.utc_due_time_of_day = Some(due.time().num_seconds_from_midnight() as i32);
+    }
+
+    fn due_at_local(&mut self, due: NaiveDateTime) {
+        self.due_date = Some(due.date());
+        self.due_time_of_day = Some(due.time().num_seconds_from_midnight() as i32);
+    }
+}
+
+impl ImportedComment {
+    /// The comment as we post it, crediting the original author
+    pub fn text(&self) -> String {
+        let author = self.author.as_deref().unwrap_or("Someone");
+        match &self.posted_at {
+            Some(posted_at) => format!(
+                "{author} wrote on {}:\n\n{}",
+                posted_at.format("%Y-%m-%d"),
+                self.body
+            ),
+            None => format!("{author} wrote:\n\n{}", self.body),
+        }
+    }
+}
+
+/// Append a markdown checklist to the description
+fn append_checklist<'a>(
+    description: &mut String,
+    name: Option<&str>,
+    items: impl IntoIterator<Item = (&'a str, bool)>,
+) {
+    if !description.is_empty() {
+        description.push_str("\n\n");
+    }
+    if let Some(name) = name {
+        description.push_str(&format!("**{name}**\n\n"));
+    }
+    let lines: Vec<String> = items
+        .into_iter()
+        .map(|(item, done)| format!("- [{}] {item}", if done { "x" } else { " " }))
+        .collect();
+    description.push_str(&lines.join("\n"));
+}
@@ -0,0 +1,212 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use serde::Deserialize;
+
+use super::{append_checklist, ImportedComment, ImportedTask, ImportedTaskList, TaskImport};
+use crate::Result;
+
+#[derive(Deserialize)]
+struct Export {
+    #[serde(default)]
+    projects: Vec<Project>,
+    #[serde(default)]
+    items: Vec<Item>,
+    #[serde(default)]
+    notes: Vec<Note>,
+    #[serde(default)]
+    collaborators: Vec<Collaborator>,
+}
+
+#[derive(Deserialize)]
+struct Project {
+    id: String,
+    name: String,
+    #[serde(default)]
+    is_archived: bool,
+    #[serde(default)]
+    child_order: i64,
+}
+
+#[derive(Deserialize)]
+struct Item {
+    id: String,
+    project_id: String,
+    content: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    due: Option<Due>,
+    #[serde(default)]
+    labels: Vec<String>,
+    #[serde(default)]
+    checked: bool,
+    #[serde(default)]
+    parent_id: Option<String>,
+    #[serde(default)]
+    child_order: i64,
+}
+
+#[derive(Deserialize)]
+struct Due {
+    /// A day, a floating local time or a time in UTC
+    date: String,
+}
+
+#[derive(Deserialize)]
+struct Note {
+    item_id: String,
+    content: String,
+    #[serde(default)]
+    posted_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    posted_uid: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Collaborator {
+    id: String,
+    full_name: String,
+}
+
+/// Every project becomes a task list, subtasks end up as checklist of their
+/// parent
+pub(super) fn parse(data: &str) -> Result<TaskImport> {
+    let mut export: Export = serde_json::from_str(data)?;
+    export.projects.sort_by_key(|p| p.child_order);
+    export.items.sort_by_key(|i| i.child_order);
+    export.notes.sort_by_key(|n| n.posted_at);
+
+    let task_lists = export
+        .projects
+        .iter()
+        .filter(|project| !project.is_archived)
+        .map(|project| ImportedTaskList {
+            name: project.name.clone(),
+            description: None,
+            tasks: export
+                .items
+                .iter()
+                .filter(|item| item.project_id == project.id && item.parent_id.is_none())
+                .map(|item| task_for_item(item, &export))
+                .collect(),
+        })
+        .collect();
+    Ok(TaskImport { task_lists })
+}
+
+fn task_for_item(item: &Item, export: &Export) -> ImportedTask {
+    let mut description = item.description.clone();
+    let subitems: Vec<&Item> = export
+        .items
+        .iter()
+        .filter(|i| i.parent_id.as_ref() == Some(&item.id))
+        .collect();
+    if !subitems.is_empty() {
+        append_checklist(
+            &mut description,
+            None,
+            subitems.iter().map(|i| (i.content.as_str(), i.checked)),
+        );
+    }
+    let mut task = ImportedTask {
+        title: item.content.clone(),
+        description: (!description.is_empty()).then_some(description),
+        done: item.checked,
+        keywords: item.labels.clone(),
+        comments: export
+            .notes
+            .iter()
+            .filter(|n| n.item_id == item.id)
+            .map(|n| ImportedComment {
+                author: n.posted_uid.as_ref().and_then(|uid| {
+                    export
+                        .collaborators
+                        .iter()
+                        .find(|c| &c.id == uid)
+                        .map(|c| c.full_name.clone())
+                }),
+                posted_at: n.posted_at,
+                body: n.content.clone(),
+            })
+            .collect(),
+        ..Default::default()
+    };
+    if let Some(due) = &item.due {
+        if let Ok(due) = due.date.parse::<DateTime<Utc>>() {
+            task.due_at_utc(due);
+        } else if let Ok(due) = due.date.parse::<NaiveDateTime>() {
+            task.due_at_local(due);
+        } else if let Ok(due) = due.date.parse::<NaiveDate>() {
+            task.due_date = Some(due);
+        }
+    }
+    task
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::Date;
+
+    #[test]
+    fn reads_projects() {
+        let data = serde_json::json!({
+            "projects": [
+                { "id": "p1", "name": "Household", "child_order": 2 },
+                { "id": "p2", "name": "Work", "child_order": 1 },
+                { "id": "p3", "name": "Archive", "is_archived": true },
+            ],
+            "items": [
+                {
+                    "id": "i1",
+                    "project_id": "p1",
+                    "content": "Clean the kitchen",
+                    "due": { "date": "2024-05-03T18:00:00" },
+                    "labels": ["chores"],
+                    "child_order": 1,
+                },
+                { "id": "i2", "project_id": "p1", "content": "Wipe the counters", "parent_id": "i1", "checked": true },
+                { "id": "i3", "project_id": "p1", "content": "Mop the floor", "parent_id": "i1", "child_order": 1 },
+                {
+                    "id": "i4",
+                    "project_id": "p2",
+                    "content": "Send the report",
+                    "description": "To the whole team",
+                    "due": { "date": "2024-05-06" },
+                },
+                { "id": "i5", "project_id": "p2", "content": "Call Carla", "due": { "date": "2024-05-07T09:00:00Z" } },
+            ],
+            "notes": [
+                { "item_id": "i4", "content": "Draft is in the drive", "posted_at": "2024-05-01T10:00:00Z", "posted_uid": "u1" },
+            ],
+            "collaborators": [{ "id": "u1", "full_name": "Alice" }],
+        })
+        .to_string();
+
+        let import = TaskImport::from_todoist_json(&data).unwrap();
+        let names: Vec<_> = import.task_lists.iter().map(|l| l.name.as_str()).collect();
+        assert_eq!(names, ["Work", "Household"]);
+        assert_eq!(import.tasks_count(), 3);
+        assert_eq!(import.total(), 2 + 3 + 1);
+
+        let report = &import.task_lists[0].tasks[0];
+        assert_eq!(report.description.as_deref(), Some("To the whole team"));
+        assert_eq!(report.due_date, Date::from_ymd_opt(2024, 5, 6));
+        assert_eq!(report.due_time_of_day, None);
+        assert_eq!(
+            report.comments[0].text(),
+            "Alice wrote on 2024-05-01:\n\nDraft is in the drive"
+        );
+
+        let call = &import.task_lists[0].tasks[1];
+        assert_eq!(call.utc_due_time_of_day, Some(9 * 3600));
+
+        let kitchen = &import.task_lists[1].tasks[0];
+        assert_eq!(
+            kitchen.description.as_deref(),
+            Some("- [x] Wipe the counters\n- [ ] Mop the floor")
+        );
+        assert_eq!(kitchen.due_date, Date::from_ymd_opt(2024, 5, 3));
+        assert_eq!(kitchen.due_time_of_day, Some(18 * 3600));
+        assert_eq!(kitchen.keywords, ["chores"]);
+    }
+}
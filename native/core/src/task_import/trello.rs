@@ -0,0 +1,273 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use super::{append_checklist, ImportedComment, ImportedTask, ImportedTaskList, TaskImport};
+use crate::Result;
+
+#[derive(Deserialize)]
+struct Board {
+    #[serde(default)]
+    lists: Vec<List>,
+    #[serde(default)]
+    cards: Vec<Card>,
+    #[serde(default)]
+    checklists: Vec<Checklist>,
+    #[serde(default)]
+    actions: Vec<Action>,
+}
+
+#[derive(Deserialize)]
+struct List {
+    id: String,
+    name: String,
+    #[serde(default)]
+    closed: bool,
+    #[serde(default)]
+    pos: f64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Card {
+    id: String,
+    name: String,
+    #[serde(default)]
+    desc: String,
+    id_list: String,
+    #[serde(default)]
+    closed: bool,
+    #[serde(default)]
+    due: Option<DateTime<Utc>>,
+    #[serde(default)]
+    due_complete: bool,
+    #[serde(default)]
+    labels: Vec<Label>,
+    #[serde(default)]
+    pos: f64,
+}
+
+#[derive(Deserialize)]
+struct Label {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    color: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Checklist {
+    id_card: String,
+    name: String,
+    #[serde(default)]
+    check_items: Vec<CheckItem>,
+    #[serde(default)]
+    pos: f64,
+}
+
+#[derive(Deserialize)]
+struct CheckItem {
+    name: String,
+    state: String,
+    #[serde(default)]
+    pos: f64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Action {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    data: ActionData,
+    #[serde(default)]
+    date: Option<DateTime<Utc>>,
+    #[serde(default)]
+    member_creator: Option<Member>,
+}
+
+#[derive(Default, Deserialize)]
+struct ActionData {
+    #[serde(default)]
+    text: Option<String>,
+    #[serde(default)]
+    card: Option<CardRef>,
+}
+
+#[derive(Deserialize)]
+struct CardRef {
+    id: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Member {
+    #[serde(default)]
+    full_name: Option<String>,
+    #[serde(default)]
+    username: Option<String>,
+}
+
+/// Every open list becomes a task list, archived lists and cards are left out
+pub(super) fn parse(data: &str) -> Result<TaskImport> {
+    let mut board: Board = serde_json::from_str(data)?;
+    board.lists.sort_by(|a, b| a.pos.total_cmp(&b.pos));
+    board.cards.sort_by(|a, b| a.pos.total_cmp(&b.pos));
+    board.checklists.sort_by(|a, b| a.pos.total_cmp(&b.pos));
+    // exports list the newest actions first
+    board.actions.sort_by_key(|a| a.date);
+
+    let task_lists = board
+        .lists
+        .iter()
+        .filter(|list| !list.closed)
+        .map(|list| ImportedTaskList {
+            name: list.name.clone(),
+            description: None,
+            tasks: board
+                .cards
+                .iter()
+                .filter(|card| card.id_list == list.id && !card.closed)
+                .map(|card| task_for_card(card, &board))
+                .collect(),
+        })
+        .collect();
+    Ok(TaskImport { task_lists })
+}
+
+fn task_for_card(card: &Card, board: &Board) -> ImportedTask {
+    let mut description = card.desc.clone();
+    for checklist in board.checklists.iter().filter(|c| c.id_card == card.id) {
+        let mut items: Vec<&CheckItem> = checklist.check_items.iter().collect();
+        items.sort_by(|a, b| a.pos.total_cmp(&b.pos));
+        append_checklist(
+            &mut description,
+            Some(&checklist.name),
+            items
+                .into_iter()
+                .map(|i| (i.name.as_str(), i.state == "complete")),
+        );
+    }
+    let mut task = ImportedTask {
+        title: card.name.clone(),
+        description: (!description.is_empty()).then_some(description),
+        done: card.due_complete,
+        keywords: card
+            .labels
+            .iter()
+            .filter_map(|l| {
+                if l.name.is_empty() {
+                    l.color.clone()
+                } else {
+                    Some(l.name.clone())
+                }
+            })
+            .collect(),
+        comments: board
+            .actions
+            .iter()
+            .filter(|a| a.kind == "commentCard")
+            .filter(|a| a.data.card.as_ref().map(|c| &c.id) == Some(&card.id))
+            .filter_map(|a| {
+                Some(ImportedComment {
+                    author: a
+                        .member_creator
+                        .as_ref()
+                        .and_then(|m| m.full_name.clone().or_else(|| m.username.clone())),
+                    posted_at: a.date,
+                    body: a.data.text.clone()?,
+                })
+            })
+            .collect(),
+        ..Default::default()
+    };
+    if let Some(due) = card.due {
+        task.due_at_utc(due);
+    }
+    task
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::Date;
+
+    #[test]
+    fn reads_board() {
+        let data = serde_json::json!({
+            "name": "Summer fair",
+            "lists": [
+                { "id": "l2", "name": "Doing", "pos": 2 },
+                { "id": "l1", "name": "To do", "pos": 1 },
+                { "id": "l3", "name": "Old stuff", "closed": true, "pos": 3 },
+            ],
+            "cards": [
+                {
+                    "id": "c1",
+                    "name": "Book venue",
+                    "desc": "The town hall, if possible",
+                    "idList": "l1",
+                    "due": "2024-05-03T10:30:00.000Z",
+                    "labels": [{ "name": "urgent", "color": "red" }, { "name": "", "color": "green" }],
+                    "pos": 1,
+                },
+                { "id": "c2", "name": "Old card", "idList": "l1", "closed": true },
+                { "id": "c3", "name": "Print flyers", "idList": "l2", "dueComplete": true },
+            ],
+            "checklists": [{
+                "idCard": "c1",
+                "name": "Before booking",
+                "checkItems": [
+                    { "name": "Ask for the price", "state": "complete", "pos": 1 },
+                    { "name": "Check the date", "state": "incomplete", "pos": 2 },
+                ],
+            }],
+            "actions": [
+                {
+                    "type": "commentCard",
+                    "date": "2024-04-02T08:00:00.000Z",
+                    "data": { "text": "They called back", "card": { "id": "c1" } },
+                    "memberCreator": { "fullName": "Alice" },
+                },
+                {
+                    "type": "commentCard",
+                    "date": "2024-04-01T08:00:00.000Z",
+                    "data": { "text": "I’ll call them", "card": { "id": "c1" } },
+                    "memberCreator": { "username": "bob" },
+                },
+                { "type": "updateCard", "data": { "card": { "id": "c1" } } },
+            ],
+        })
+        .to_string();
+
+        let import = TaskImport::from_trello_json(&data).unwrap();
+        let names: Vec<_> = import.task_lists.iter().map(|l| l.name.as_str()).collect();
+        assert_eq!(names, ["To do", "Doing"]);
+        assert_eq!(import.tasks_count(), 2);
+        assert_eq!(import.total(), 2 + 2 + 2);
+
+        let venue = &import.task_lists[0].tasks[0];
+        assert_eq!(venue.title, "Book venue");
+        assert_eq!(
+            venue.description.as_deref(),
+            Some(
+                "The town hall, if possible\n\n**Before booking**\n\n\
+                 - [x] Ask for the price\n- [ ] Check the date"
+            )
+        );
+        assert_eq!(venue.due_date, Date::from_ymd_opt(2024, 5, 3));
+        assert_eq!(venue.utc_due_time_of_day, Some(10 * 3600 + 30 * 60));
+        assert_eq!(venue.keywords, ["urgent", "green"]);
+        assert!(!venue.done);
+        let comments: Vec<_> = venue.comments.iter().map(|c| c.text()).collect();
+        assert_eq!(
+            comments,
+            [
+                "bob wrote on 2024-04-01:\n\nI’ll call them",
+                "Alice wrote on 2024-04-02:\n\nThey called back"
+            ]
+        );
+
+        assert!(import.task_lists[1].tasks[0].done);
+    }
+}
@@ -403,3 +403,112 @@ async fn task_list_external_link() -> Result<()> {
     assert_eq!(ext_url.fragment().expect("must have fragment"), &path);
     Ok(())
 }
+
+#[tokio::test]
+async fn task_subtasks_smoketest() -> Result<()> {
+    let _ = env_logger::try_init();
+    let (mut user, room_id) = random_user_with_random_space("subtasks_smoketest").await?;
+
+    let state_sync = user.start_sync();
+    state_sync.await_has_synced_history().await?;
+
+    // wait for sync to catch up
+    let retry_strategy = FibonacciBackoff::from_millis(100).map(jitter).take(10);
+    let fetcher_client = user.clone();
+    let target_id = room_id.clone();
+    Retry::spawn(retry_strategy, move || {
+        let client = fetcher_client.clone();
+        let room_id = target_id.clone();
+        async move { client.space(room_id.to_string()).await }
+    })
+    .await?;
+
+    let space = user.space(room_id.to_string()).await?;
+
+    let task_list_id = {
+        let mut draft = space.task_list_draft()?;
+        draft.name("Campaign".to_owned());
+        draft.send().await?
+    };
+
+    let task_list_key = task_list_id.to_string();
+
+    let wait_for_space = space.clone();
+    let task_list = wait_for(move || {
+        let space = wait_for_space.clone();
+        let task_list_key = task_list_key.clone();
+        async move { Ok(space.task_list(task_list_key).await.ok()) }
+    })
+    .await?
+    .expect("freshly created Task List couldn’t be found");
+
+    let task_list_listener = task_list.subscribe();
+
+    let parent_id = task_list
+        .task_builder()?
+        .title("Prepare the rally".into())
+        .send()
+        .await?;
+
+    let retry_strategy = FibonacciBackoff::from_millis(500).map(jitter).take(10);
+    Retry::spawn(retry_strategy.clone(), || async {
+        if task_list_listener.is_empty() {
+            bail!("all still empty");
+        }
+        Ok(())
+    })
+    .await?;
+
+    let parent = task_list.task(parent_id.to_string()).await?;
+    assert!(!parent.has_subtasks());
+    assert_eq!(parent.subtasks_done_percent(), None);
+
+    let parent_listener = parent.subscribe();
+
+    let subtask_id = parent
+        .subtask_builder()?
+        .title("Print flyers".into())
+        .send()
+        .await?;
+
+    Retry::spawn(retry_strategy.clone(), || async {
+        if parent_listener.is_empty() {
+            bail!("all still empty");
+        }
+        Ok(())
+    })
+    .await?;
+
+    let parent = parent.refresh().await?;
+    assert!(parent.has_subtasks());
+    assert_eq!(parent.subtasks_count(), 1);
+    assert_eq!(parent.done_subtasks_count(), 0);
+    assert_eq!(parent.subtasks_done_percent(), Some(0));
+
+    let subtasks = parent.subtasks().await?;
+    assert_eq!(subtasks.len(), 1);
+    assert_eq!(subtasks[0].event_id(), subtask_id);
+    assert_eq!(
+        subtasks[0].parent_task_id_str(),
+        Some(parent_id.to_string())
+    );
+
+    let parent_listener = parent.subscribe();
+
+    subtasks[0].update_builder()?.mark_done().send().await?;
+
+    Retry::spawn(retry_strategy.clone(), || async {
+        if parent_listener.is_empty() {
+            bail!("all still empty");
+        }
+        Ok(())
+    })
+    .await?;
+
+    let parent = parent.refresh().await?;
+    assert_eq!(parent.subtasks_count(), 1);
+    assert_eq!(parent.done_subtasks_count(), 1);
+    assert_eq!(parent.subtasks_done_percent(), Some(100));
+
+    Ok(())
+}